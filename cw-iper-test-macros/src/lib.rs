@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
+
 use proc_macro::TokenStream;
-use proc_macro2::TokenTree;
+use proc_macro2::{Ident, Span, TokenTree};
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput, Expr, Lit, Meta};
+use syn::{
+    parse_macro_input, DeriveInput, Expr, FnArg, ImplItem, ItemImpl, Lit, LitStr, Meta, Path, Type,
+};
 
 /// Implements following traits from `cw_iper_test` crate:
 /// - `IbcPortInterface`
@@ -144,25 +148,674 @@ pub fn urls(_attr: proc_macro::TokenStream, input: proc_macro::TokenStream) -> T
     TokenStream::from(expanded)
 }
 
+/// Hooks of `#[ibc_application]` that can be implemented by annotated methods.
+const IBC_APPLICATION_HOOKS: [&str; 11] = [
+    "handle_outgoing_packet",
+    "packet_receive",
+    "packet_ack",
+    "packet_timeout",
+    "open_channel",
+    "channel_connect",
+    "init",
+    "stargate_msg",
+    "stargate_query",
+    "sudo",
+    "stargate_sudo",
+];
+
+/// Implements following traits from `cw_iper_test` crate on the type of an `impl` block:
+/// - `IbcPortInterface`, from the `port` attribute;
+/// - `IbcApplication`;
+/// - `StargateUrls`, `StargateName` and `StargateApplication`, only if the `stargate` attribute is set.
+///
+/// Every hook has a default implementation:
+/// - `handle_outgoing_packet`, `stargate_msg` and `stargate_query` return an `IperError::UnsupportedMsg`;
+/// - `packet_receive` fails without `ack`;
+/// - `packet_ack`, `packet_timeout`, `open_channel` and `channel_connect` return an empty `AppResponse`;
+/// - `init` does nothing;
+/// - `sudo` and `stargate_sudo` use the default implementation of the traits.
+///
+/// To override a hook, annotate a method of the `impl` block with the hook name.
+/// The method must have the same signature of the hook in the trait:
+///
+/// ```ignore
+/// #[derive(Default, Clone)]
+/// pub struct Counter;
+///
+/// #[ibc_application(
+///     port = "counter",
+///     stargate(name = "counter", query_urls = CounterQueryUrls, msgs_urls = CounterMsgUrls)
+/// )]
+/// impl Counter {
+///     #[packet_receive]
+///     fn receive(
+///         &self,
+///         api: &dyn Api,
+///         block: &BlockInfo,
+///         router: &RouterWrapper,
+///         storage: Rc<RefCell<&mut dyn Storage>>,
+///         msg: IbcPacketReceiveMsg,
+///     ) -> InfallibleResult<PacketReceiveOk, PacketReceiveFailing> {
+///         ...
+///     }
+///
+///     #[init]
+///     fn init(&self, api: &MockApiBech32, storage: &mut dyn Storage) -> AppResult<()> {
+///         ...
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn ibc_application(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let mut port: Option<LitStr> = None;
+    let mut stargate: Option<(LitStr, Path, Path)> = None;
+
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("port") {
+            port = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("stargate") {
+            let mut name = None;
+            let mut query = None;
+            let mut msgs = None;
+
+            meta.parse_nested_meta(|nested| {
+                if nested.path.is_ident("name") {
+                    name = Some(nested.value()?.parse::<LitStr>()?);
+                } else if nested.path.is_ident("query_urls") {
+                    query = Some(nested.value()?.parse::<Path>()?);
+                } else if nested.path.is_ident("msgs_urls") {
+                    msgs = Some(nested.value()?.parse::<Path>()?);
+                } else {
+                    return Err(nested.error("unsupported stargate property"));
+                }
+                Ok(())
+            })?;
+
+            stargate = Some((
+                name.ok_or_else(|| meta.error("stargate name not found"))?,
+                query.ok_or_else(|| meta.error("stargate query_urls not found"))?,
+                msgs.ok_or_else(|| meta.error("stargate msgs_urls not found"))?,
+            ));
+            Ok(())
+        } else {
+            Err(meta.error("unsupported ibc_application property"))
+        }
+    });
+
+    parse_macro_input!(attr with parser);
+
+    let mut input = parse_macro_input!(input as ItemImpl);
+
+    let Some(port) = port else {
+        return syn::Error::new(
+            Span::call_site(),
+            "port not found. Requested in format #[ibc_application(port = \"port\")]",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let mut hooks: BTreeMap<String, Ident> = BTreeMap::new();
+
+    for item in &mut input.items {
+        if let ImplItem::Fn(method) = item {
+            let mut error = None;
+
+            method.attrs.retain(|attr| {
+                let Some(hook) = IBC_APPLICATION_HOOKS
+                    .iter()
+                    .find(|hook| attr.path().is_ident(hook))
+                else {
+                    return true;
+                };
+
+                if hooks
+                    .insert(hook.to_string(), method.sig.ident.clone())
+                    .is_some()
+                {
+                    error = Some(syn::Error::new_spanned(
+                        attr,
+                        format!("hook {} implemented twice", hook),
+                    ));
+                }
+
+                false
+            });
+
+            if let Some(error) = error {
+                return error.to_compile_error().into();
+            }
+        }
+    }
+
+    if stargate.is_none() {
+        for hook in ["stargate_msg", "stargate_query", "stargate_sudo"] {
+            if let Some(ident) = hooks.get(hook) {
+                return syn::Error::new_spanned(
+                    ident,
+                    format!("{} requires the stargate attribute", hook),
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
+    }
+
+    let prepath = prepath();
+
+    let self_ty = &input.self_ty;
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+
+    // Call the annotated method, if any, or use the default implementation
+    let hook = |name: &str, args: proc_macro2::TokenStream, default: proc_macro2::TokenStream| {
+        if let Some(ident) = hooks.get(name) {
+            quote! { self.#ident(#args) }
+        } else {
+            default
+        }
+    };
+
+    let handle_outgoing_packet = hook(
+        "handle_outgoing_packet",
+        quote! { api, block, sender, router, storage, msg, channel },
+        quote! {
+            Err(#prepath::IperError::UnsupportedMsg {
+                module: #port.to_string(),
+                msg: format!("{:?}", msg),
+            }
+            .into())
+        },
+    );
+
+    let packet_receive = hook(
+        "packet_receive",
+        quote! { api, block, router, storage, msg },
+        quote! {
+            #prepath::InfallibleResult::Err(#prepath::PacketReceiveFailing {
+                error: format!("packet_receive not implemented on port {}", #port),
+                ack: None,
+            })
+        },
+    );
+
+    let empty_response = quote! { Ok(#prepath::cw_multi_test::AppResponse::default()) };
+
+    let packet_ack = hook(
+        "packet_ack",
+        quote! { api, block, router, storage, msg },
+        empty_response.clone(),
+    );
+
+    let packet_timeout = hook(
+        "packet_timeout",
+        quote! { api, block, router, storage, msg },
+        empty_response.clone(),
+    );
+
+    let open_channel = hook(
+        "open_channel",
+        quote! { api, block, router, storage, msg },
+        empty_response.clone(),
+    );
+
+    let channel_connect = hook(
+        "channel_connect",
+        quote! { api, block, router, storage, msg },
+        empty_response,
+    );
+
+    let init = hook("init", quote! { api, storage }, quote! { Ok(()) });
+
+    let storage_ty =
+        quote! { ::std::rc::Rc<::std::cell::RefCell<&mut dyn ::cosmwasm_std::Storage>> };
+
+    // Hooks with a default implementation in the trait are implemented only if annotated
+    let sudo = hooks.get("sudo").map(|ident| {
+        quote! {
+            fn sudo(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                block: &::cosmwasm_std::BlockInfo,
+                router: &#prepath::RouterWrapper,
+                storage: #storage_ty,
+                msg: ::cosmwasm_std::Binary,
+            ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                self.#ident(api, block, router, storage, msg)
+            }
+        }
+    });
+
+    let stargate = stargate.map(|(name, query, msgs)| {
+        let stargate_msg = hook(
+            "stargate_msg",
+            quote! { api, storage, router, block, sender, type_url, data },
+            quote! {
+                Err(#prepath::IperError::UnsupportedMsg {
+                    module: #name.to_string(),
+                    msg: type_url,
+                }
+                .into())
+            },
+        );
+
+        let stargate_query = hook(
+            "stargate_query",
+            quote! { api, storage, querier, block, request },
+            quote! {
+                Err(#prepath::IperError::UnsupportedMsg {
+                    module: #name.to_string(),
+                    msg: request.path,
+                }
+                .into())
+            },
+        );
+
+        let stargate_sudo = hooks.get("stargate_sudo").map(|ident| {
+            quote! {
+                fn stargate_sudo(
+                    &self,
+                    api: &dyn ::cosmwasm_std::Api,
+                    storage: #storage_ty,
+                    router: &#prepath::RouterWrapper,
+                    block: &::cosmwasm_std::BlockInfo,
+                    msg: ::cosmwasm_std::Binary,
+                ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                    self.#ident(api, storage, router, block, msg)
+                }
+            }
+        });
+
+        quote! {
+            impl #impl_generics #prepath::StargateUrls for #self_ty #where_clause {
+                fn is_query_type_url(&self, type_url: String) -> bool {
+                    <#query as std::str::FromStr>::from_str(&type_url).is_ok()
+                }
+
+                fn is_msg_type_url(&self, type_url: String) -> bool {
+                    <#msgs as std::str::FromStr>::from_str(&type_url).is_ok()
+                }
+
+                fn type_urls(&self) -> Vec<String> {
+                    let mut urls = Vec::new();
+                    urls.extend(<#query as #prepath::strum::IntoEnumIterator>::iter().map(|url| url.to_string()));
+                    urls.extend(<#msgs as #prepath::strum::IntoEnumIterator>::iter().map(|url| url.to_string()));
+                    urls
+                }
+            }
+
+            impl #impl_generics #prepath::StargateName for #self_ty #where_clause {
+                fn stargate_name(&self) -> String {
+                    #name.to_string()
+                }
+            }
+
+            impl #impl_generics #prepath::StargateApplication for #self_ty #where_clause {
+                #[allow(unused_variables)]
+                fn stargate_msg(
+                    &self,
+                    api: &dyn ::cosmwasm_std::Api,
+                    storage: #storage_ty,
+                    router: &#prepath::RouterWrapper,
+                    block: &::cosmwasm_std::BlockInfo,
+                    sender: ::cosmwasm_std::Addr,
+                    type_url: String,
+                    data: ::cosmwasm_std::Binary,
+                ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                    #stargate_msg
+                }
+
+                #[allow(unused_variables)]
+                fn stargate_query(
+                    &self,
+                    api: &dyn ::cosmwasm_std::Api,
+                    storage: &dyn ::cosmwasm_std::Storage,
+                    querier: &dyn ::cosmwasm_std::Querier,
+                    block: &::cosmwasm_std::BlockInfo,
+                    request: ::cosmwasm_std::GrpcQuery,
+                ) -> #prepath::AppResult<::cosmwasm_std::Binary> {
+                    #stargate_query
+                }
+
+                #stargate_sudo
+            }
+        }
+    });
+
+    let expanded = quote! {
+        #input
+
+        impl #impl_generics #self_ty #where_clause {
+            /// Ibc port name
+            pub const IBC_PORT: &'static str = #port;
+        }
+
+        impl #impl_generics #prepath::IbcPortInterface for #self_ty #where_clause {
+            fn port_name(&self) -> String {
+                #port.to_string()
+            }
+        }
+
+        impl #impl_generics #prepath::IbcApplication for #self_ty #where_clause {
+            #[allow(unused_variables)]
+            fn handle_outgoing_packet(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                block: &::cosmwasm_std::BlockInfo,
+                sender: ::cosmwasm_std::Addr,
+                router: &#prepath::RouterWrapper,
+                storage: #storage_ty,
+                msg: ::cosmwasm_std::IbcMsg,
+                channel: #prepath::IbcChannelWrapper,
+            ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                #handle_outgoing_packet
+            }
+
+            #[allow(unused_variables)]
+            fn packet_receive(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                block: &::cosmwasm_std::BlockInfo,
+                router: &#prepath::RouterWrapper,
+                storage: #storage_ty,
+                msg: ::cosmwasm_std::IbcPacketReceiveMsg,
+            ) -> #prepath::InfallibleResult<#prepath::PacketReceiveOk, #prepath::PacketReceiveFailing> {
+                #packet_receive
+            }
+
+            #[allow(unused_variables)]
+            fn packet_ack(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                block: &::cosmwasm_std::BlockInfo,
+                router: &#prepath::RouterWrapper,
+                storage: #storage_ty,
+                msg: #prepath::AckPacket,
+            ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                #packet_ack
+            }
+
+            #[allow(unused_variables)]
+            fn packet_timeout(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                block: &::cosmwasm_std::BlockInfo,
+                router: &#prepath::RouterWrapper,
+                storage: #storage_ty,
+                msg: #prepath::TimeoutPacket,
+            ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                #packet_timeout
+            }
+
+            #[allow(unused_variables)]
+            fn open_channel(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                block: &::cosmwasm_std::BlockInfo,
+                router: &#prepath::RouterWrapper,
+                storage: #storage_ty,
+                msg: ::cosmwasm_std::IbcChannelOpenMsg,
+            ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                #open_channel
+            }
+
+            #[allow(unused_variables)]
+            fn channel_connect(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                block: &::cosmwasm_std::BlockInfo,
+                router: &#prepath::RouterWrapper,
+                storage: #storage_ty,
+                msg: ::cosmwasm_std::IbcChannelConnectMsg,
+            ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                #channel_connect
+            }
+
+            #[allow(unused_variables)]
+            fn init(
+                &self,
+                api: &#prepath::cw_multi_test::MockApiBech32,
+                storage: &mut dyn ::cosmwasm_std::Storage,
+            ) -> #prepath::AppResult<()> {
+                #init
+            }
+
+            #sudo
+        }
+
+        #stargate
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Implements following traits from `cw_iper_test` crate on the type of an `impl` block,
+/// from typed handlers of protobuf messages:
+/// - `StargateUrls`;
+/// - `StargateName`, from the `name` attribute;
+/// - `StargateApplication`.
+///
+/// Methods annotated with `#[msg]` handle the `AnyMsg` with the `type_url` of the request type (from `prost::Name`).
+/// Methods annotated with `#[query("path")]` handle the `GrpcQuery` with the given `path`.
+///
+/// Requests are decoded and responses encoded automatically.
+/// A `#[msg]` handler can return any protobuf message or a `StargateResponse`, to also return `events`:
+///
+/// ```ignore
+/// #[derive(Default, Clone)]
+/// pub struct CosmosSdk;
+///
+/// #[stargate_application(name = "cosmos_sdk")]
+/// impl CosmosSdk {
+///     #[msg]
+///     fn send(&self, ctx: StargateMsgCtx, msg: MsgSend) -> AppResult<StargateResponse<MsgSendResponse>> {
+///         ...
+///     }
+///
+///     #[query("/cosmos.bank.v1beta1.Query/Balance")]
+///     fn balance(
+///         &self,
+///         ctx: StargateQueryCtx,
+///         request: QueryBalanceRequest,
+///     ) -> AppResult<QueryBalanceResponse> {
+///         ...
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn stargate_application(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let mut name: Option<LitStr> = None;
+
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported stargate_application property"))
+        }
+    });
+
+    parse_macro_input!(attr with parser);
+
+    let mut input = parse_macro_input!(input as ItemImpl);
+
+    let Some(name) = name else {
+        return syn::Error::new(
+            Span::call_site(),
+            "name not found. Requested in format #[stargate_application(name = \"name\")]",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let mut msgs: Vec<(Ident, Type)> = vec![];
+    let mut queries: Vec<(Ident, Type, LitStr)> = vec![];
+
+    for item in &mut input.items {
+        if let ImplItem::Fn(method) = item {
+            let mut kind: Option<Option<LitStr>> = None;
+            let mut error = None;
+
+            method.attrs.retain(|attr| {
+                if attr.path().is_ident("msg") {
+                    kind = Some(None);
+                } else if attr.path().is_ident("query") {
+                    match attr.parse_args::<LitStr>() {
+                        Ok(path) => kind = Some(Some(path)),
+                        Err(err) => error = Some(err),
+                    }
+                } else {
+                    return true;
+                }
+
+                false
+            });
+
+            if let Some(error) = error {
+                return error.to_compile_error().into();
+            }
+
+            let Some(kind) = kind else {
+                continue;
+            };
+
+            let request_ty = match (method.sig.inputs.len(), method.sig.inputs.last()) {
+                (3, Some(FnArg::Typed(arg))) => (*arg.ty).clone(),
+                _ => {
+                    return syn::Error::new_spanned(
+                        &method.sig,
+                        "handlers must have the signature fn(&self, ctx, request)",
+                    )
+                    .to_compile_error()
+                    .into()
+                }
+            };
+
+            match kind {
+                None => msgs.push((method.sig.ident.clone(), request_ty)),
+                Some(path) => queries.push((method.sig.ident.clone(), request_ty, path)),
+            }
+        }
+    }
+
+    let prepath = prepath();
+
+    let self_ty = &input.self_ty;
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+
+    let storage_ty =
+        quote! { ::std::rc::Rc<::std::cell::RefCell<&mut dyn ::cosmwasm_std::Storage>> };
+
+    let msg_idents = msgs.iter().map(|(ident, _)| ident);
+    let msg_tys = msgs.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+    let query_idents = queries.iter().map(|(ident, ..)| ident);
+    let query_tys = queries.iter().map(|(_, ty, _)| ty);
+    let query_paths = queries.iter().map(|(.., path)| path).collect::<Vec<_>>();
+
+    let expanded = quote! {
+        #input
+
+        impl #impl_generics #prepath::StargateUrls for #self_ty #where_clause {
+            fn is_query_type_url(&self, type_url: String) -> bool {
+                let paths: Vec<&str> = vec![#(#query_paths),*];
+                paths.contains(&type_url.as_str())
+            }
+
+            fn is_msg_type_url(&self, type_url: String) -> bool {
+                let urls: Vec<String> = vec![#(<#msg_tys as #prepath::prost::Name>::type_url()),*];
+                urls.contains(&type_url)
+            }
+
+            fn type_urls(&self) -> Vec<String> {
+                let mut urls: Vec<String> = vec![#(<#msg_tys as #prepath::prost::Name>::type_url()),*];
+                urls.extend([#(#query_paths.to_string()),*]);
+                urls
+            }
+        }
+
+        impl #impl_generics #prepath::StargateName for #self_ty #where_clause {
+            fn stargate_name(&self) -> String {
+                #name.to_string()
+            }
+        }
+
+        impl #impl_generics #prepath::StargateApplication for #self_ty #where_clause {
+            #[allow(unused_variables)]
+            fn stargate_msg(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                storage: #storage_ty,
+                router: &#prepath::RouterWrapper,
+                block: &::cosmwasm_std::BlockInfo,
+                sender: ::cosmwasm_std::Addr,
+                type_url: String,
+                data: ::cosmwasm_std::Binary,
+            ) -> #prepath::AppResult<#prepath::cw_multi_test::AppResponse> {
+                let ctx = #prepath::StargateMsgCtx {
+                    api,
+                    storage,
+                    router,
+                    block,
+                    sender,
+                };
+
+                #(
+                    if type_url == <#msg_tys as #prepath::prost::Name>::type_url() {
+                        return #prepath::handle_stargate_msg(&data, |msg: #msg_tys| {
+                            self.#msg_idents(ctx, msg)
+                        });
+                    }
+                )*
+
+                Err(#prepath::IperError::UnsupportedMsg {
+                    module: #name.to_string(),
+                    msg: type_url,
+                }
+                .into())
+            }
+
+            #[allow(unused_variables)]
+            fn stargate_query(
+                &self,
+                api: &dyn ::cosmwasm_std::Api,
+                storage: &dyn ::cosmwasm_std::Storage,
+                querier: &dyn ::cosmwasm_std::Querier,
+                block: &::cosmwasm_std::BlockInfo,
+                request: ::cosmwasm_std::GrpcQuery,
+            ) -> #prepath::AppResult<::cosmwasm_std::Binary> {
+                let ctx = #prepath::StargateQueryCtx {
+                    api,
+                    storage,
+                    querier,
+                    block,
+                };
+
+                #(
+                    if request.path == #query_paths {
+                        return #prepath::handle_stargate_query(&request.data, |request: #query_tys| {
+                            self.#query_idents(ctx, request)
+                        });
+                    }
+                )*
+
+                Err(#prepath::IperError::UnsupportedMsg {
+                    module: #name.to_string(),
+                    msg: request.path,
+                }
+                .into())
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
 fn get_attr<'a>(attr_ident: &str, attrs: &'a [syn::Attribute]) -> Option<&'a syn::Attribute> {
     attrs.iter().find(|&attr| {
         attr.path().segments.len() == 1 && attr.path().segments[0].ident == attr_ident
     })
 }
 
-#[allow(unreachable_code)]
-fn is_internal() -> bool {
-    #[cfg(feature = "internal")]
-    {
-        return true;
-    }
-    false
-}
-
 fn prepath() -> proc_macro2::TokenStream {
-    if is_internal() {
-        quote! {crate}
-    } else {
-        quote! {cw_iper_test}
-    }
+    quote! {cw_iper_test}
 }
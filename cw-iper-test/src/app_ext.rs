@@ -6,6 +6,7 @@ use serde::de::DeserializeOwned;
 
 use crate::{
     chain_helper::ChainHelper,
+    error::AppResult,
     ibc_module::IperIbcModule,
     iper_app::{IperApp, SharedChannels},
 };
@@ -25,23 +26,27 @@ pub trait AppExt<
     CustomT::QueryT: CustomQuery,
 {
     /// Transform a standar [cw_multi_test::App] into [IperApp]
+    ///
+    /// Return an error if the [IperApp] state can't be initialized.
     #[allow(clippy::type_complexity)]
     fn into_iper_app(
         self,
         chain_id: impl Into<String>,
-    ) -> Rc<
-        RefCell<
-            IperApp<
-                BankT,
-                MockApiBech32,
-                StorageT,
-                CustomT,
-                WasmT,
-                StakingT,
-                DistrT,
-                IperIbcModule,
-                GovT,
-                StargateT,
+    ) -> AppResult<
+        Rc<
+            RefCell<
+                IperApp<
+                    BankT,
+                    MockApiBech32,
+                    StorageT,
+                    CustomT,
+                    WasmT,
+                    StakingT,
+                    DistrT,
+                    IperIbcModule,
+                    GovT,
+                    StargateT,
+                >,
             >,
         >,
     >;
@@ -76,35 +81,44 @@ where
     fn into_iper_app(
         mut self,
         chain_id: impl Into<String>,
-    ) -> Rc<
-        RefCell<
-            IperApp<
-                BankT,
-                MockApiBech32,
-                StorageT,
-                CustomT,
-                WasmT,
-                StakingT,
-                DistrT,
-                IperIbcModule,
-                GovT,
-                StargateT,
+    ) -> AppResult<
+        Rc<
+            RefCell<
+                IperApp<
+                    BankT,
+                    MockApiBech32,
+                    StorageT,
+                    CustomT,
+                    WasmT,
+                    StakingT,
+                    DistrT,
+                    IperIbcModule,
+                    GovT,
+                    StargateT,
+                >,
             >,
         >,
     > {
+        let chain_id: String = chain_id.into();
+
         let channels: SharedChannels = self.read_module(|router, _, _| router.ibc.channels.clone());
+        channels.borrow_mut().chain_id = chain_id.clone();
 
         let chain_prefix = self.api().prefix().to_string();
-        ChainHelper { chain_prefix }
-            .save(self.storage_mut())
-            .unwrap();
+        ChainHelper {
+            chain_prefix,
+            revision_number: ChainHelper::parse_revision_number(&chain_id),
+            halted: false,
+        }
+        .save(self.storage_mut())?;
 
-        Rc::new(RefCell::new(IperApp {
+        Ok(Rc::new(RefCell::new(IperApp {
             relayer: self.api().addr_make("default_relayer"),
-            chain_id: chain_id.into(),
+            chain_id,
             app: self,
             code_ids: Default::default(),
+            stored_codes: Default::default(),
             channels,
-        }))
+        })))
     }
 }
@@ -0,0 +1,284 @@
+use std::fmt::{self, Display};
+
+use cosmwasm_std::{from_json, Binary, Event};
+use cw_multi_test::AppResponse;
+
+use crate::{
+    error::IperError,
+    history::{packet_data, PacketStatus, RelayHistoryEntry},
+    ibc_module::IbcPacketType,
+    iper_app::MayResponse,
+};
+
+/// Fluent assertions over a `packet` relayed by an [`Ecosystem`](crate::Ecosystem), created with [`Ecosystem::assert_packet`](crate::Ecosystem::assert_packet).
+///
+/// Each assertion panics on failure, printing the decoded `packet`, its channels and the responses of both chains:
+/// ```ignore
+/// eco.assert_packet("neutron", "channel-0", 1)
+///     .assert_ack_success()
+///     .assert_event("osmosis", "ics20_mint", &[("receiver", receiver.as_str())]);
+///
+/// eco.assert_no_pending();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PacketAssertion {
+    pub(crate) entries: Vec<RelayHistoryEntry>,
+    pub(crate) status: PacketStatus,
+}
+
+impl PacketAssertion {
+    /// Return the current [`PacketStatus`].
+    pub fn status(&self) -> &PacketStatus {
+        &self.status
+    }
+
+    /// Assert that the `packet` is waiting to be relayed.
+    #[track_caller]
+    pub fn assert_pending(&self) -> &Self {
+        if self.status != PacketStatus::Pending {
+            self.fail("expected pending packet")
+        }
+        self
+    }
+
+    /// Assert that a success `acknowledgement` has been written.
+    ///
+    /// `acknowledgements` written by `contracts` are considered as success unless they are a [`StdAck::Error`](cosmwasm_std::StdAck::Error).
+    #[track_caller]
+    pub fn assert_ack_success(&self) -> &Self {
+        if !self.acks().any(|(_, success)| success == Some(true)) {
+            self.fail("expected success ack")
+        }
+        self
+    }
+
+    /// Assert that an error `acknowledgement` containing `text` has been written.
+    #[track_caller]
+    pub fn assert_error_ack_contains(&self, text: &str) -> &Self {
+        if !self.acks().any(|(ack, success)| {
+            success == Some(false) && String::from_utf8_lossy(ack).contains(text)
+        }) {
+            self.fail(&format!("expected error ack containing: {}", text))
+        }
+        self
+    }
+
+    /// Assert that the `acknowledgement` has been relayed back to the source chain.
+    #[track_caller]
+    pub fn assert_acknowledged(&self) -> &Self {
+        if !matches!(self.status, PacketStatus::Acknowledged { .. }) {
+            self.fail("expected acknowledged packet")
+        }
+        self
+    }
+
+    /// Assert that the `packet` has timed out.
+    #[track_caller]
+    pub fn assert_timed_out(&self) -> &Self {
+        if !matches!(self.status, PacketStatus::TimedOut { .. }) {
+            self.fail("expected timed out packet")
+        }
+        self
+    }
+
+    /// Assert that a response of the `packet` on `chain_id` contains an event of type `ty` with all `attrs`.
+    ///
+    /// The `wasm-` prefix of `contract` events has to be included in `ty`.
+    #[track_caller]
+    pub fn assert_event(&self, chain_id: &str, ty: &str, attrs: &[(&str, &str)]) -> &Self {
+        let found = self
+            .entries
+            .iter()
+            .flat_map(|entry| {
+                [
+                    (&entry.dest_chain_id, &entry.dest_response),
+                    (&entry.src_chain_id, &entry.src_response),
+                ]
+            })
+            .filter(|(chain, _)| *chain == chain_id)
+            .filter_map(|(_, response)| match response {
+                Some(MayResponse::Ok(response)) => Some(response),
+                _ => None,
+            })
+            .any(|response| has_event(&response.events, ty, attrs));
+
+        if !found {
+            self.fail(&format!(
+                "expected event {} with attributes {:?} on {}",
+                ty, attrs, chain_id
+            ))
+        }
+        self
+    }
+
+    fn acks(&self) -> impl Iterator<Item = (&Binary, Option<bool>)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.ack.as_ref().map(|ack| (ack, entry.ack_success)))
+    }
+
+    #[track_caller]
+    fn fail(&self, msg: &str) -> ! {
+        panic!("{}\n{}", msg, self)
+    }
+}
+
+impl Display for PacketAssertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "status: {:?}", self.status)?;
+
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "- {:?}: {}/{} -> {}/{}, sequence: {:?}",
+                entry.kind,
+                entry.src_chain_id,
+                entry.src_channel,
+                entry.dest_chain_id,
+                entry.dest_channel,
+                entry.sequence
+            )?;
+            writeln!(f, "  data: {}", display_data(&entry.data))?;
+
+            if let Some(ack) = &entry.ack {
+                writeln!(
+                    f,
+                    "  ack: {}, success: {:?}",
+                    display_data(ack),
+                    entry.ack_success
+                )?;
+            }
+
+            if let Some(response) = &entry.dest_response {
+                writeln!(f, "  {} response: {:?}", entry.dest_chain_id, response)?;
+            }
+
+            if let Some(response) = &entry.src_response {
+                writeln!(f, "  {} response: {:?}", entry.src_chain_id, response)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl MayResponse {
+    /// Assert that the `packet` has been handled successfully, returning the [`AppResponse`].
+    #[track_caller]
+    pub fn assert_ok(&self) -> &AppResponse {
+        match self {
+            MayResponse::Ok(response) => response,
+            MayResponse::Err(err) => panic!("expected ok response, found error: {}", err),
+        }
+    }
+
+    /// Assert that the `packet` handling returned an error, returning it.
+    #[track_caller]
+    pub fn assert_err(&self) -> &str {
+        match self {
+            MayResponse::Ok(response) => {
+                panic!("expected error response, found ok: {:?}", response)
+            }
+            MayResponse::Err(err) => err,
+        }
+    }
+
+    /// Assert that the `packet` handling returned an error containing `text`.
+    #[track_caller]
+    pub fn assert_err_contains(&self, text: &str) -> &Self {
+        match self {
+            MayResponse::Err(err) if err.contains(text) => self,
+            _ => panic!("expected error containing: {}, found: {:?}", text, self),
+        }
+    }
+
+    /// Assert that the response contains an event of type `ty` with all `attrs`.
+    #[track_caller]
+    pub fn assert_event(&self, ty: &str, attrs: &[(&str, &str)]) -> &Self {
+        if !has_event(&self.assert_ok().events, ty, attrs) {
+            panic!(
+                "expected event {} with attributes {:?}, found: {:?}",
+                ty, attrs, self
+            )
+        }
+        self
+    }
+}
+
+/// Assert that an error is an [`IperError`](crate::IperError) matching `pattern`.
+///
+/// Works on both [`anyhow::Error`] and [`IperError`](crate::IperError), printing the error on failure:
+/// ```ignore
+/// let err = eco.relay_next_packet("neutron").unwrap_err();
+///
+/// assert_iper_err!(err, IperError::ClientNotActive { status: ClientStatus::Expired, .. });
+/// ```
+#[macro_export]
+macro_rules! assert_iper_err {
+    ($err:expr, $pattern:pat) => {{
+        let err = &$err;
+        match $crate::AsIperError::as_iper_error(err) {
+            Some($pattern) => {}
+            _ => panic!(
+                "expected error matching {}, found: {:?}",
+                stringify!($pattern),
+                err
+            ),
+        }
+    }};
+}
+
+/// Access the [`IperError`] behind an error, used by [`assert_iper_err`](crate::assert_iper_err).
+#[doc(hidden)]
+pub trait AsIperError {
+    fn as_iper_error(&self) -> Option<&IperError>;
+}
+
+impl AsIperError for anyhow::Error {
+    fn as_iper_error(&self) -> Option<&IperError> {
+        self.downcast_ref()
+    }
+}
+
+impl AsIperError for IperError {
+    fn as_iper_error(&self) -> Option<&IperError> {
+        Some(self)
+    }
+}
+
+pub(crate) fn has_event(events: &[Event], ty: &str, attrs: &[(&str, &str)]) -> bool {
+    events.iter().any(|event| {
+        event.ty == ty
+            && attrs.iter().all(|(key, value)| {
+                event
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.key == *key && attr.value == *value)
+            })
+    })
+}
+
+/// Describe a pending `packet` for assertion failures.
+pub(crate) fn display_packet(chain_id: &str, packet_id: u64, packet: &IbcPacketType) -> String {
+    let kind = match packet {
+        IbcPacketType::AckPacket(..) => "ack",
+        IbcPacketType::OutgoingPacket(..) | IbcPacketType::OutgoinPacketRaw(..) => "packet",
+        IbcPacketType::CloseChannel { .. } => "close channel",
+        IbcPacketType::Timeout(..) => "timeout",
+    };
+
+    format!(
+        "- {} {} on {}/{}: {}",
+        kind,
+        packet_id,
+        chain_id,
+        packet.get_local_channel_id(),
+        display_data(&packet_data(packet))
+    )
+}
+
+fn display_data(data: &Binary) -> String {
+    from_json::<serde_json::Value>(data)
+        .map(|value| value.to_string())
+        .unwrap_or_else(|_| data.to_base64())
+}
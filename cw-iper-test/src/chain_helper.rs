@@ -11,12 +11,35 @@ use crate::error::AppResult;
 pub struct ChainHelper {
     /// `chain_prefix` of the chain
     pub chain_prefix: String,
+    /// `revision_number` of the chain, derived from the `chain_id` (e.g. `1` for `osmosis-1`)
+    /// and increased by [`Ecosystem::upgrade_chain`](crate::Ecosystem::upgrade_chain)
+    #[serde(default)]
+    pub revision_number: u64,
+    /// `true` if the chain has been halted by [`Ecosystem::halt_chain`](crate::Ecosystem::halt_chain)
+    #[serde(default)]
+    pub halted: bool,
 }
 
 impl ChainHelper {
     const KEY: &'static str = "chain_helper_key";
 }
 
+impl ChainHelper {
+    /// Parse the `revision_number` from a `chain_id` in the `{name}-{revision_number}` format.
+    ///
+    /// Return `0` if the `chain_id` is not in this format, as done by `ibc-go`.
+    pub fn parse_revision_number(chain_id: &str) -> u64 {
+        match chain_id.rsplit_once('-') {
+            Some((name, revision))
+                if !name.is_empty() && !name.ends_with('-') && !revision.starts_with('0') =>
+            {
+                revision.parse().unwrap_or_default()
+            }
+            _ => 0,
+        }
+    }
+}
+
 impl ChainHelper {
     /// Load the stored `ChainHelper`
     pub fn load(storage: &dyn Storage) -> AppResult<Self> {
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Storage, Timestamp};
+use cw_storage_plus::Item;
+
+use crate::error::AppResult;
+
+/// `light clients` of an [`IperApp`](crate::IperApp), by `client_id`.
+pub(crate) const LIGHT_CLIENTS: Item<BTreeMap<String, LightClient>> = Item::new("light_clients");
+
+/// Number of `light clients` created on an [`IperApp`](crate::IperApp), used as sequence of the `client_id`.
+const CLIENT_SEQUENCE: Item<u64> = Item::new("client_sequence");
+
+/// Default trusting period of a [`LightClient`], in seconds (14 days).
+pub const DEFAULT_TRUSTING_PERIOD: u64 = 14 * 24 * 60 * 60;
+
+/// Simulated `ibc light client` stored on an [`IperApp`](crate::IperApp), tracking a counterparty chain.
+///
+/// A client is created for each counterparty when the first `channel` is opened by [`Ecosystem::open_ibc_channel`](crate::Ecosystem::open_ibc_channel).
+/// The relayer updates the client with the latest block of the counterparty before delivering each `packet`.
+///
+/// The client expires when the local block time is past the timestamp of the latest consensus state plus the trusting period,
+/// and it's frozen by [`Ecosystem::submit_misbehaviour`](crate::Ecosystem::submit_misbehaviour).
+/// Expired and frozen clients can't be updated, so `packets` can't be delivered.
+#[cw_serde]
+pub struct LightClient {
+    /// Id of the client, as `07-tendermint-{n}`.
+    pub client_id: String,
+    /// `chain_id` of the tracked chain.
+    pub counterparty_chain_id: String,
+    /// Trusting period, in seconds.
+    pub trusting_period: u64,
+    /// Latest `revision_number` of the tracked chain known by the client.
+    pub revision_number: u64,
+    /// Latest height of the tracked chain known by the client, in the current `revision_number`.
+    pub latest_height: u64,
+    /// Block time of the tracked chain, by height in the current `revision_number`.
+    pub consensus_states: BTreeMap<u64, Timestamp>,
+    /// Height at which misbehaviour has been detected, if any.
+    pub frozen_height: Option<u64>,
+}
+
+impl LightClient {
+    pub(crate) fn new(
+        client_id: String,
+        counterparty_chain_id: impl Into<String>,
+        trusting_period: u64,
+        header: &ClientHeader,
+    ) -> Self {
+        Self {
+            client_id,
+            counterparty_chain_id: counterparty_chain_id.into(),
+            trusting_period,
+            revision_number: header.revision_number,
+            latest_height: header.height,
+            consensus_states: BTreeMap::from([(header.height, header.time)]),
+            frozen_height: None,
+        }
+    }
+
+    /// Return the block time of the tracked chain at [`LightClient::latest_height`].
+    pub fn latest_timestamp(&self) -> Option<Timestamp> {
+        self.consensus_states.get(&self.latest_height).cloned()
+    }
+
+    /// Return the [`ClientStatus`] at local block time `now`.
+    pub fn status(&self, now: Timestamp) -> ClientStatus {
+        if self.frozen_height.is_some() {
+            return ClientStatus::Frozen;
+        }
+
+        match self.latest_timestamp() {
+            Some(timestamp) if timestamp.plus_seconds(self.trusting_period) > now => {
+                ClientStatus::Active
+            }
+            _ => ClientStatus::Expired,
+        }
+    }
+
+    /// Store the consensus state of `header`.
+    ///
+    /// When the tracked chain has been upgraded, the consensus states of the previous `revision_number` are discarded.
+    pub(crate) fn update(&mut self, header: &ClientHeader) {
+        if header.revision_number > self.revision_number {
+            self.revision_number = header.revision_number;
+            self.latest_height = header.height;
+            self.consensus_states.clear();
+        }
+
+        self.latest_height = self.latest_height.max(header.height);
+        self.consensus_states.insert(header.height, header.time);
+    }
+
+    /// Freeze the client at [`LightClient::latest_height`].
+    pub(crate) fn freeze(&mut self) {
+        self.frozen_height = Some(self.latest_height);
+    }
+}
+
+/// Latest block of a tracked chain, used to create and update a [`LightClient`].
+#[derive(Debug, Clone)]
+pub struct ClientHeader {
+    /// `revision_number` of the tracked chain.
+    pub revision_number: u64,
+    /// Block height of the tracked chain.
+    pub height: u64,
+    /// Block time of the tracked chain.
+    pub time: Timestamp,
+}
+
+/// Status of a [`LightClient`].
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum ClientStatus {
+    /// The client can be updated and `packets` can be delivered.
+    Active,
+    /// The trusting period is elapsed without updates.
+    Expired,
+    /// Misbehaviour has been submitted.
+    Frozen,
+}
+
+/// Return the `client_id` of the next client, incrementing the client sequence.
+pub(crate) fn next_client_id(storage: &mut dyn Storage) -> AppResult<String> {
+    let sequence = CLIENT_SEQUENCE.may_load(storage)?.unwrap_or_default();
+    CLIENT_SEQUENCE.save(storage, &(sequence + 1))?;
+
+    Ok(format!("07-tendermint-{}", sequence))
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::IbcOrder;
+use cw_storage_plus::Item;
+
+use crate::{
+    error::{AppResult, IperError},
+    ibc::{IbcChannelCreator, IbcPort},
+};
+
+/// `connections` of an [`IperApp`](crate::IperApp), by `connection_id`.
+pub(crate) const IBC_CONNECTIONS: Item<BTreeMap<String, ConnectionEnd>> =
+    Item::new("ibc_connections");
+
+/// Local side of an `ibc connection`, stored on an [`IperApp`](crate::IperApp).
+///
+/// Connections are created by [`Ecosystem::open_connection`](crate::Ecosystem::open_connection).
+#[cw_serde]
+pub struct ConnectionEnd {
+    /// Id of the connection, as `connection-{n}`.
+    pub connection_id: String,
+    /// Id of the local [`LightClient`](crate::LightClient) tracking the counterparty chain.
+    pub client_id: String,
+    /// Handshake state.
+    pub state: ConnectionState,
+    /// Counterparty side of the connection.
+    pub counterparty: ConnectionCounterparty,
+}
+
+/// Counterparty side of a [`ConnectionEnd`].
+#[cw_serde]
+pub struct ConnectionCounterparty {
+    /// `chain_id` of the counterparty chain.
+    pub chain_id: String,
+    /// Id of the [`LightClient`](crate::LightClient) on the counterparty chain tracking the local chain.
+    pub client_id: String,
+    /// Id of the connection on the counterparty chain, [`None`] until `ConnOpenAck`.
+    pub connection_id: Option<String>,
+}
+
+/// State of a [`ConnectionEnd`] during the handshake.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum ConnectionState {
+    /// `ConnOpenInit` done.
+    Init,
+    /// `ConnOpenTry` done.
+    TryOpen,
+    /// Handshake completed, `channels` can be opened.
+    Open,
+}
+
+/// Handle of an open `ibc connection` between two chains, returned by [`Ecosystem::open_connection`](crate::Ecosystem::open_connection).
+#[cw_serde]
+pub struct ConnectionHandle {
+    /// `chain_id` of the chain that started the handshake.
+    pub chain_a: String,
+    /// `connection_id` on `chain_a`.
+    pub connection_a: String,
+    /// `chain_id` of the counterparty chain.
+    pub chain_b: String,
+    /// `connection_id` on `chain_b`.
+    pub connection_b: String,
+}
+
+impl ConnectionHandle {
+    /// Create the [`IbcChannelCreator`]s of a `channel` on this connection,
+    /// with `port_a` on `chain_a` and `port_b` on `chain_b`.
+    pub fn channel_creators(
+        &self,
+        port_a: IbcPort,
+        port_b: IbcPort,
+        order: IbcOrder,
+        version: impl Into<String>,
+    ) -> (IbcChannelCreator, IbcChannelCreator) {
+        let version = version.into();
+
+        (
+            IbcChannelCreator::new(
+                port_a,
+                order.clone(),
+                version.clone(),
+                &self.connection_a,
+                &self.chain_a,
+            ),
+            IbcChannelCreator::new(port_b, order, version, &self.connection_b, &self.chain_b),
+        )
+    }
+
+    /// Return the same connection, with `chain_b` as first side.
+    pub fn reversed(&self) -> Self {
+        Self {
+            chain_a: self.chain_b.clone(),
+            connection_a: self.connection_b.clone(),
+            chain_b: self.chain_a.clone(),
+            connection_b: self.connection_a.clone(),
+        }
+    }
+}
+
+/// Return the `connection_id` of the next connection.
+pub(crate) fn next_connection_id(connections: &BTreeMap<String, ConnectionEnd>) -> String {
+    format!("connection-{}", connections.len())
+}
+
+/// Validate an identifier against `ICS-24`: allowed characters are
+/// alphanumeric, `.`, `_`, `+`, `-`, `#`, `[`, `]`, `<` and `>`.
+pub(crate) fn validate_identifier(identifier: &str, min: usize, max: usize) -> AppResult<()> {
+    let invalid = |reason: String| {
+        Err(IperError::InvalidIdentifier {
+            identifier: identifier.to_string(),
+            reason,
+        }
+        .into())
+    };
+
+    if identifier.len() < min || identifier.len() > max {
+        return invalid(format!("length must be between {} and {}", min, max));
+    }
+
+    if let Some(char) = identifier
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !"._+-#[]<>".contains(*c))
+    {
+        return invalid(format!("invalid character: {:?}", char));
+    }
+
+    Ok(())
+}
+
+/// Validate a `connection_id` against `ICS-24`, in the `connection-{n}` format.
+pub(crate) fn validate_connection_id(connection_id: &str) -> AppResult<()> {
+    validate_identifier(connection_id, 10, 64)?;
+
+    if connection_id
+        .strip_prefix("connection-")
+        .and_then(|n| n.parse::<u64>().ok())
+        .is_none()
+    {
+        return Err(IperError::InvalidIdentifier {
+            identifier: connection_id.to_string(),
+            reason: "not in the connection-{n} format".to_string(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Validate a `port_id` against `ICS-24`.
+pub(crate) fn validate_port_id(port_id: &str) -> AppResult<()> {
+    validate_identifier(port_id, 2, 128)
+}
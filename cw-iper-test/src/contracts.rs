@@ -308,3 +308,160 @@ where
         Box::new(self) as Box<dyn Contract<C, Q>>
     }
 }
+
+/// Create an [`IperContract`] from the `entry_points` of a `contract` module or crate, found by name:
+/// - `instantiate`, `execute`, `query`, `sudo`, `reply` and `migrate`;
+/// - `ibc_channel_open`, `ibc_channel_close`, `ibc_channel_connect`, `ibc_packet_receive`, `ibc_packet_ack` and `ibc_packet_timeout`.
+///
+/// The functions have to be `pub`. A function missing from the module is replaced by a default:
+/// - `instantiate`, `ibc_channel_open`, `ibc_channel_close`, `ibc_channel_connect`, `ibc_packet_ack` and `ibc_packet_timeout` succeed without doing anything;
+/// - `ibc_packet_receive` writes an error `ack`;
+/// - the other `entry_points` return an error.
+///
+/// Default `entry_points` take [`Empty`] as message.
+///
+/// ```ignore
+/// let contract: IperContract<Empty> = ibc_entry_points!(crate::contracts::counter);
+///
+/// let code_id = app.borrow_mut().store_ibc_code(contract);
+/// ```
+#[macro_export]
+macro_rules! ibc_entry_points {
+    ($($module:ident)::+) => {{
+        // `entry_points` of the module shadow the defaults, since they are imported in an inner scope
+        #[allow(unused_imports)]
+        use $crate::default_entry_points::*;
+        {
+            #[allow(unused_imports)]
+            use $($module)::+::*;
+
+            $crate::IperContract::new(
+                $crate::ContractWrapperExt::to_contract(
+                    $crate::cw_multi_test::ContractWrapper::new(execute, instantiate, query)
+                        .with_sudo(sudo)
+                        .with_reply(reply)
+                        .with_migrate(migrate),
+                ),
+                Some($crate::IbcClosures::new_as_ibc_contract(
+                    ibc_channel_open,
+                    ibc_channel_close,
+                    ibc_channel_connect,
+                    ibc_packet_receive,
+                    ibc_packet_ack,
+                    ibc_packet_timeout,
+                )),
+            )
+        }
+    }};
+}
+
+/// Default `entry_points` used by [`ibc_entry_points`](crate::ibc_entry_points) when missing from the `contract` module.
+#[doc(hidden)]
+pub mod default_entry_points {
+    use cosmwasm_std::{
+        Binary, CustomMsg, CustomQuery, Deps, DepsMut, Empty, Env, Ibc3ChannelOpenResponse,
+        IbcBasicResponse, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+        IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, MessageInfo,
+        Never, Reply, Response, StdAck, StdError, StdResult,
+    };
+
+    fn not_implemented(entry_point: &str) -> StdError {
+        StdError::generic_err(format!("{} entry_point not implemented", entry_point))
+    }
+
+    pub fn instantiate<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response<C>> {
+        Ok(Response::default())
+    }
+
+    pub fn execute<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response<C>> {
+        Err(not_implemented("execute"))
+    }
+
+    pub fn query<Q: CustomQuery>(_deps: Deps<Q>, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(not_implemented("query"))
+    }
+
+    pub fn sudo<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _msg: Empty,
+    ) -> StdResult<Response<C>> {
+        Err(not_implemented("sudo"))
+    }
+
+    pub fn reply<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _reply: Reply,
+    ) -> StdResult<Response<C>> {
+        Err(not_implemented("reply"))
+    }
+
+    pub fn migrate<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _msg: Empty,
+    ) -> StdResult<Response<C>> {
+        Err(not_implemented("migrate"))
+    }
+
+    pub fn ibc_channel_open<Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _msg: IbcChannelOpenMsg,
+    ) -> StdResult<Option<Ibc3ChannelOpenResponse>> {
+        Ok(None)
+    }
+
+    pub fn ibc_channel_close<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _msg: IbcChannelCloseMsg,
+    ) -> StdResult<IbcBasicResponse<C>> {
+        Ok(IbcBasicResponse::default())
+    }
+
+    pub fn ibc_channel_connect<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _msg: IbcChannelConnectMsg,
+    ) -> StdResult<IbcBasicResponse<C>> {
+        Ok(IbcBasicResponse::default())
+    }
+
+    pub fn ibc_packet_receive<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _msg: IbcPacketReceiveMsg,
+    ) -> Result<IbcReceiveResponse<C>, Never> {
+        Ok(IbcReceiveResponse::new(StdAck::error(
+            "ibc_packet_receive entry_point not implemented",
+        )))
+    }
+
+    pub fn ibc_packet_ack<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _msg: IbcPacketAckMsg,
+    ) -> StdResult<IbcBasicResponse<C>> {
+        Ok(IbcBasicResponse::default())
+    }
+
+    pub fn ibc_packet_timeout<C: CustomMsg, Q: CustomQuery>(
+        _deps: DepsMut<Q>,
+        _env: Env,
+        _msg: IbcPacketTimeoutMsg,
+    ) -> StdResult<IbcBasicResponse<C>> {
+        Ok(IbcBasicResponse::default())
+    }
+}
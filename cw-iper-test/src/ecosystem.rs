@@ -1,11 +1,32 @@
 use crate::{
-    error::AppResult,
-    ibc::IbcChannelCreator,
+    assertions::{display_packet, PacketAssertion},
+    client::{ClientStatus, LightClient, DEFAULT_TRUSTING_PERIOD},
+    connection::{
+        validate_connection_id, validate_port_id, ConnectionCounterparty, ConnectionEnd,
+        ConnectionHandle, ConnectionState,
+    },
+    error::{AppResult, IperError},
+    fixture::EcosystemFixture,
+    history::{
+        packet_data, OriginTx, PacketStatus, RelayEventKind, RelayHistory, RelayHistoryEntry,
+        TrackedPacket,
+    },
+    ibc::{IbcChannelCreator, IbcPort},
     ibc_module::IbcPacketType,
-    iper_app::{IperAppRef, MayResponse},
+    iper_app::{IperAppRef, IperSudoMsg, MayResponse},
+    record::{RecordedAction, RecordedStep, Recording},
+    response::{BlockedPacket, RelayReport, RelayedResponse},
+    snapshot::EcosystemSnapshot,
+};
+use anyhow::{anyhow, bail};
+use cosmwasm_std::{to_json_binary, Addr, Binary, CosmosMsg, IbcOrder};
+use cw_multi_test::AppResponse;
+use serde::Serialize;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
 };
-use anyhow::anyhow;
-use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
 
 #[derive(Default)]
 /// This structure acts as a wrapper containing all [`IperApp`](crate::iper_app::IperApp).
@@ -15,41 +36,146 @@ use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
 /// [`IperApp`](crate::iper_app::IperApp)s are stored as [`IperAppRef`] traits instead of as [`IperApp`](crate::iper_app::IperApp) instances.
 /// This approach is used to decouple them from the specific typing of the generic parameters
 /// required by the [`IperApp`](crate::iper_app::IperApp) and [`App`](cw_multi_test::App) classes.
+///
+/// [`Ecosystem`] is not `Send`, use [`SyncEcosystem`](crate::SyncEcosystem) to share it across threads.
 pub struct Ecosystem {
     apps: BTreeMap<String, Rc<RefCell<dyn IperAppRef>>>,
+    history: RefCell<RelayHistory>,
+    snapshots: RefCell<BTreeMap<u64, EcosystemSnapshot>>,
+    recording: RefCell<Option<Recording>>,
+    max_relay_iterations: Option<usize>,
+    trusting_period: Option<u64>,
+    paused_channels: RefCell<BTreeSet<(String, String)>>,
+    relayer_paused: Cell<bool>,
 }
 
+/// Default max number of `packets` relayed by a single [`Ecosystem::relay_all_packets`] call.
+pub const DEFAULT_MAX_RELAY_ITERATIONS: usize = 1_000;
+
 impl Ecosystem {
     /// Add a [`IperApp`](crate::iper_app::IperApp) as [`IperAppRef`]
-    pub fn add_app(self, app: Rc<RefCell<dyn IperAppRef>>) -> Self {
-        let mut apps = self.apps;
+    pub fn add_app(mut self, app: Rc<RefCell<dyn IperAppRef>>) -> Self {
         let chain_id = app.borrow().chain_id().to_string();
-        apps.insert(chain_id, app);
-        Self { apps }
+        self.apps.insert(chain_id, app);
+        self
+    }
+
+    /// Open an `ibc connection` between two [`IperApp`](crate::iper_app::IperApp), returning its [`ConnectionHandle`].
+    ///
+    /// The [`LightClient`]s tracking each other are created if missing,
+    /// then the connection handshake (`ConnOpenInit`, `ConnOpenTry`, `ConnOpenAck`, `ConnOpenConfirm`) is executed,
+    /// updating the [`LightClient`] of the receiving chain before each step.
+    pub fn open_connection(
+        &self,
+        chain_a: impl Into<String>,
+        chain_b: impl Into<String>,
+    ) -> AppResult<ConnectionHandle> {
+        let chain_a: String = chain_a.into();
+        let chain_b: String = chain_b.into();
+
+        let app_a = self.get_app(&chain_a)?;
+        let app_b = self.get_app(&chain_b)?;
+
+        self.create_light_clients(app_a, app_b)?;
+
+        let client_a = app_a.borrow().light_client(&chain_b)?.client_id;
+        let client_b = app_b.borrow().light_client(&chain_a)?.client_id;
+
+        // ConnOpenInit
+        let connection_a = app_a.borrow().next_connection_id()?;
+
+        let mut end_a = ConnectionEnd {
+            connection_id: connection_a.clone(),
+            client_id: client_a.clone(),
+            state: ConnectionState::Init,
+            counterparty: ConnectionCounterparty {
+                chain_id: chain_b.clone(),
+                client_id: client_b.clone(),
+                connection_id: None,
+            },
+        };
+
+        app_a.borrow_mut().save_connection(end_a.clone())?;
+
+        // ConnOpenTry
+        self.update_client(&chain_b, &chain_a)?;
+
+        let connection_b = app_b.borrow().next_connection_id()?;
+
+        let mut end_b = ConnectionEnd {
+            connection_id: connection_b.clone(),
+            client_id: client_b,
+            state: ConnectionState::TryOpen,
+            counterparty: ConnectionCounterparty {
+                chain_id: chain_a.clone(),
+                client_id: client_a,
+                connection_id: Some(connection_a.clone()),
+            },
+        };
+
+        app_b.borrow_mut().save_connection(end_b.clone())?;
+
+        // ConnOpenAck
+        self.update_client(&chain_a, &chain_b)?;
+
+        end_a.state = ConnectionState::Open;
+        end_a.counterparty.connection_id = Some(connection_b.clone());
+
+        app_a.borrow_mut().save_connection(end_a)?;
+
+        // ConnOpenConfirm
+        self.update_client(&chain_b, &chain_a)?;
+
+        end_b.state = ConnectionState::Open;
+
+        app_b.borrow_mut().save_connection(end_b)?;
+
+        Ok(ConnectionHandle {
+            chain_a,
+            connection_a,
+            chain_b,
+            connection_b,
+        })
+    }
+
+    /// Open a `IbcChannel` on a `connection` created with [`Ecosystem::open_connection`],
+    /// with `port_a` on [`ConnectionHandle::chain_a`] and `port_b` on [`ConnectionHandle::chain_b`].
+    pub fn open_ibc_channel_on(
+        &self,
+        connection: &ConnectionHandle,
+        port_a: IbcPort,
+        port_b: IbcPort,
+        order: IbcOrder,
+        version: impl Into<String>,
+    ) -> AppResult<()> {
+        let (channel_a, channel_b) = connection.channel_creators(port_a, port_b, order, version);
+        self.open_ibc_channel(channel_a, channel_b)
     }
 
     /// Open a `IbcChannel` bewteen two [`IperApp`](crate::iper_app::IperApp)
+    ///
+    /// `port` and `connection_id` of both sides are validated against `ICS-24`.
+    /// The two sides have to be on the same open `connection`, created with [`Ecosystem::open_connection`].
     pub fn open_ibc_channel(
         &self,
         mut channel_1: IbcChannelCreator,
         mut channel_2: IbcChannelCreator,
     ) -> AppResult<()> {
+        self.check_channel_connection(&channel_1, &channel_2)?;
+        self.check_channel_connection(&channel_2, &channel_1)?;
+
         let app_1 = self.get_app(&channel_1.chain_id)?;
         let app_2 = self.get_app(&channel_2.chain_id)?;
 
+        self.create_light_clients(app_1, app_2)?;
+
         let channel_id_1 = app_1.borrow().get_next_channel_id();
         let channel_id_2 = app_2.borrow().get_next_channel_id();
         channel_1.set_channel_id(channel_id_1);
         channel_2.set_channel_id(channel_id_2);
 
-        let sequence = Rc::new(RefCell::new(0));
-
-        app_1
-            .borrow_mut()
-            .open_channel(&channel_1, &channel_2, sequence.clone())?;
-        app_2
-            .borrow_mut()
-            .open_channel(&channel_2, &channel_1, sequence)?;
+        app_1.borrow_mut().open_channel(&channel_1, &channel_2)?;
+        app_2.borrow_mut().open_channel(&channel_2, &channel_1)?;
 
         app_1.borrow_mut().channel_connect(channel_id_1)?;
         app_2.borrow_mut().channel_connect(channel_id_2)?;
@@ -60,28 +186,220 @@ impl Ecosystem {
         Ok(())
     }
 
+    /// Set the max number of `packets` relayed by a single [`Ecosystem::relay_all_packets`] call.
+    ///
+    /// Default is [`DEFAULT_MAX_RELAY_ITERATIONS`].
+    pub fn with_max_relay_iterations(mut self, max_relay_iterations: usize) -> Self {
+        self.max_relay_iterations = Some(max_relay_iterations);
+        self
+    }
+
+    /// Set the trusting period, in seconds, of the [`LightClient`]s created by [`Ecosystem::open_ibc_channel`].
+    ///
+    /// Default is [`DEFAULT_TRUSTING_PERIOD`].
+    pub fn with_trusting_period(mut self, trusting_period: u64) -> Self {
+        self.trusting_period = Some(trusting_period);
+        self
+    }
+
+    /// Return the [`LightClient`] of `chain_id` tracking `counterparty_chain_id`.
+    pub fn light_client(
+        &self,
+        chain_id: impl Into<String>,
+        counterparty_chain_id: &str,
+    ) -> AppResult<LightClient> {
+        self.get_app(chain_id)?
+            .borrow()
+            .light_client(counterparty_chain_id)
+    }
+
+    /// Update the [`LightClient`] of `chain_id` tracking `counterparty_chain_id` with the current block of the counterparty.
+    ///
+    /// Clients are updated automatically before each `packet` delivery.
+    /// Updating them manually allows to keep them alive while only the block time of the chain moves forward.
+    pub fn update_client(
+        &self,
+        chain_id: impl Into<String>,
+        counterparty_chain_id: &str,
+    ) -> AppResult<()> {
+        let counterparty_header = self
+            .get_app(counterparty_chain_id)?
+            .borrow()
+            .client_header()?;
+
+        self.get_app(chain_id)?
+            .borrow_mut()
+            .update_light_client(counterparty_chain_id, &counterparty_header)
+    }
+
+    /// Simulate the submission of a misbehaviour of `counterparty_chain_id`,
+    /// freezing the [`LightClient`] of `chain_id` tracking it.
+    ///
+    /// `packets` from `counterparty_chain_id` can't be delivered to `chain_id` anymore.
+    pub fn submit_misbehaviour(
+        &self,
+        chain_id: impl Into<String>,
+        counterparty_chain_id: &str,
+    ) -> AppResult<()> {
+        self.get_app(chain_id)?
+            .borrow_mut()
+            .freeze_light_client(counterparty_chain_id)
+    }
+
+    /// Simulate a chain upgrade of `chain_id`: the `revision_number` is increased and the block height is reset to `1`.
+    ///
+    /// `packets` in flight are not affected, but timeouts based on the previous `revision_number` are expired
+    /// since heights are compared as (revision, height) pairs.
+    /// The [`LightClient`]s tracking `chain_id` move to the new `revision_number` on the next update.
+    ///
+    /// Return the new `revision_number`.
+    pub fn upgrade_chain(&self, chain_id: impl Into<String>) -> AppResult<u64> {
+        self.get_app(chain_id)?.borrow_mut().upgrade()
+    }
+
+    /// Halt `chain_id`, simulating a chain outage:
+    /// - txs sent via [`Ecosystem::execute`] fail with [`IperError::ChainHalted`];
+    /// - `packets` can't be delivered to the chain, so they stay pending;
+    /// - the block is not advanced by [`Ecosystem::produce_blocks`].
+    ///
+    /// `packets` already sent by the chain can still be delivered to the counterparty, that keeps producing blocks,
+    /// so they time out as soon as the counterparty passes their timeout.
+    /// [`LightClient`]s tracking the chain are not updated while halted and expire after the trusting period.
+    ///
+    /// The inner [`App`](cw_multi_test::App) is not affected, so txs executed directly on it still succeed.
+    pub fn halt_chain(&self, chain_id: impl Into<String>) -> AppResult<()> {
+        self.get_app(chain_id)?.borrow_mut().halt()
+    }
+
+    /// Resume a chain halted by [`Ecosystem::halt_chain`].
+    ///
+    /// A new block is produced with the latest block time among the other running chains,
+    /// so the time elapsed during the outage is caught up at once.
+    pub fn resume_chain(&self, chain_id: impl Into<String>) -> AppResult<()> {
+        let chain_id: String = chain_id.into();
+
+        let mut time = self.get_app(&chain_id)?.borrow().block_info().time;
+
+        for (other_chain_id, app) in &self.apps {
+            let app = app.borrow();
+
+            if other_chain_id != &chain_id && !app.is_halted()? {
+                time = time.max(app.block_info().time);
+            }
+        }
+
+        self.get_app(&chain_id)?.borrow_mut().resume(time)
+    }
+
+    /// Advance the block of all running chains by `blocks` blocks of `block_time` seconds each.
+    ///
+    /// Chains halted by [`Ecosystem::halt_chain`] are skipped.
+    pub fn produce_blocks(&self, blocks: u64, block_time: u64) -> AppResult<()> {
+        for app in self.apps.values() {
+            app.borrow_mut().produce_blocks(blocks, block_time)?;
+        }
+
+        Ok(())
+    }
+
+    /// Pause relaying on a `channel`, in both directions, identified by the `chain_id` and `channel` of one of the two sides.
+    ///
+    /// `packets` on the `channel` stay pending and are skipped by [`Ecosystem::relay_all_packets`],
+    /// while [`Ecosystem::relay_packet`] fails with [`IperError::ChannelPaused`].
+    pub fn pause_channel(
+        &self,
+        chain_id: impl Into<String>,
+        channel: impl Into<String>,
+    ) -> AppResult<()> {
+        let sides = self.channel_sides(chain_id, channel)?;
+        self.paused_channels.borrow_mut().extend(sides);
+        Ok(())
+    }
+
+    /// Resume relaying on a `channel` paused by [`Ecosystem::pause_channel`].
+    pub fn resume_channel(
+        &self,
+        chain_id: impl Into<String>,
+        channel: impl Into<String>,
+    ) -> AppResult<()> {
+        let mut paused_channels = self.paused_channels.borrow_mut();
+
+        for side in self.channel_sides(chain_id, channel)? {
+            paused_channels.remove(&side);
+        }
+
+        Ok(())
+    }
+
+    /// Pause the relayer, simulating a relayer outage: no `packets` are relayed untill [`Ecosystem::resume_relayer`] is called.
+    ///
+    /// [`Ecosystem::relay_all_packets`] relays nothing, while [`Ecosystem::relay_packet`] fails with [`IperError::RelayerPaused`].
+    pub fn pause_relayer(&self) {
+        self.relayer_paused.set(true);
+    }
+
+    /// Resume the relayer paused by [`Ecosystem::pause_relayer`].
+    pub fn resume_relayer(&self) {
+        self.relayer_paused.set(false);
+    }
+
     /// Relay all `packets` untill not `packets` are in pending.
     /// The order is based on the [`BTreeMap`] key orders.
     /// Iterating all [`IperApp`](crate::iper_app::IperApp), if one [`IperApp`](crate::iper_app::IperApp) has not pending packets, next [`IperApp`](crate::iper_app::IperApp) is checked.
     /// Once one `packet` is `relayed`, the loop is restarted from the first [`IperApp`](crate::iper_app::IperApp)
-    pub fn relay_all_packets(&self) -> AppResult<Vec<MayResponse>> {
-        let mut res = vec![];
+    ///
+    /// Return an error if `packets` are still pending after the max number of iterations (see [`Ecosystem::with_max_relay_iterations`]),
+    /// usually caused by `contracts` sending `packets` to each other forever.
+    ///
+    /// `packets` that can't be delivered because of a halted chain, a paused `channel`, a paused relayer or a not active [`LightClient`]
+    /// are skipped and stay pending. They are returned in [`RelayReport::blocked`].
+    pub fn relay_all_packets(&self) -> AppResult<RelayReport> {
+        let max_relay_iterations = self
+            .max_relay_iterations
+            .unwrap_or(DEFAULT_MAX_RELAY_ITERATIONS);
+
+        let mut report = RelayReport::default();
 
         let mut finished = false;
 
         while !finished {
             finished = true;
 
-            for (chain_id, app) in &self.apps {
-                if app.borrow().some_pending_packets() {
-                    res.push(self.relay_next_packet(chain_id)?);
+            for chain_id in self.apps.keys() {
+                if let Some(packet_id) = self.next_relayable_packet(chain_id)? {
+                    if report.len() >= max_relay_iterations {
+                        let last = report.relayed.last();
+                        bail!(IperError::MaxRelayIterations {
+                            max: max_relay_iterations,
+                            chain_id: last.map(|r| r.src_chain_id.clone()).unwrap_or_default(),
+                            channel: last.map(|r| r.src_channel.clone()).unwrap_or_default(),
+                            sequence: last.and_then(|r| r.sequence)
+                        })
+                    }
+
+                    report
+                        .relayed
+                        .push(self.relay_and_record(chain_id, packet_id)?);
                     finished = false;
                     break;
                 }
             }
         }
 
-        Ok(res)
+        for chain_id in self.apps.keys() {
+            for (packet_id, packet) in self.get_app(chain_id)?.borrow().get_pending_packets()? {
+                if let Some(reason) = self.relay_blocked(chain_id, &packet)? {
+                    report.blocked.push(BlockedPacket {
+                        chain_id: chain_id.clone(),
+                        packet_id,
+                        packet,
+                        reason,
+                    });
+                }
+            }
+        }
+
+        Ok(report)
     }
 
     /// Relay the next `packet` of a specific [`IperApp`](crate::iper_app::IperApp)
@@ -97,7 +415,191 @@ impl Ecosystem {
         chain_id: impl Into<String>,
         packet_id: u64,
     ) -> AppResult<MayResponse> {
-        let app_src = self.get_app(chain_id)?;
+        let chain_id: String = chain_id.into();
+
+        self.relay_and_record(&chain_id, packet_id)
+            .map(|relayed| relayed.response)
+    }
+
+    /// Execute a `msg` on a specific [`IperApp`](crate::iper_app::IperApp).
+    ///
+    /// Equal to call [`App::execute`](cw_multi_test::App::execute) on the inner [`App`](cw_multi_test::App),
+    /// but the tx is included in the current [`Recording`], if any.
+    pub fn execute<C: Serialize>(
+        &self,
+        chain_id: impl Into<String>,
+        sender: Addr,
+        msg: CosmosMsg<C>,
+    ) -> AppResult<AppResponse> {
+        let chain_id: String = chain_id.into();
+        let msg = to_json_binary(&msg)?;
+
+        let res = self.do_execute(&chain_id, &sender, &msg);
+
+        self.record(
+            RecordedAction::Execute {
+                chain_id,
+                sender,
+                msg,
+            },
+            match &res {
+                Ok(response) => MayResponse::Ok(response.clone()),
+                Err(err) => MayResponse::Err(err.to_string()),
+            },
+        )?;
+
+        res
+    }
+
+    /// Execute a privileged [`IperSudoMsg`] on the modules of a specific [`IperApp`](crate::iper_app::IperApp).
+    ///
+    /// Equal to call [`IperApp::sudo_module`](crate::IperApp::sudo_module),
+    /// but the msg is included in the current [`Recording`], if any.
+    pub fn sudo(&self, chain_id: impl Into<String>, msg: IperSudoMsg) -> AppResult<AppResponse> {
+        let chain_id: String = chain_id.into();
+
+        let res = self.do_sudo(&chain_id, &msg);
+
+        self.record(
+            RecordedAction::Sudo { chain_id, msg },
+            match &res {
+                Ok(response) => MayResponse::Ok(response.clone()),
+                Err(err) => MayResponse::Err(err.to_string()),
+            },
+        )?;
+
+        res
+    }
+
+    /// Start recording txs sent via [`Ecosystem::execute`] and `packets` relayed, discarding any previous [`Recording`].
+    pub fn start_recording(&self) {
+        *self.recording.borrow_mut() = Some(Recording::default());
+    }
+
+    /// Stop recording, returning the [`Recording`].
+    pub fn stop_recording(&self) -> AppResult<Recording> {
+        self.recording
+            .borrow_mut()
+            .take()
+            .ok_or(IperError::RecordingNotStarted.into())
+    }
+
+    /// Replay a [`Recording`], usually on a fresh [`Ecosystem`] built with the same setup of the recorded one.
+    ///
+    /// The replay stops with an error at the first step where the response
+    /// or the pending `packets` differ from the [`Recording`].
+    ///
+    /// Replayed steps are not added to the current [`Recording`], if any.
+    pub fn replay(&self, recording: &Recording) -> AppResult<()> {
+        for (index, step) in recording.steps.iter().enumerate() {
+            let response = match &step.action {
+                RecordedAction::Execute {
+                    chain_id,
+                    sender,
+                    msg,
+                } => {
+                    into_may_response(&self.do_execute(chain_id, sender, msg).map(MayResponse::Ok))
+                }
+                RecordedAction::Relay {
+                    chain_id,
+                    packet_id,
+                } => into_may_response(
+                    &self
+                        .do_relay_packet(chain_id, *packet_id)
+                        .map(|relayed| relayed.response),
+                ),
+                RecordedAction::Sudo { chain_id, msg } => {
+                    into_may_response(&self.do_sudo(chain_id, msg).map(MayResponse::Ok))
+                }
+            };
+
+            let pending_packets = self.get_all_pending_packets()?;
+
+            let expected = serde_json::to_string(&step.response)?;
+            let actual = serde_json::to_string(&response)?;
+
+            if expected != actual {
+                bail!(IperError::ReplayDiverged {
+                    step: index,
+                    action: format!("{:?}", step.action),
+                    reason: "response mismatch".to_string(),
+                    expected,
+                    actual
+                })
+            }
+
+            if step.pending_packets != pending_packets {
+                bail!(IperError::ReplayDiverged {
+                    step: index,
+                    action: format!("{:?}", step.action),
+                    reason: "pending packets mismatch".to_string(),
+                    expected: serde_json::to_string(&step.pending_packets)?,
+                    actual: serde_json::to_string(&pending_packets)?
+                })
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Relay a `packet`, adding it to the current [`Recording`], if any.
+    fn relay_and_record(&self, chain_id: &str, packet_id: u64) -> AppResult<RelayedResponse> {
+        let res = self.do_relay_packet(chain_id, packet_id);
+
+        self.record(
+            RecordedAction::Relay {
+                chain_id: chain_id.to_string(),
+                packet_id,
+            },
+            match &res {
+                Ok(relayed) => relayed.response.clone(),
+                Err(err) => MayResponse::Err(err.to_string()),
+            },
+        )?;
+
+        res
+    }
+
+    /// Execute a `json` encoded `CosmosMsg`, registering the emitted `packets` in the [`RelayHistory`] with the tx as origin.
+    fn do_execute(&self, chain_id: &str, sender: &Addr, msg: &Binary) -> AppResult<AppResponse> {
+        self.sync_history(None, None)?;
+
+        let res = self
+            .get_app(chain_id)?
+            .borrow_mut()
+            .execute(sender.clone(), msg);
+
+        self.sync_history(
+            None,
+            Some(OriginTx::Execute {
+                sender: sender.clone(),
+                msg: msg.clone(),
+            }),
+        )?;
+
+        res
+    }
+
+    /// Execute a privileged [`IperSudoMsg`], registering the emitted `packets` in the [`RelayHistory`] with the msg as origin.
+    fn do_sudo(&self, chain_id: &str, msg: &IperSudoMsg) -> AppResult<AppResponse> {
+        self.sync_history(None, None)?;
+
+        let res = self
+            .get_app(chain_id)?
+            .borrow_mut()
+            .sudo_module(msg.clone());
+
+        self.sync_history(None, Some(OriginTx::Sudo { msg: msg.clone() }))?;
+
+        res
+    }
+
+    fn do_relay_packet(&self, chain_id: &str, packet_id: u64) -> AppResult<RelayedResponse> {
+        let chain_id = chain_id.to_string();
+
+        self.sync_history(None, None)?;
+
+        let app_src = self.get_app(&chain_id)?;
 
         let packet = app_src.borrow().get_pending_packet(packet_id)?;
 
@@ -107,11 +609,115 @@ impl Ecosystem {
 
         let app_dest = self.get_app(&channel_info.remote.chain_id)?;
 
-        let response = app_dest.borrow_mut().incoming_packet(packet)?;
+        if let Some(err) = self.relay_blocked(&chain_id, &packet)? {
+            bail!(err)
+        }
+
+        let src_header = app_src.borrow().client_header()?;
+
+        app_dest
+            .borrow_mut()
+            .update_light_client(&chain_id, &src_header)?;
+
+        let tracked = self.history.borrow().get_tracked(&chain_id, packet_id);
+
+        let response = app_dest
+            .borrow_mut()
+            .incoming_packet(packet.clone(), tracked.and_then(|t| t.sequence))?;
 
         app_src.borrow_mut().remove_packet(packet_id)?;
 
-        Ok(response)
+        let local_channel = packet.get_local_channel_id();
+        let remote_channel = channel_info.remote.as_endpoint()?.channel_id;
+        let height = app_dest.borrow().block_info().height;
+
+        let mut history = self.history.borrow_mut();
+
+        let (parent, origin) = history
+            .untrack(&chain_id, packet_id)
+            .map(|tracked| (tracked.parent, tracked.origin))
+            .unwrap_or_default();
+
+        let mut entry = RelayHistoryEntry {
+            id: 0,
+            parent,
+            origin: parent.map_or(origin, |_| None),
+            children: vec![],
+            kind: RelayEventKind::Receive,
+            src_chain_id: chain_id.clone(),
+            src_channel: local_channel.clone(),
+            dest_chain_id: channel_info.remote.chain_id.clone(),
+            dest_channel: remote_channel.clone(),
+            sequence: None,
+            height,
+            data: packet_data(&packet),
+            ack: None,
+            ack_success: None,
+            timed_out: false,
+            dest_response: None,
+            src_response: None,
+        };
+
+        match &packet {
+            IbcPacketType::AckPacket(ack) => {
+                entry.kind = RelayEventKind::Ack;
+                entry.ack = Some(ack.ack.clone());
+                entry.ack_success = Some(ack.success);
+                entry.sequence = Some(ack.original_packet.packet.sequence);
+            }
+            IbcPacketType::Timeout(timeout) => {
+                entry.kind = RelayEventKind::Timeout;
+                entry.sequence = Some(timeout.original_packet.packet.sequence);
+            }
+            _ => {
+                entry.sequence = parent
+                    .and_then(|parent| history.get(parent))
+                    .and_then(|sent| sent.sequence);
+                entry.dest_response = Some(response.clone());
+            }
+        }
+
+        // Ack and timeout packets travel back to the chain that sent the original packet
+        if entry.kind != RelayEventKind::Receive {
+            entry.src_chain_id = channel_info.remote.chain_id.clone();
+            entry.src_channel = remote_channel;
+            entry.dest_chain_id = chain_id.clone();
+            entry.dest_channel = local_channel;
+            entry.src_response = Some(response.clone());
+
+            if let Some(received) = parent.and_then(|parent| history.get_mut(parent)) {
+                received.src_response = Some(response.clone());
+                entry.dest_response = received.dest_response.clone();
+                entry.ack_success = entry.ack_success.or(received.ack_success);
+            }
+        }
+
+        let entry_id = history.push(entry);
+
+        drop(history);
+
+        self.sync_history(Some(entry_id), None)?;
+
+        let history = self.history.borrow();
+
+        let entry = history
+            .get(entry_id)
+            .ok_or(anyhow!("History entry not found: {}", entry_id))?;
+
+        Ok(RelayedResponse {
+            packet,
+            kind: entry.kind,
+            src_chain_id: entry.src_chain_id.clone(),
+            src_channel: entry.src_channel.clone(),
+            dest_chain_id: entry.dest_chain_id.clone(),
+            dest_channel: entry.dest_channel.clone(),
+            sequence: entry.sequence,
+            ack: entry.ack.clone(),
+            ack_success: entry.ack_success,
+            dest_response: entry.dest_response.clone(),
+            src_response: entry.src_response.clone(),
+            response,
+        })
     }
 
     /// Return all pending `packets` between all [`IperApp`](crate::iper_app::IperApp)
@@ -127,10 +733,468 @@ impl Ecosystem {
         Ok(map)
     }
 
+    /// Take a snapshot of the whole [`Ecosystem`] state, returning its id.
+    ///
+    /// The snapshot contains, for each [`IperApp`](crate::iper_app::IperApp), the `storage` (including pending `packets`),
+    /// the `block` info and the `channels` registry, together with the [`RelayHistory`].
+    /// Stored codes are not part of the snapshot.
+    ///
+    /// The same snapshot can be restored multiple times with [`Ecosystem::restore`],
+    /// allowing to branch the same setup into different scenarios.
+    pub fn snapshot(&self) -> u64 {
+        let snapshot = EcosystemSnapshot {
+            apps: self
+                .apps
+                .iter()
+                .map(|(chain_id, app)| (chain_id.clone(), app.borrow().snapshot()))
+                .collect(),
+            history: self.history.borrow().clone(),
+        };
+
+        let mut snapshots = self.snapshots.borrow_mut();
+        let id = snapshots.last_key_value().map(|(k, _)| *k).unwrap_or(0) + 1;
+        snapshots.insert(id, snapshot);
+        id
+    }
+
+    /// Restore a snapshot taken with [`Ecosystem::snapshot`].
+    pub fn restore(&self, snapshot_id: u64) -> AppResult<()> {
+        let snapshots = self.snapshots.borrow();
+
+        let snapshot = snapshots
+            .get(&snapshot_id)
+            .ok_or(IperError::SnapshotNotFound { snapshot_id })?;
+
+        for chain_id in self.apps.keys() {
+            if !snapshot.apps.contains_key(chain_id) {
+                bail!(IperError::ChainAddedAfterSnapshot {
+                    chain_id: chain_id.clone(),
+                    snapshot_id
+                })
+            }
+        }
+
+        for (chain_id, app) in &self.apps {
+            app.borrow_mut().restore(&snapshot.apps[chain_id]);
+        }
+
+        *self.history.borrow_mut() = snapshot.history.clone();
+
+        Ok(())
+    }
+
+    /// Export the whole [`Ecosystem`] state as [`EcosystemFixture`].
+    ///
+    /// See [`EcosystemFixture`] for how to load it in other tests.
+    pub fn export_fixture(&self) -> AppResult<EcosystemFixture> {
+        self.sync_history(None, None)?;
+
+        let mut chains = BTreeMap::new();
+
+        for (chain_id, app) in &self.apps {
+            chains.insert(chain_id.clone(), app.borrow().export_fixture()?);
+        }
+
+        Ok(EcosystemFixture {
+            chains,
+            history: self.history.borrow().clone(),
+        })
+    }
+
+    /// Load an [`EcosystemFixture`] created with [`Ecosystem::export_fixture`].
+    ///
+    /// Each chain of the fixture must have been added with the same `chain_id` and `bech32` prefix,
+    /// and its codes must have been stored with [`IperApp::store_fixture_codes`](crate::IperApp::store_fixture_codes).
+    pub fn load_fixture(&self, fixture: &EcosystemFixture) -> AppResult<()> {
+        for chain_id in fixture.chains.keys() {
+            self.get_app(chain_id)?;
+        }
+
+        for (chain_id, app) in &self.apps {
+            let chain = fixture.get_chain(chain_id)?;
+            let app = app.borrow();
+
+            let chain_prefix = app.chain_prefix()?;
+
+            if chain_prefix != chain.chain_prefix {
+                bail!(IperError::FixtureMismatch {
+                    chain_id: chain_id.clone(),
+                    reason: format!(
+                        "chain_prefix {}, expected: {}",
+                        chain_prefix, chain.chain_prefix
+                    ),
+                })
+            }
+
+            if app.stored_codes() != &chain.codes {
+                bail!(IperError::FixtureMismatch {
+                    chain_id: chain_id.clone(),
+                    reason: "stored codes, use `IperApp::store_fixture_codes` before loading the fixture".to_string(),
+                })
+            }
+        }
+
+        let snapshots = fixture.app_snapshots()?;
+
+        for (chain_id, app) in &self.apps {
+            app.borrow_mut().restore(&snapshots[chain_id]);
+        }
+
+        *self.history.borrow_mut() = fixture.history.clone();
+
+        Ok(())
+    }
+
+    /// Return the [`RelayHistory`] of all `packets` relayed by this [`Ecosystem`].
+    ///
+    /// `packets` currently pending are included as [`RelayEventKind::Send`] entries.
+    pub fn relay_history(&self) -> AppResult<RelayHistory> {
+        self.sync_history(None, None)?;
+        Ok(self.history.borrow().clone())
+    }
+
+    /// Return the current [`PacketStatus`] of a `packet`, identified by the `chain_id` and `channel` of one of the two sides and its `sequence`.
+    pub fn packet_status(
+        &self,
+        chain_id: impl Into<String>,
+        channel: impl Into<String>,
+        sequence: u64,
+    ) -> AppResult<PacketStatus> {
+        self.sync_history(None, None)?;
+        self.history
+            .borrow()
+            .packet_status(&chain_id.into(), &channel.into(), sequence)
+    }
+
+    /// Return a [`PacketAssertion`] over a `packet`, identified by the `chain_id` and `channel` of one of the two sides and its `sequence`.
+    ///
+    /// Panics if the `packet` is not found.
+    #[track_caller]
+    pub fn assert_packet(
+        &self,
+        chain_id: impl Into<String>,
+        channel: impl Into<String>,
+        sequence: u64,
+    ) -> PacketAssertion {
+        let chain_id: String = chain_id.into();
+        let channel: String = channel.into();
+
+        let history = self.relay_history().unwrap();
+
+        let status = history
+            .packet_status(&chain_id, &channel, sequence)
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        PacketAssertion {
+            entries: history
+                .find(&chain_id, &channel, sequence)
+                .into_iter()
+                .cloned()
+                .collect(),
+            status,
+        }
+    }
+
+    /// Assert that there are no pending `packets` on any [`IperApp`](crate::iper_app::IperApp).
+    #[track_caller]
+    pub fn assert_no_pending(&self) {
+        let pending: Vec<String> = self
+            .get_all_pending_packets()
+            .unwrap()
+            .iter()
+            .flat_map(|(chain_id, packets)| {
+                packets
+                    .iter()
+                    .map(|(packet_id, packet)| display_packet(chain_id, *packet_id, packet))
+            })
+            .collect();
+
+        if !pending.is_empty() {
+            panic!(
+                "expected no pending packets, found:\n{}",
+                pending.join("\n")
+            )
+        }
+    }
+
+    /// Register in the [`RelayHistory`] all `packets` emitted since the last sync.
+    ///
+    /// The `sequence` of new `packets` is the one assigned when they have been emitted.
+    /// Their commitment is stored on the chain that sent them.
+    ///
+    /// `cause` is the entry that emitted the new `packets`, [`None`] if they have been emitted by a tx.
+    /// `origin` is the tx, if sent via the [`Ecosystem`].
+    fn sync_history(&self, cause: Option<u64>, origin: Option<OriginTx>) -> AppResult<()> {
+        let mut history = self.history.borrow_mut();
+
+        for (chain_id, shared_app) in &self.apps {
+            let app = shared_app.borrow();
+            let packets = app.get_pending_packets()?;
+            let mut commitments = vec![];
+
+            history.retain_pending(chain_id, &packets);
+
+            for (packet_id, packet) in packets {
+                if history.is_tracked(chain_id, packet_id) {
+                    continue;
+                }
+
+                let mut sequence = None;
+
+                let parent = match &packet {
+                    IbcPacketType::OutgoingPacket(..) | IbcPacketType::OutgoinPacketRaw(..) => {
+                        match app.get_channel_info(packet.get_local_channel_id()) {
+                            Ok(channel) => {
+                                sequence = app.pending_sequence(packet_id)?;
+
+                                if let Some(sequence) = sequence {
+                                    commitments.push((packet_id, packet.clone(), sequence));
+                                }
+
+                                Some(history.push(RelayHistoryEntry {
+                                    id: 0,
+                                    parent: cause,
+                                    origin: origin.clone(),
+                                    children: vec![],
+                                    kind: RelayEventKind::Send,
+                                    src_chain_id: chain_id.clone(),
+                                    src_channel: packet.get_local_channel_id(),
+                                    dest_chain_id: channel.remote.chain_id.clone(),
+                                    dest_channel: channel.remote.as_endpoint()?.channel_id,
+                                    sequence,
+                                    height: app.block_info().height,
+                                    data: packet_data(&packet),
+                                    ack: None,
+                                    ack_success: None,
+                                    timed_out: false,
+                                    dest_response: None,
+                                    src_response: None,
+                                }))
+                            }
+                            Err(..) => cause,
+                        }
+                    }
+                    IbcPacketType::AckPacket(ack) => {
+                        if let Some(received) = cause.and_then(|cause| history.get_mut(cause)) {
+                            received.ack = Some(ack.ack.clone());
+                            received.ack_success = Some(ack.success);
+                        }
+                        cause
+                    }
+                    IbcPacketType::Timeout(..) => {
+                        if let Some(received) = cause.and_then(|cause| history.get_mut(cause)) {
+                            received.timed_out = true;
+                        }
+                        cause
+                    }
+                    IbcPacketType::CloseChannel { .. } => cause,
+                };
+
+                history.track(
+                    chain_id,
+                    packet_id,
+                    TrackedPacket {
+                        packet,
+                        parent,
+                        origin: parent.map_or(origin.clone(), |_| None),
+                        sequence,
+                    },
+                );
+            }
+
+            drop(app);
+
+            for (packet_id, packet, sequence) in commitments {
+                shared_app
+                    .borrow_mut()
+                    .commit_packet(packet_id, &packet, sequence)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a step to the current [`Recording`], if any.
+    fn record(&self, action: RecordedAction, response: MayResponse) -> AppResult<()> {
+        if self.recording.borrow().is_none() {
+            return Ok(());
+        }
+
+        let pending_packets = self.get_all_pending_packets()?;
+
+        if let Some(recording) = self.recording.borrow_mut().as_mut() {
+            recording.steps.push(RecordedStep {
+                action,
+                response,
+                pending_packets,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Create the [`LightClient`]s tracking each other on both [`IperApp`](crate::iper_app::IperApp), if not already created.
+    fn create_light_clients(
+        &self,
+        app_1: &Rc<RefCell<dyn IperAppRef>>,
+        app_2: &Rc<RefCell<dyn IperAppRef>>,
+    ) -> AppResult<()> {
+        let trusting_period = self.trusting_period.unwrap_or(DEFAULT_TRUSTING_PERIOD);
+
+        let (chain_id_1, header_1) = {
+            let app = app_1.borrow();
+            (app.chain_id().to_string(), app.client_header()?)
+        };
+        let (chain_id_2, header_2) = {
+            let app = app_2.borrow();
+            (app.chain_id().to_string(), app.client_header()?)
+        };
+
+        app_1
+            .borrow_mut()
+            .create_light_client(&chain_id_2, &header_2, trusting_period)?;
+        app_2
+            .borrow_mut()
+            .create_light_client(&chain_id_1, &header_1, trusting_period)?;
+
+        Ok(())
+    }
+
+    /// Validate the identifiers of the `local` side of a `channel`
+    /// and check that its `connection` is open and connected to the `remote` side.
+    fn check_channel_connection(
+        &self,
+        local: &IbcChannelCreator,
+        remote: &IbcChannelCreator,
+    ) -> AppResult<()> {
+        validate_port_id(&local.port.port_name())?;
+        validate_connection_id(&local.connection_id)?;
+
+        let connection = self
+            .get_app(&local.chain_id)?
+            .borrow()
+            .connection(&local.connection_id)?;
+
+        if connection.state != ConnectionState::Open {
+            bail!(IperError::ConnectionNotOpen {
+                chain_id: local.chain_id.clone(),
+                connection_id: local.connection_id.clone(),
+                state: connection.state,
+            })
+        }
+
+        if connection.counterparty.chain_id != remote.chain_id
+            || connection.counterparty.connection_id.as_ref() != Some(&remote.connection_id)
+        {
+            bail!(IperError::ConnectionMismatch {
+                chain_id: local.chain_id.clone(),
+                connection_id: local.connection_id.clone(),
+                counterparty_chain_id: remote.chain_id.clone(),
+                counterparty_connection_id: remote.connection_id.clone(),
+            })
+        }
+
+        Ok(())
+    }
+
+    /// Return the first pending `packet` of `chain_id` that can be delivered, if any.
+    fn next_relayable_packet(&self, chain_id: &str) -> AppResult<Option<u64>> {
+        let packets = self.get_app(chain_id)?.borrow().get_pending_packets()?;
+
+        for (packet_id, packet) in packets {
+            if self.relay_blocked(chain_id, &packet)?.is_none() {
+                return Ok(Some(packet_id));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Return the reason why a pending `packet` of `chain_id` can't be delivered, if any.
+    fn relay_blocked(
+        &self,
+        chain_id: &str,
+        packet: &IbcPacketType,
+    ) -> AppResult<Option<IperError>> {
+        if self.relayer_paused.get() {
+            return Ok(Some(IperError::RelayerPaused));
+        }
+
+        let channel = packet.get_local_channel_id();
+
+        if self
+            .paused_channels
+            .borrow()
+            .contains(&(chain_id.to_string(), channel.clone()))
+        {
+            return Ok(Some(IperError::ChannelPaused {
+                chain_id: chain_id.to_string(),
+                channel,
+            }));
+        }
+
+        let dest_chain_id = self
+            .get_app(chain_id)?
+            .borrow()
+            .get_channel_info(channel)?
+            .remote
+            .chain_id;
+
+        let app_dest = self.get_app(&dest_chain_id)?;
+        let app_dest = app_dest.borrow();
+
+        if app_dest.is_halted()? {
+            return Ok(Some(IperError::ChainHalted {
+                chain_id: dest_chain_id,
+            }));
+        }
+
+        let client = app_dest.light_client(chain_id)?;
+        let status = client.status(app_dest.block_info().time);
+
+        if status != ClientStatus::Active {
+            return Ok(Some(IperError::ClientNotActive {
+                chain_id: dest_chain_id,
+                client_id: client.client_id,
+                status,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Return the (`chain_id`, `channel-id`) pairs of both sides of a `channel`.
+    fn channel_sides(
+        &self,
+        chain_id: impl Into<String>,
+        channel: impl Into<String>,
+    ) -> AppResult<[(String, String); 2]> {
+        let chain_id: String = chain_id.into();
+        let channel: String = channel.into();
+
+        let remote = self
+            .get_app(&chain_id)?
+            .borrow()
+            .get_channel_info(channel.clone())?
+            .remote;
+
+        Ok([
+            (chain_id, channel),
+            (remote.chain_id.clone(), remote.as_endpoint()?.channel_id),
+        ])
+    }
+
     fn get_app(&self, chain_id: impl Into<String>) -> AppResult<&Rc<RefCell<dyn IperAppRef>>> {
         let chain_id: String = chain_id.into();
         self.apps
             .get(&chain_id)
-            .ok_or(anyhow!("App not found for chain_id: {}", chain_id))
+            .ok_or(IperError::ChainNotFound { chain_id }.into())
+    }
+}
+
+fn into_may_response(res: &AppResult<MayResponse>) -> MayResponse {
+    match res {
+        Ok(response) => response.clone(),
+        Err(err) => MayResponse::Err(err.to_string()),
     }
 }
@@ -1,2 +1,332 @@
+use thiserror::Error;
+
+use crate::{client::ClientStatus, connection::ConnectionState, ibc::IbcChannelStatus};
+
 /// Default [`Result`] used in the project
 pub type AppResult<T> = Result<T, anyhow::Error>;
+
+/// Errors raised by `cw-iper-test`.
+///
+/// Since [`AppResult`] is based on [`anyhow::Error`], the variant can be matched via `downcast_ref`:
+/// ```ignore
+/// let err = eco.relay_next_packet("osmosis").unwrap_err();
+///
+/// assert_eq!(
+///     err.downcast_ref::<IperError>(),
+///     Some(&IperError::NoPendingPackets {
+///         chain_id: "osmosis".to_string()
+///     })
+/// );
+/// ```
+#[derive(Error, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum IperError {
+    /// No [`IperApp`](crate::IperApp) with this `chain_id` has been added to the [`Ecosystem`](crate::Ecosystem).
+    #[error("App not found for chain_id: {chain_id}")]
+    ChainNotFound {
+        /// `chain_id` requested.
+        chain_id: String,
+    },
+
+    /// The `channel` doesn't exist on the chain.
+    #[error("Channel not found: {chain_id}/{channel}")]
+    ChannelNotFound {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `channel-id` requested.
+        channel: String,
+    },
+
+    /// The `channel-id` is not in the `channel-{n}` format.
+    #[error("Invalid channel-id: {channel}")]
+    InvalidChannelId {
+        /// `channel-id` provided.
+        channel: String,
+    },
+
+    /// The `channel` is not in the status required by the operation.
+    #[error("Channel {chain_id}/{channel} is not open, status: {status:?}")]
+    ChannelNotOpen {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `channel-id` of the channel.
+        channel: String,
+        /// Current status of the channel.
+        status: IbcChannelStatus,
+    },
+
+    /// The `channel` handshake can't proceed from the current status.
+    #[error("Invalid status for channel {chain_id}/{channel}: {status:?}")]
+    InvalidChannelStatus {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `channel-id` of the channel.
+        channel: String,
+        /// Current status of the channel.
+        status: IbcChannelStatus,
+    },
+
+    /// No [`LightClient`](crate::LightClient) tracking the counterparty exists on the chain.
+    #[error("Light client of {counterparty_chain_id} not found on {chain_id}")]
+    ClientNotFound {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `chain_id` of the tracked chain.
+        counterparty_chain_id: String,
+    },
+
+    /// The [`LightClient`](crate::LightClient) can't be updated, since it's expired or frozen.
+    #[error("Light client {client_id} on {chain_id} is not active, status: {status:?}")]
+    ClientNotActive {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `client_id` of the client.
+        client_id: String,
+        /// Current status of the client.
+        status: ClientStatus,
+    },
+
+    /// The identifier is not valid according to `ICS-24`.
+    #[error("Invalid identifier {identifier}: {reason}")]
+    InvalidIdentifier {
+        /// Identifier provided.
+        identifier: String,
+        /// Why the identifier is not valid.
+        reason: String,
+    },
+
+    /// The `connection` doesn't exist on the chain.
+    #[error("Connection not found: {chain_id}/{connection_id}")]
+    ConnectionNotFound {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `connection_id` requested.
+        connection_id: String,
+    },
+
+    /// The `connection` handshake is not completed.
+    #[error("Connection {chain_id}/{connection_id} is not open, state: {state:?}")]
+    ConnectionNotOpen {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `connection_id` of the connection.
+        connection_id: String,
+        /// Current state of the connection.
+        state: ConnectionState,
+    },
+
+    /// The two sides of a `channel` are not on the same `connection`.
+    #[error("Connection {chain_id}/{connection_id} is not connected to {counterparty_chain_id}/{counterparty_connection_id}")]
+    ConnectionMismatch {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `connection_id` on the chain.
+        connection_id: String,
+        /// `chain_id` of the other side of the `channel`.
+        counterparty_chain_id: String,
+        /// `connection_id` of the other side of the `channel`.
+        counterparty_connection_id: String,
+    },
+
+    /// The `contract` bound to a `port` has been stored without `ibc entry points`.
+    #[error("Missing ibc entry points for contract {contract} (code_id {code_id}) on {chain_id}")]
+    MissingIbcEntryPoints {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// Address of the `contract`.
+        contract: String,
+        /// `code_id` of the `contract`.
+        code_id: u64,
+    },
+
+    /// No [`IbcApplication`](crate::IbcApplication) is registered for the `port`.
+    #[error("Application not found: {port}")]
+    ApplicationNotFound {
+        /// `port` name requested.
+        port: String,
+    },
+
+    /// No [`StargateApplication`](crate::StargateApplication) is registered for the `name`.
+    #[error("Stargate application not found: {name}")]
+    StargateApplicationNotFound {
+        /// Name requested.
+        name: String,
+    },
+
+    /// No [`StargateApplication`](crate::StargateApplication) handles the `type_url`.
+    #[error("No stargate application handles the type_url: {type_url}")]
+    UnhandledTypeUrl {
+        /// `type_url` of the msg, or path of the query.
+        type_url: String,
+    },
+
+    /// The message or query is not supported by the module.
+    #[error("Unsupported message on {module}: {msg}")]
+    UnsupportedMsg {
+        /// Name of the module, or of the [`IbcApplication`](crate::IbcApplication).
+        module: String,
+        /// Message or query received.
+        msg: String,
+    },
+
+    /// The `ibc timeout` has neither a timestamp nor a block height.
+    #[error("Invalid ibc timeout: neither timestamp nor height set")]
+    InvalidTimeout,
+
+    /// The `type_url` is already handled by another [`StargateApplication`](crate::StargateApplication).
+    #[error("Duplicated type_url among applications: {type_url}")]
+    DuplicatedTypeUrl {
+        /// `type_url` already registered.
+        type_url: String,
+    },
+
+    /// The signer declared in a stargate msg is not the sender of the msg.
+    #[error("Invalid signer of {type_url}: expected {sender}, got {signer}")]
+    InvalidSigner {
+        /// `type_url` of the msg.
+        type_url: String,
+        /// Sender of the msg.
+        sender: String,
+        /// Signer declared in the msg.
+        signer: String,
+    },
+
+    /// The outgoing `packet` has been relayed without the `sequence` assigned when it was emitted.
+    #[error("Sequence not assigned to the packet sent from {chain_id}/{channel}")]
+    SequenceNotAssigned {
+        /// `chain_id` of the chain that sent the `packet`.
+        chain_id: String,
+        /// `channel-id` on the chain that sent the `packet`.
+        channel: String,
+    },
+
+    /// The pending `packet` doesn't exist on the chain.
+    #[error("Packet not found: {chain_id}, packet_id: {packet_id}")]
+    PacketNotFound {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// Id of the pending `packet`.
+        packet_id: u64,
+    },
+
+    /// There are no pending `packets` on the chain.
+    #[error("No pending packets on {chain_id}")]
+    NoPendingPackets {
+        /// `chain_id` of the chain.
+        chain_id: String,
+    },
+
+    /// The `packet` has timed out on the destination chain.
+    #[error("Packet has timed out: {chain_id}/{channel}, sequence: {sequence}")]
+    PacketTimedOut {
+        /// `chain_id` of the destination chain.
+        chain_id: String,
+        /// `channel-id` on the destination chain.
+        channel: String,
+        /// `packet` sequence.
+        sequence: u64,
+    },
+
+    /// There is no commitment for the `packet` on the chain, it has never been sent or it has already been acked or timed out.
+    #[error("Packet commitment not found: {port}/{channel}, sequence: {sequence}")]
+    PacketCommitmentNotFound {
+        /// `port` of the `packet` on the chain.
+        port: String,
+        /// `channel-id` of the `packet` on the chain.
+        channel: String,
+        /// `packet` sequence.
+        sequence: u64,
+    },
+
+    /// The `packet` is not part of the [`RelayHistory`](crate::RelayHistory).
+    #[error("Packet not found: chain_id: {chain_id}, channel: {channel}, sequence: {sequence}")]
+    UnknownPacket {
+        /// `chain_id` provided.
+        chain_id: String,
+        /// `channel-id` provided.
+        channel: String,
+        /// `sequence` provided.
+        sequence: u64,
+    },
+
+    /// The chain has been halted by [`Ecosystem::halt_chain`](crate::Ecosystem::halt_chain).
+    #[error("Chain halted: {chain_id}")]
+    ChainHalted {
+        /// `chain_id` of the chain.
+        chain_id: String,
+    },
+
+    /// Relaying on the `channel` has been paused by [`Ecosystem::pause_channel`](crate::Ecosystem::pause_channel).
+    #[error("Channel paused: {chain_id}/{channel}")]
+    ChannelPaused {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// `channel-id` of the channel.
+        channel: String,
+    },
+
+    /// The relayer has been paused by [`Ecosystem::pause_relayer`](crate::Ecosystem::pause_relayer).
+    #[error("Relayer paused")]
+    RelayerPaused,
+
+    /// [`Ecosystem::relay_all_packets`](crate::Ecosystem::relay_all_packets) reached the max number of iterations.
+    #[error("Max relay iterations ({max}) reached with packets still pending, possible infinite packet loop. Last relayed: {chain_id}/{channel}, sequence: {sequence:?}")]
+    MaxRelayIterations {
+        /// Max number of iterations.
+        max: usize,
+        /// `chain_id` of the chain that sent the original `packet` of the last delivery.
+        chain_id: String,
+        /// `channel-id` on the chain that sent the original `packet` of the last delivery.
+        channel: String,
+        /// `sequence` of the last delivery.
+        sequence: Option<u64>,
+    },
+
+    /// The snapshot doesn't exist.
+    #[error("Snapshot not found: {snapshot_id}")]
+    SnapshotNotFound {
+        /// Id of the snapshot.
+        snapshot_id: u64,
+    },
+
+    /// The chain has been added to the [`Ecosystem`](crate::Ecosystem) after the snapshot has been taken.
+    #[error("Chain {chain_id} added after snapshot {snapshot_id}, unable to restore")]
+    ChainAddedAfterSnapshot {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// Id of the snapshot.
+        snapshot_id: u64,
+    },
+
+    /// The [`EcosystemFixture`](crate::EcosystemFixture) doesn't match the chain it is loaded on.
+    #[error("Fixture mismatch for {chain_id}: {reason}")]
+    FixtureMismatch {
+        /// `chain_id` of the chain.
+        chain_id: String,
+        /// What doesn't match.
+        reason: String,
+    },
+
+    /// [`Ecosystem::stop_recording`](crate::Ecosystem::stop_recording) has been called without starting a recording.
+    #[error("Recording not started")]
+    RecordingNotStarted,
+
+    /// [`Ecosystem::replay`](crate::Ecosystem::replay) diverged from the [`Recording`](crate::Recording).
+    #[error("Replay diverged at step {step} ({action}): {reason}\nexpected: {expected}\nactual: {actual}")]
+    ReplayDiverged {
+        /// Index of the step.
+        step: usize,
+        /// Action of the step.
+        action: String,
+        /// What diverged.
+        reason: String,
+        /// Recorded value, as `json`.
+        expected: String,
+        /// Replayed value, as `json`.
+        actual: String,
+    },
+
+    /// The thread of a [`SyncEcosystem`](crate::SyncEcosystem) is not running, because its `setup` panicked.
+    #[error("Ecosystem thread stopped")]
+    EcosystemStopped,
+}
@@ -0,0 +1,143 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use cosmwasm_std::{Binary, BlockInfo};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{AppResult, IperError},
+    history::RelayHistory,
+    ibc::{Channels, IbcChannelCreator, IbcChannelStatus, IbcChannelWrapper},
+    snapshot::AppSnapshot,
+};
+
+/// Serializable export of a whole [`Ecosystem`](crate::Ecosystem), created with [`Ecosystem::export_fixture`](crate::Ecosystem::export_fixture).
+///
+/// A fixture allows to build a complex setup once, save it on disk as `json` and load it in many tests:
+/// ```ignore
+/// // Build the fixture once
+/// eco.export_fixture()?.save("fixtures/deployment.json")?;
+///
+/// // Load it in the tests
+/// let fixture = EcosystemFixture::load("fixtures/deployment.json")?;
+///
+/// let osmosis = IperAppBuilder::new("osmo")
+///     .with_ibc_app(Ics20)?
+///     .build(no_init)
+///     .into_iper_app("osmosis")?;
+///
+/// osmosis
+///     .borrow_mut()
+///     .store_fixture_codes(&fixture, |name| match name {
+///         "counter" => Some(counter_contract()),
+///         _ => None,
+///     })?;
+///
+/// let eco = Ecosystem::default().add_app(osmosis.clone());
+/// eco.load_fixture(&fixture)?;
+/// ```
+///
+/// Stored codes are kept in memory and can't be serialized: only their `code_id` and name are exported.
+/// Codes must be stored via [`IperApp::store_named_ibc_code`](crate::IperApp::store_named_ibc_code) to be stored again
+/// with [`IperApp::store_fixture_codes`](crate::IperApp::store_fixture_codes).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EcosystemFixture {
+    /// State of each chain, by `chain_id`.
+    pub chains: BTreeMap<String, ChainFixture>,
+    /// [`RelayHistory`] of the [`Ecosystem`](crate::Ecosystem).
+    pub history: RelayHistory,
+}
+
+impl EcosystemFixture {
+    /// Serialize the fixture as `json`.
+    pub fn to_json(&self) -> AppResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserialize a fixture from `json`.
+    pub fn from_json(json: &str) -> AppResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Save the fixture as `json` file.
+    pub fn save(&self, path: impl AsRef<Path>) -> AppResult<()> {
+        Ok(fs::write(path, self.to_json()?)?)
+    }
+
+    /// Load a fixture from a `json` file.
+    pub fn load(path: impl AsRef<Path>) -> AppResult<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Return the state of a specific chain.
+    pub fn get_chain(&self, chain_id: &str) -> AppResult<&ChainFixture> {
+        self.chains.get(chain_id).ok_or_else(|| {
+            IperError::ChainNotFound {
+                chain_id: chain_id.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Build the [`AppSnapshot`] of each chain.
+    pub(crate) fn app_snapshots(&self) -> AppResult<BTreeMap<String, AppSnapshot>> {
+        let mut snapshots = BTreeMap::new();
+
+        for (chain_id, chain) in &self.chains {
+            let mut channels = Channels::new(chain_id);
+
+            for channel in &chain.channels {
+                let mut wrapper =
+                    IbcChannelWrapper::new(channel.local.clone(), channel.remote.clone());
+                wrapper.status = channel.status.clone();
+
+                channels.insert(channel.local.channel_id()?, wrapper)?;
+            }
+
+            snapshots.insert(
+                chain_id.clone(),
+                AppSnapshot {
+                    storage: chain
+                        .storage
+                        .iter()
+                        .map(|(key, value)| (key.to_vec(), value.to_vec()))
+                        .collect(),
+                    block: chain.block.clone(),
+                    channels,
+                },
+            );
+        }
+
+        Ok(snapshots)
+    }
+}
+
+/// State of a single [`IperApp`](crate::IperApp) inside an [`EcosystemFixture`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ChainFixture {
+    /// `chain_id` of the chain.
+    pub chain_id: String,
+    /// `bech32` prefix of the chain.
+    pub chain_prefix: String,
+    /// Codes stored, by `code_id`, with their name if any.
+    pub codes: BTreeMap<u64, Option<String>>,
+    /// Current block.
+    pub block: BlockInfo,
+    /// Whole content of the `storage` (including pending `packets`).
+    pub storage: Vec<(Binary, Binary)>,
+    /// `ibc channels` of the chain.
+    pub channels: Vec<ChannelFixture>,
+}
+
+/// `ibc channel` inside a [`ChainFixture`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ChannelFixture {
+    /// Local side of the channel.
+    pub local: IbcChannelCreator,
+    /// Remote side of the channel.
+    pub remote: IbcChannelCreator,
+    /// Status of the channel.
+    pub status: IbcChannelStatus,
+}
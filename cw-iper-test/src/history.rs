@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{from_json, Addr, Binary};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{AppResult, IperError},
+    ibc_module::IbcPacketType,
+    iper_app::{IperSudoMsg, MayResponse},
+};
+
+/// Kind of event stored in a [`RelayHistoryEntry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelayEventKind {
+    /// A `packet` has been emitted on the source chain and is waiting to be relayed.
+    Send,
+    /// A `packet` has been delivered on the destination chain.
+    Receive,
+    /// An `acknowledgement` has been delivered back on the source chain.
+    Ack,
+    /// A `timeout` has been delivered back on the source chain.
+    Timeout,
+}
+
+/// Tx that emitted the `packet` of a root [`RelayHistoryEntry`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OriginTx {
+    /// Tx sent via [`Ecosystem::execute`](crate::Ecosystem::execute).
+    Execute {
+        /// Sender of the tx.
+        sender: Addr,
+        /// `json` encoded `CosmosMsg`.
+        msg: Binary,
+    },
+    /// Privileged msg sent via [`Ecosystem::sudo`](crate::Ecosystem::sudo).
+    Sudo {
+        /// The privileged msg.
+        msg: IperSudoMsg,
+    },
+}
+
+/// Single event of the relay history of an [`Ecosystem`](crate::Ecosystem).
+///
+/// `src` and `dest` fields always refer to the direction of the original `packet`,
+/// also for [`RelayEventKind::Ack`] and [`RelayEventKind::Timeout`] entries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RelayHistoryEntry {
+    /// Unique id of the entry.
+    pub id: u64,
+    /// Entry that caused this entry. [`None`] if the entry has been caused by a tx, see [`RelayHistoryEntry::origin`].
+    pub parent: Option<u64>,
+    /// Tx that caused this entry, only on root entries.
+    /// [`None`] also if the tx has been sent directly on the inner [`App`](cw_multi_test::App) instead of via the [`Ecosystem`](crate::Ecosystem).
+    pub origin: Option<OriginTx>,
+    /// Entries caused by this entry.
+    pub children: Vec<u64>,
+    /// Kind of event.
+    pub kind: RelayEventKind,
+    /// `chain_id` of the chain that sent the original `packet`.
+    pub src_chain_id: String,
+    /// `channel-id` on the chain that sent the original `packet`.
+    pub src_channel: String,
+    /// `chain_id` of the chain that received the original `packet`.
+    pub dest_chain_id: String,
+    /// `channel-id` on the chain that received the original `packet`.
+    pub dest_channel: String,
+    /// `packet` sequence. [`None`] only if the `packet` has been emitted on an unknown channel.
+    pub sequence: Option<u64>,
+    /// Block height of the chain where the event happened.
+    pub height: u64,
+    /// Raw data of the original `packet`.
+    pub data: Binary,
+    /// `acknowledgement` written on the destination chain, if any.
+    pub ack: Option<Binary>,
+    /// Whether the `acknowledgement` is a success, if any.
+    pub ack_success: Option<bool>,
+    /// `true` if the `packet` has timed out when delivered on the destination chain.
+    pub timed_out: bool,
+    /// Response of the destination chain on `packet` receive.
+    pub dest_response: Option<MayResponse>,
+    /// Response of the source chain on `acknowledgement` / `timeout`.
+    pub src_response: Option<MayResponse>,
+}
+
+impl RelayHistoryEntry {
+    /// `chain_id` of the chain where the event happened.
+    pub fn chain_id(&self) -> &str {
+        match self.kind {
+            RelayEventKind::Send | RelayEventKind::Ack | RelayEventKind::Timeout => {
+                &self.src_chain_id
+            }
+            RelayEventKind::Receive => &self.dest_chain_id,
+        }
+    }
+
+    /// Try to decode the `packet` data as `json`.
+    pub fn decoded_data(&self) -> Option<serde_json::Value> {
+        from_json(&self.data).ok()
+    }
+}
+
+/// Node of the tree returned by [`RelayHistory::tree`].
+#[derive(Debug, Clone)]
+pub struct RelayTreeNode {
+    /// Entry of the node.
+    pub entry: RelayHistoryEntry,
+    /// Nodes caused by this node.
+    pub children: Vec<RelayTreeNode>,
+}
+
+/// Lifecycle status of a `packet`, returned by [`RelayHistory::packet_status`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PacketStatus {
+    /// The `packet` has been sent and is waiting to be relayed.
+    Pending,
+    /// The `packet` has been received on the destination chain.
+    /// If an `acknowledgement` has been written, it is waiting to be relayed back.
+    Received {
+        /// `acknowledgement` written, if any.
+        ack: Option<Binary>,
+        /// Whether the `acknowledgement` is a success, if any.
+        success: Option<bool>,
+    },
+    /// The `acknowledgement` has been relayed back to the source chain.
+    Acknowledged {
+        /// `acknowledgement` relayed.
+        ack: Binary,
+        /// Whether the `acknowledgement` is a success.
+        success: bool,
+    },
+    /// The `packet` has timed out.
+    TimedOut {
+        /// `true` if the `timeout` has been relayed back to the source chain.
+        relayed: bool,
+    },
+    /// The `packet` (or its `acknowledgement` / `timeout`) has been removed before being relayed.
+    Dropped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TrackedPacket {
+    pub packet: IbcPacketType,
+    /// Entry to use as parent when the `packet` is delivered.
+    pub parent: Option<u64>,
+    /// Tx that emitted the `packet`, used as origin when the `packet` is delivered without a parent.
+    pub origin: Option<OriginTx>,
+    pub sequence: Option<u64>,
+}
+
+/// History of all `packets` sent, received, acknowledged and timed out inside an [`Ecosystem`](crate::Ecosystem).
+///
+/// Each [`RelayHistoryEntry`] is linked to the entry that caused it,
+/// allowing to inspect the history as a tree (e.g. `ibc hook` -> `transfer` -> `ack`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RelayHistory {
+    entries: BTreeMap<u64, RelayHistoryEntry>,
+    /// `chain_id` -> `packet_id` -> [`TrackedPacket`]
+    pending: BTreeMap<String, BTreeMap<u64, TrackedPacket>>,
+}
+
+impl RelayHistory {
+    /// Return all entries ordered by `id`.
+    pub fn entries(&self) -> impl Iterator<Item = &RelayHistoryEntry> {
+        self.entries.values()
+    }
+
+    /// Return a specific entry.
+    pub fn get(&self, id: u64) -> Option<&RelayHistoryEntry> {
+        self.entries.get(&id)
+    }
+
+    /// Return all entries related to a `packet`, identified by the `chain_id` and `channel` of one of the two sides and its `sequence`.
+    pub fn find(&self, chain_id: &str, channel: &str, sequence: u64) -> Vec<&RelayHistoryEntry> {
+        self.entries
+            .values()
+            .filter(|entry| {
+                entry.sequence == Some(sequence)
+                    && ((entry.src_chain_id == chain_id && entry.src_channel == channel)
+                        || (entry.dest_chain_id == chain_id && entry.dest_channel == channel))
+            })
+            .collect()
+    }
+
+    /// Return the current [`PacketStatus`] of a `packet`, identified by the `chain_id` and `channel` of one of the two sides and its `sequence`.
+    pub fn packet_status(
+        &self,
+        chain_id: &str,
+        channel: &str,
+        sequence: u64,
+    ) -> AppResult<PacketStatus> {
+        let entries = self.find(chain_id, channel, sequence);
+
+        let find_kind = |kind: RelayEventKind| entries.iter().find(|entry| entry.kind == kind);
+
+        if find_kind(RelayEventKind::Timeout).is_some() {
+            return Ok(PacketStatus::TimedOut { relayed: true });
+        }
+
+        if let Some(entry) = find_kind(RelayEventKind::Ack) {
+            return Ok(PacketStatus::Acknowledged {
+                ack: entry.ack.clone().unwrap_or_default(),
+                success: entry.ack_success.unwrap_or_default(),
+            });
+        }
+
+        if let Some(entry) = find_kind(RelayEventKind::Receive) {
+            let waiting = entry.ack.is_some() || entry.timed_out;
+
+            if waiting && !self.is_pending(entry.id) {
+                return Ok(PacketStatus::Dropped);
+            }
+
+            if entry.timed_out {
+                return Ok(PacketStatus::TimedOut { relayed: false });
+            }
+
+            return Ok(PacketStatus::Received {
+                ack: entry.ack.clone(),
+                success: entry.ack_success,
+            });
+        }
+
+        if let Some(entry) = find_kind(RelayEventKind::Send) {
+            return Ok(if self.is_pending(entry.id) {
+                PacketStatus::Pending
+            } else {
+                PacketStatus::Dropped
+            });
+        }
+
+        Err(IperError::UnknownPacket {
+            chain_id: chain_id.to_string(),
+            channel: channel.to_string(),
+            sequence,
+        }
+        .into())
+    }
+
+    /// Return all entries without a parent.
+    pub fn roots(&self) -> Vec<&RelayHistoryEntry> {
+        self.entries
+            .values()
+            .filter(|entry| entry.parent.is_none())
+            .collect()
+    }
+
+    /// Return the tree of entries starting from a specific entry.
+    pub fn tree(&self, id: u64) -> Option<RelayTreeNode> {
+        let entry = self.entries.get(&id)?.clone();
+
+        let children = entry
+            .children
+            .iter()
+            .filter_map(|child| self.tree(*child))
+            .collect();
+
+        Some(RelayTreeNode { entry, children })
+    }
+
+    /// Return the trees of all entries without a parent.
+    pub fn trees(&self) -> Vec<RelayTreeNode> {
+        self.roots()
+            .into_iter()
+            .filter_map(|entry| self.tree(entry.id))
+            .collect()
+    }
+
+    pub(crate) fn push(&mut self, mut entry: RelayHistoryEntry) -> u64 {
+        let id = self.entries.last_key_value().map(|(k, _)| *k).unwrap_or(0) + 1;
+
+        entry.id = id;
+
+        if let Some(parent) = entry
+            .parent
+            .and_then(|parent| self.entries.get_mut(&parent))
+        {
+            parent.children.push(id);
+        }
+
+        self.entries.insert(id, entry);
+
+        id
+    }
+
+    pub(crate) fn get_mut(&mut self, id: u64) -> Option<&mut RelayHistoryEntry> {
+        self.entries.get_mut(&id)
+    }
+
+    /// Stop tracking `packets` no longer pending on `chain_id`.
+    pub(crate) fn retain_pending(
+        &mut self,
+        chain_id: &str,
+        packets: &BTreeMap<u64, IbcPacketType>,
+    ) {
+        if let Some(tracked) = self.pending.get_mut(chain_id) {
+            tracked.retain(|packet_id, tracked| packets.get(packet_id) == Some(&tracked.packet));
+        }
+    }
+
+    /// Return `true` if a `packet` waiting to be delivered has been caused by the entry.
+    fn is_pending(&self, entry_id: u64) -> bool {
+        self.pending
+            .values()
+            .flat_map(|packets| packets.values())
+            .any(|tracked| tracked.parent == Some(entry_id))
+    }
+
+    pub(crate) fn is_tracked(&self, chain_id: &str, packet_id: u64) -> bool {
+        self.pending
+            .get(chain_id)
+            .is_some_and(|packets| packets.contains_key(&packet_id))
+    }
+
+    pub(crate) fn track(&mut self, chain_id: &str, packet_id: u64, tracked: TrackedPacket) {
+        self.pending
+            .entry(chain_id.to_string())
+            .or_default()
+            .insert(packet_id, tracked);
+    }
+
+    pub(crate) fn get_tracked(&self, chain_id: &str, packet_id: u64) -> Option<TrackedPacket> {
+        self.pending
+            .get(chain_id)
+            .and_then(|packets| packets.get(&packet_id))
+            .cloned()
+    }
+
+    pub(crate) fn untrack(&mut self, chain_id: &str, packet_id: u64) -> Option<TrackedPacket> {
+        self.pending
+            .get_mut(chain_id)
+            .and_then(|packets| packets.remove(&packet_id))
+    }
+}
+
+pub(crate) fn packet_data(packet: &IbcPacketType) -> Binary {
+    match packet {
+        IbcPacketType::AckPacket(packet) => packet.original_packet.packet.data.clone(),
+        IbcPacketType::OutgoingPacket(packet) => packet.data.clone(),
+        IbcPacketType::OutgoinPacketRaw(packet) => packet.data.clone(),
+        IbcPacketType::CloseChannel { .. } => Binary::default(),
+        IbcPacketType::Timeout(packet) => packet.original_packet.packet.data.clone(),
+    }
+}
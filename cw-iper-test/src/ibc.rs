@@ -1,4 +1,4 @@
-use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+use std::collections::BTreeMap;
 
 use anyhow::{anyhow, bail};
 use cosmwasm_schema::cw_serde;
@@ -9,51 +9,65 @@ use cosmwasm_std::{
 use ibc_proto::ibc::{apps::transfer::v2::FungibleTokenPacketData, core::client::v1::Height};
 
 use crate::{
-    error::AppResult,
+    error::{AppResult, IperError},
+    fixture::ChannelFixture,
     ibc_module::{IbcPacketType, OutgoingPacket},
     IbcApplication,
 };
 
+/// `channel` stored on an [`IperApp`](crate::IperApp), with both sides.
 #[derive(Clone)]
 #[non_exhaustive]
 pub struct IbcChannelWrapper {
+    /// Local side of the `channel`.
     pub local: IbcChannelCreator,
+    /// Remote side of the `channel`.
     pub remote: IbcChannelCreator,
+    /// Handshake status of the `channel`.
     pub status: IbcChannelStatus,
-    pub sequence: Rc<RefCell<u64>>,
 }
 
 impl IbcChannelWrapper {
-    pub fn new(
-        local: IbcChannelCreator,
-        remote: IbcChannelCreator,
-        sequence: Rc<RefCell<u64>>,
-    ) -> Self {
+    /// Create a `channel` in [`IbcChannelStatus::Created`] status.
+    pub fn new(local: IbcChannelCreator, remote: IbcChannelCreator) -> Self {
         Self {
             local,
             remote,
             status: IbcChannelStatus::Created,
-            sequence,
         }
     }
 }
 
 #[derive(Default, Clone)]
 pub struct Channels {
+    /// `chain_id` of the chain owning the channels, used in errors.
+    pub(crate) chain_id: String,
     channels: BTreeMap<u64, IbcChannelWrapper>,
 }
 
 impl Channels {
+    pub(crate) fn new(chain_id: impl Into<String>) -> Self {
+        Self {
+            chain_id: chain_id.into(),
+            channels: BTreeMap::new(),
+        }
+    }
+
     pub fn get(&self, id: impl Channelable) -> AppResult<&IbcChannelWrapper> {
         self.channels
             .get(&id.as_channel_number()?)
-            .ok_or(anyhow!("channel not found"))
+            .ok_or_else(|| channel_not_found(&self.chain_id, &id))
     }
 
     pub fn get_mut(&mut self, id: impl Channelable) -> AppResult<&mut IbcChannelWrapper> {
         self.channels
             .get_mut(&id.as_channel_number()?)
-            .ok_or(anyhow!("channel not found"))
+            .ok_or_else(|| channel_not_found(&self.chain_id, &id))
+    }
+
+    /// Iterate the channels, ordered by `channel-id`.
+    pub fn iter(&self) -> impl Iterator<Item = &IbcChannelWrapper> {
+        self.channels.values()
     }
 
     pub fn next_key(&self) -> u64 {
@@ -68,6 +82,18 @@ impl Channels {
         self.channels.insert(key, channel);
         Ok(())
     }
+
+    /// Export the registry as [`ChannelFixture`]s.
+    pub fn export_fixture(&self) -> Vec<ChannelFixture> {
+        self.channels
+            .values()
+            .map(|channel| ChannelFixture {
+                local: channel.local.clone(),
+                remote: channel.remote.clone(),
+                status: channel.status.clone(),
+            })
+            .collect()
+    }
 }
 
 pub trait Channelable {
@@ -91,12 +117,7 @@ impl Channelable for String {
     }
 
     fn as_channel_number(&self) -> AppResult<u64> {
-        self.strip_prefix("channel-")
-            .ok_or(anyhow!("invalid `channel-id`"))
-            .and_then(|s| {
-                s.parse::<u64>()
-                    .map_err(|_| anyhow!("invalid `channel-id`"))
-            })
+        parse_channel_id(self)
     }
 }
 
@@ -106,26 +127,46 @@ impl Channelable for &str {
     }
 
     fn as_channel_number(&self) -> AppResult<u64> {
-        self.strip_prefix("channel-")
-            .ok_or(anyhow!("invalid `channel-id`"))
-            .and_then(|s| {
-                s.parse::<u64>()
-                    .map_err(|_| anyhow!("invalid `channel-id`"))
-            })
+        parse_channel_id(self)
+    }
+}
+
+fn channel_not_found(chain_id: &str, id: &impl Channelable) -> anyhow::Error {
+    IperError::ChannelNotFound {
+        chain_id: chain_id.to_string(),
+        channel: id.as_channel_string(),
     }
+    .into()
 }
 
+fn parse_channel_id(channel: &str) -> AppResult<u64> {
+    channel
+        .strip_prefix("channel-")
+        .and_then(|s| s.parse::<u64>().ok())
+        .ok_or(
+            IperError::InvalidChannelId {
+                channel: channel.to_string(),
+            }
+            .into(),
+        )
+}
+
+/// Status of an `ibc channel` during the handshake.
 #[cw_serde]
 pub enum IbcChannelStatus {
+    /// `OpenInit` / `OpenTry` done.
     Created,
+    /// `OpenAck` / `OpenConfirm` in progress.
     Opening,
+    /// Handshake completed, `packets` can be relayed.
     Connected,
+    /// Channel closed.
     Closed,
 }
 
 impl IbcChannelStatus {
     #[allow(clippy::wrong_self_convention)]
-    pub fn to_next_status(&mut self) -> AppResult<()> {
+    pub(crate) fn to_next_status(&mut self) -> AppResult<()> {
         match self {
             IbcChannelStatus::Created => *self = IbcChannelStatus::Opening,
             IbcChannelStatus::Opening => *self = IbcChannelStatus::Connected,
@@ -237,7 +278,7 @@ impl IbcChannelExt for IbcChannel {
 }
 
 pub trait IbcMsgExt {
-    fn get_src_channel(&self) -> String;
+    fn get_src_channel(&self) -> AppResult<String>;
     fn into_packet(
         self,
         sender: &Addr,
@@ -246,12 +287,12 @@ pub trait IbcMsgExt {
 }
 
 impl IbcMsgExt for IbcMsg {
-    fn get_src_channel(&self) -> String {
+    fn get_src_channel(&self) -> AppResult<String> {
         match self {
-            IbcMsg::Transfer { channel_id, .. } => channel_id.clone(),
-            IbcMsg::SendPacket { channel_id, .. } => channel_id.clone(),
-            IbcMsg::CloseChannel { channel_id } => channel_id.clone(),
-            _ => todo!(),
+            IbcMsg::Transfer { channel_id, .. } => Ok(channel_id.clone()),
+            IbcMsg::SendPacket { channel_id, .. } => Ok(channel_id.clone()),
+            IbcMsg::CloseChannel { channel_id } => Ok(channel_id.clone()),
+            _ => Err(unsupported_ibc_msg(self)),
         }
     }
 
@@ -291,26 +332,34 @@ impl IbcMsgExt for IbcMsg {
                 }))
             }
             IbcMsg::CloseChannel { channel_id } => Ok(IbcPacketType::CloseChannel { channel_id }),
-            _ => unimplemented!(),
+            msg => Err(unsupported_ibc_msg(&msg)),
         }
     }
 }
 
-pub fn create_ibc_timeout(nanos: u64, height: Option<Height>) -> IbcTimeout {
+fn unsupported_ibc_msg(msg: &IbcMsg) -> anyhow::Error {
+    IperError::UnsupportedMsg {
+        module: "ibc".to_string(),
+        msg: format!("{:?}", msg),
+    }
+    .into()
+}
+
+pub fn create_ibc_timeout(nanos: u64, height: Option<Height>) -> AppResult<IbcTimeout> {
     match (nanos, height) {
-        (0, None) => unimplemented!(),
-        (0, Some(height)) => IbcTimeout::with_block(IbcTimeoutBlock {
+        (0, None) => Err(IperError::InvalidTimeout.into()),
+        (0, Some(height)) => Ok(IbcTimeout::with_block(IbcTimeoutBlock {
             revision: height.revision_number,
             height: height.revision_height,
-        }),
-        (seconds, None) => IbcTimeout::with_timestamp(Timestamp::from_nanos(seconds)),
+        })),
+        (seconds, None) => Ok(IbcTimeout::with_timestamp(Timestamp::from_nanos(seconds))),
 
-        (seconds, Some(height)) => IbcTimeout::with_both(
+        (seconds, Some(height)) => Ok(IbcTimeout::with_both(
             IbcTimeoutBlock {
                 revision: height.revision_number,
                 height: height.revision_height,
             },
             Timestamp::from_nanos(seconds),
-        ),
+        )),
     }
 }
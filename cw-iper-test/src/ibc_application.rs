@@ -7,7 +7,7 @@ use cosmwasm_std::{
 use cw_multi_test::{AppResponse, MockApiBech32};
 
 use crate::{
-    error::AppResult,
+    error::{AppResult, IperError},
     ibc::IbcChannelWrapper,
     ibc_module::{AckPacket, TimeoutPacket},
     iper_app::InfallibleResult,
@@ -26,6 +26,7 @@ use crate::{
 /// - **packet_timeout**: A `timeout packet` returns and the source `channel-id` was this [`IbcApplication`].
 /// - **open_channel**: An `IBC channel` is being opened that carries this [`IbcApplication`].
 /// - **channel_connect**: An `IBC channel` is being connected that carries this [`IbcApplication`].
+/// - **sudo**: A privileged `msg` is targetting this [`IbcApplication`].
 ///
 /// ## Implementation of the trait:
 /// In order to be implemented, the struct has to implement also [`IbcPortInterface`]
@@ -104,8 +105,27 @@ pub trait IbcApplication: IbcPortInterface {
         msg: IbcChannelConnectMsg,
     ) -> AppResult<AppResponse>;
 
+    /// Called once when the [`IbcApplication`] is added to the [`AppBuilder`](cw_multi_test::AppBuilder), allowing to initialize its storage.
+    fn init(&self, api: &MockApiBech32, storage: &mut dyn Storage) -> AppResult<()>;
+
+    /// A privileged `msg` is sent to this [`IbcApplication`] via [`IperSudoMsg::IbcApplication`](crate::IperSudoMsg::IbcApplication).
     ///
-    fn init(&self, api: &MockApiBech32, storage: &mut dyn Storage);
+    /// Used to drive admin paths, like changing the params of the application.
+    /// By default, the `msg` is not supported.
+    fn sudo(
+        &self,
+        _api: &dyn Api,
+        _block: &BlockInfo,
+        _router: &RouterWrapper,
+        _storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: Binary,
+    ) -> AppResult<AppResponse> {
+        Err(IperError::UnsupportedMsg {
+            module: self.port_name(),
+            msg: format!("sudo {}", msg),
+        }
+        .into())
+    }
 }
 
 /// This trait is used to implement the `port_name` for a [`IbcApplication`].
@@ -21,7 +21,7 @@ use sha2::{Digest, Sha256};
 use crate::ibc_application::PacketReceiveFailing;
 use crate::ibc_module::{AckPacket, TimeoutPacket};
 use crate::iper_app::InfallibleResult;
-use crate::middleware::{IbcAndStargate, MidRecFailing, MidRecOk, Middleware, MiddlewareResponse};
+use crate::middleware::{MidRecFailing, MidRecOk, Middleware, MiddlewareResponse};
 use crate::{
     chain_helper::ChainHelper, error::AppResult, ibc_application::PacketReceiveOk,
     ibc_applications::ics20::ICS20DB, router::RouterWrapper,
@@ -30,21 +30,11 @@ use crate::{
 use super::ics20::FungibleTokenPacketAck;
 use super::MemoField;
 
-/// `IbcHook` implementation as [`Middleware`]
-pub struct IbcHook {
-    /// Inner [`IbcApplication`](crate::ibc_application::IbcApplication). It should be [`Ics20`](crate::ibc_applications::Ics20)
-    /// or another [`Middleware`] that wrap [`Ics20`](crate::ibc_applications::Ics20).
-    pub inner: Box<dyn IbcAndStargate>,
-}
+/// `IbcHook` implementation as [`Middleware`], added to a [`MiddlewareStack`](crate::MiddlewareStack)
+/// on top of [`Ics20`](crate::ibc_applications::Ics20) or of another [`Middleware`] wrapping [`Ics20`](crate::ibc_applications::Ics20).
+pub struct IbcHook;
 
 impl IbcHook {
-    /// Constructor
-    pub fn new<T: IbcAndStargate + 'static>(inner: T) -> Self {
-        Self {
-            inner: Box::new(inner),
-        }
-    }
-
     fn try_handle_callback(
         &self,
         api: &dyn Api,
@@ -84,10 +74,6 @@ impl IbcHook {
 }
 
 impl Middleware for IbcHook {
-    fn get_inner(&self) -> &dyn IbcAndStargate {
-        &*self.inner
-    }
-
     fn mid_packet_receive_before(
         &self,
         _api: &dyn Api,
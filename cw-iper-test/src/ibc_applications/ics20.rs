@@ -1,57 +1,51 @@
 use std::collections::BTreeMap;
 use std::{cell::RefCell, rc::Rc};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
     from_json, to_json_binary, Addr, Api, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Empty,
-    Event, GrpcQuery, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcMsg, IbcPacketReceiveMsg,
-    Storage, Uint128,
+    Event, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcMsg, IbcPacketReceiveMsg, Storage, Uint128,
 };
-use cw_iper_test_macros::{urls, IbcPort, Stargate};
+use cw_iper_test_macros::{stargate_application, IbcPort};
 use cw_multi_test::{AppResponse, BankSudo, SudoMsg};
 
 use cw_storage_plus::Item;
-use ibc_proto::ibc::apps::transfer::v1::MsgTransfer;
+use ibc_proto::ibc::apps::transfer::v1::{DenomTrace, MsgTransfer, MsgTransferResponse};
 use ibc_proto::ibc::apps::transfer::v2::FungibleTokenPacketData;
+use prost::Message;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use crate::ibc::create_ibc_timeout;
-use crate::ibc_application::{IbcApplication, PacketReceiveFailing, PacketReceiveOk};
-use crate::ibc_module::{
-    emit_packet_boxed, AckPacket, IbcPacketType, OutgoingPacket, OutgoingPacketRaw, TimeoutPacket,
+use crate::ibc_application::{
+    IbcApplication, IbcPortInterface, PacketReceiveFailing, PacketReceiveOk,
 };
+use crate::ibc_module::{emit_outgoing_packet, AckPacket, OutgoingPacket, TimeoutPacket};
 use crate::iper_app::InfallibleResult;
 
 use crate::{
-    error::AppResult, ibc::IbcChannelWrapper, router::RouterWrapper, stargate::StargateApplication,
+    error::{AppResult, IperError},
+    ibc::IbcChannelWrapper,
+    router::RouterWrapper,
+    stargate::{StargateMsgCtx, StargateResponse},
 };
-use prost::Message;
 
 use std::str::FromStr;
 
 use super::WasmField;
 /// Ics20 Application
-#[derive(Default, Clone, IbcPort, Stargate)]
+#[derive(Default, Clone, IbcPort)]
 #[ibc_port = "transfer"]
-#[stargate(name = "ics20", query_urls = Ics20QueryUrls, msgs_urls = Ics20MsgUrls)]
 pub struct Ics20;
 
-#[urls]
-pub enum Ics20MsgUrls {
-    #[strum(serialize = "/ibc.applications.transfer.v1.MsgTransfer")]
-    MsgTransfer,
-}
-
-#[urls]
-pub enum Ics20QueryUrls {}
-
 impl IbcApplication for Ics20 {
-    fn init(&self, api: &cw_multi_test::MockApiBech32, storage: &mut dyn Storage) {
+    fn init(&self, api: &cw_multi_test::MockApiBech32, storage: &mut dyn Storage) -> AppResult<()> {
         let db = Ics20Db::new(api.addr_make("ics20_addr_container"));
 
-        ICS20DB.save(storage, &db).unwrap();
+        ICS20DB.save(storage, &db)?;
+
+        Ok(())
     }
 
     fn handle_outgoing_packet(
@@ -84,15 +78,17 @@ impl IbcApplication for Ics20 {
             IbcMsg::SendPacket { data, timeout, .. } => {
                 (from_json::<FungibleTokenPacketData>(&data)?, timeout)
             }
-            IbcMsg::CloseChannel { .. } => {
-                unimplemented!("Close Channel not implemented yet on ICS20")
-            }
-            _ => todo!(),
+            msg => bail!(IperError::UnsupportedMsg {
+                module: self.port_name(),
+                msg: format!("{:?}", msg),
+            }),
         };
 
+        Ics20Params::load(*storage.borrow())?.ensure_send_enabled()?;
+
         let db = ICS20DB.load(*storage.borrow())?;
 
-        let (packet_denom, is_local) = db.handle_outgoing(&data.denom);
+        let (packet_denom, is_local) = db.handle_outgoing(&data.denom)?;
 
         let response = if is_local {
             router.execute(
@@ -119,17 +115,21 @@ impl IbcApplication for Ics20 {
 
         data.denom = packet_denom;
 
-        emit_packet_boxed(
-            IbcPacketType::OutgoingPacket(OutgoingPacket {
+        let sequence = emit_outgoing_packet(
+            OutgoingPacket {
                 timeout,
                 data: to_json_binary(&data)?,
                 src: channel.local.as_endpoint()?,
                 dest: channel.remote.as_endpoint()?,
-            }),
+            },
             &storage,
         )?;
 
-        Ok(response)
+        // As `wasmd`, the response of the transfer is returned as `data`
+        Ok(AppResponse {
+            data: Some(MsgTransferResponse { sequence }.encode_to_vec().into()),
+            ..response
+        })
     }
 
     fn packet_receive(
@@ -141,6 +141,10 @@ impl IbcApplication for Ics20 {
         msg: IbcPacketReceiveMsg,
     ) -> InfallibleResult<PacketReceiveOk, PacketReceiveFailing> {
         let clos = || {
+            if !Ics20Params::load(*storage.borrow())?.receive_enabled {
+                bail!("ics20 receive is disabled");
+            }
+
             let data: FungibleTokenPacketData = from_json(&msg.packet.data)?;
 
             let mut db = ICS20DB.load(*storage.borrow())?;
@@ -177,14 +181,16 @@ impl IbcApplication for Ics20 {
             }
         };
 
-        match clos() {
-            Ok(response) => InfallibleResult::Ok(PacketReceiveOk {
+        match clos()
+            .and_then(|response| Ok((response, to_json_binary(&FungibleTokenPacketAck::Ok)?)))
+        {
+            Ok((response, ack)) => InfallibleResult::Ok(PacketReceiveOk {
                 response,
-                ack: Some(to_json_binary(&FungibleTokenPacketAck::Ok).unwrap()),
+                ack: Some(ack),
             }),
             Err(err) => InfallibleResult::Err(PacketReceiveFailing {
                 error: err.to_string(),
-                ack: Some(to_json_binary(&FungibleTokenPacketAck::Err(err.to_string())).unwrap()),
+                ack: to_json_binary(&FungibleTokenPacketAck::Err(err.to_string())).ok(),
             }),
         }
     }
@@ -260,67 +266,64 @@ impl IbcApplication for Ics20 {
     ) -> AppResult<AppResponse> {
         Ok(AppResponse::default())
     }
-}
 
-impl StargateApplication for Ics20 {
-    fn stargate_msg(
+    fn sudo(
         &self,
         _api: &dyn Api,
-        storage: Rc<RefCell<&mut dyn Storage>>,
-        router: &RouterWrapper,
         _block: &BlockInfo,
-        sender: Addr,
-        type_url: String,
-        data: Binary,
+        _router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: Binary,
     ) -> AppResult<AppResponse> {
-        match Ics20MsgUrls::from_str(&type_url)? {
-            Ics20MsgUrls::MsgTransfer => {
-                let msg = MsgTransfer::decode(data.as_slice())?;
-
-                let coin = msg.token.ok_or(anyhow!("missing token"))?;
-
-                let packet = FungibleTokenPacketData {
-                    denom: coin.denom.clone(),
-                    amount: coin.amount.clone(),
-                    sender: sender.to_string(),
-                    receiver: msg.receiver,
-                    memo: msg.memo,
-                };
-
-                let response = router.execute(
-                    sender,
-                    CosmosMsg::<Empty>::Bank(BankMsg::Burn {
-                        amount: vec![Coin::new(
-                            Uint128::from_str(&coin.amount)?,
-                            coin.denom.clone(),
-                        )],
-                    }),
-                )?;
-
-                emit_packet_boxed(
-                    IbcPacketType::OutgoinPacketRaw(OutgoingPacketRaw {
-                        data: to_json_binary(&packet)?,
-                        src_port: msg.source_port,
-                        src_channel: msg.source_channel,
-                        timeout: create_ibc_timeout(msg.timeout_timestamp, msg.timeout_height),
-                    }),
-                    &storage,
-                )?;
+        match from_json::<Ics20SudoMsg>(msg)? {
+            Ics20SudoMsg::UpdateParams(params) => {
+                ICS20_PARAMS.save(*storage.borrow_mut(), &params)?;
 
-                Ok(response)
+                Ok(AppResponse {
+                    events: vec![Event::new("ics20_update_params")
+                        .add_attribute("send_enabled", params.send_enabled.to_string())
+                        .add_attribute("receive_enabled", params.receive_enabled.to_string())],
+                    data: None,
+                })
             }
         }
     }
+}
 
-    fn stargate_query(
+#[stargate_application(name = "ics20")]
+impl Ics20 {
+    #[msg]
+    fn transfer(
         &self,
-        _api: &dyn Api,
-        _storage: &dyn Storage,
-        _querier: &dyn cosmwasm_std::Querier,
-        _block: &BlockInfo,
-        _request: GrpcQuery,
-    ) -> AppResult<cosmwasm_std::Binary> {
-        todo!()
+        ctx: StargateMsgCtx,
+        msg: MsgTransfer,
+    ) -> AppResult<StargateResponse<MsgTransferResponse>> {
+        Ics20Params::load(*ctx.storage.borrow())?.ensure_send_enabled()?;
+
+        if msg.source_port != self.port_name() {
+            bail!(IperError::UnsupportedMsg {
+                module: self.port_name(),
+                msg: format!("MsgTransfer from port {}", msg.source_port),
+            });
+        }
+
+        let coin = msg.token.ok_or(anyhow!("missing token"))?;
+
+        let response = ctx.router.execute(
+            ctx.sender,
+            CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+                channel_id: msg.source_channel,
+                to_address: msg.receiver,
+                amount: Coin::new(Uint128::from_str(&coin.amount)?, coin.denom),
+                timeout: create_ibc_timeout(msg.timeout_timestamp, msg.timeout_height)?,
+                memo: Some(msg.memo).filter(|memo| !memo.is_empty()),
+            }),
+        )?;
+
+        let data =
+            MsgTransferResponse::decode(response.data.clone().unwrap_or_default().as_slice())?;
+
+        Ok(StargateResponse::new(response, data))
     }
 }
 
@@ -332,6 +335,49 @@ pub enum FungibleTokenPacketAck {
 
 pub const ICS20DB: Item<Ics20Db> = Item::new("ics20_db");
 
+const ICS20_PARAMS: Item<Ics20Params> = Item::new("ics20_params");
+
+/// Params of [`Ics20`], updated via [`Ics20SudoMsg::UpdateParams`].
+#[cw_serde]
+pub struct Ics20Params {
+    /// Outgoing transfers are enabled.
+    pub send_enabled: bool,
+    /// Incoming transfers are enabled. If disabled, `packets` are acked with an error.
+    pub receive_enabled: bool,
+}
+
+impl Default for Ics20Params {
+    fn default() -> Self {
+        Self {
+            send_enabled: true,
+            receive_enabled: true,
+        }
+    }
+}
+
+impl Ics20Params {
+    /// Load the current params, or the default ones if never updated.
+    pub fn load(storage: &dyn Storage) -> AppResult<Self> {
+        Ok(ICS20_PARAMS.may_load(storage)?.unwrap_or_default())
+    }
+
+    fn ensure_send_enabled(&self) -> AppResult<()> {
+        if !self.send_enabled {
+            bail!("ics20 send is disabled");
+        }
+
+        Ok(())
+    }
+}
+
+/// Privileged msgs of [`Ics20`], sent `json` encoded via [`IperSudoMsg::IbcApplication`](crate::IperSudoMsg::IbcApplication)
+/// with the `transfer` port.
+#[cw_serde]
+pub enum Ics20SudoMsg {
+    /// Replace the [`Ics20Params`].
+    UpdateParams(Ics20Params),
+}
+
 #[cw_serde]
 pub struct Ics20Db {
     pub incoming_denoms: BTreeMap<IbcDenom, Trace>,
@@ -346,12 +392,15 @@ impl Ics20Db {
         }
     }
 
-    pub fn handle_outgoing(&self, denom: &str) -> (String, bool) {
+    pub fn handle_outgoing(&self, denom: &str) -> AppResult<(String, bool)> {
         if denom.starts_with("ibc/") {
-            let trace = self.incoming_denoms.get(denom).unwrap();
-            (trace.clone(), false)
+            let trace = self
+                .incoming_denoms
+                .get(denom)
+                .ok_or(anyhow!("trace not found for denom: {}", denom))?;
+            Ok((trace.clone(), false))
         } else {
-            (denom.to_string(), true)
+            Ok((denom.to_string(), true))
         }
     }
 
@@ -414,6 +463,31 @@ impl Ics20Helper {
         hasher.update(trace);
         format!("ibc/{}", format!("{:x}", hasher.finalize()).to_uppercase())
     }
+
+    /// Split a trace with the format `{port/channel-id/...}{denom}` into a [`DenomTrace`].
+    pub fn denom_trace(trace: &str) -> DenomTrace {
+        let mut hops = 0;
+        let segments = trace.split('/').collect::<Vec<_>>();
+
+        while segments.len() > (hops + 1) * 2 && segments[hops * 2 + 1].starts_with("channel-") {
+            hops += 1;
+        }
+
+        DenomTrace {
+            path: segments[..hops * 2].join("/"),
+            base_denom: segments[hops * 2..].join("/"),
+        }
+    }
+
+    /// Load the `ibc/{hash}` vouchers received by [`Ics20`], with their trace.
+    ///
+    /// Empty if [`Ics20`] is not registered on the chain.
+    pub fn incoming_vouchers(storage: &dyn Storage) -> AppResult<BTreeMap<String, String>> {
+        Ok(ICS20DB
+            .may_load(storage)?
+            .map(|db| db.incoming_denoms)
+            .unwrap_or_default())
+    }
 }
 
 #[test]
@@ -5,6 +5,6 @@
 mod ibc_hook;
 mod ics20;
 
-pub use ics20::{Ics20, Ics20Helper, MemoField};
+pub use ics20::{Ics20, Ics20Helper, Ics20Params, Ics20SudoMsg, MemoField};
 
 pub use ibc_hook::{IBCLifecycleComplete, IbcHook, IbcHookSudoMsg, WasmField};
@@ -7,7 +7,7 @@ use crate::{
     router::{RouterWrapper, UseRouter, UseRouterResponse},
 };
 
-use anyhow::{anyhow, bail};
+use anyhow::bail;
 
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
@@ -16,11 +16,12 @@ use cosmwasm_std::{
     IbcPacketReceiveMsg, IbcQuery, IbcTimeout, Querier, Storage,
 };
 use cw_multi_test::{AppResponse, CosmosRouter, Ibc, Module};
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
 
 use crate::{
-    error::AppResult,
+    error::{AppResult, IperError},
     ibc::{IbcMsgExt, IbcPort},
     iper_app::SharedChannels,
     router_closure,
@@ -28,6 +29,21 @@ use crate::{
 
 pub(crate) const PENDING_PACKETS: Item<BTreeMap<u64, IbcPacketType>> = Item::new("pending_packets");
 
+/// Commitments of the `packets` sent and not yet acked or timed out, by `(port, channel, sequence)`.
+pub(crate) const PACKET_COMMITMENTS: Map<(&str, &str, u64), Binary> =
+    Map::new("packet_commitments");
+
+/// Next `sequence` of the outgoing `packets`, by `(port, channel)`.
+///
+/// Kept in the `storage`, so that the `sequences` consumed by a failed tx are reverted with it.
+pub(crate) const NEXT_SEQUENCE_SEND: Map<(&str, &str), u64> = Map::new("next_sequence_send");
+
+/// `sequence` of the outgoing `packets` emitted with [`emit_outgoing_packet`] and not yet synced, by `packet_id`.
+pub(crate) const PENDING_SEQUENCES: Map<u64, u64> = Map::new("pending_sequences");
+
+/// Receipts of the `packets` received, by `(port, channel, sequence)`.
+pub(crate) const PACKET_RECEIPTS: Map<(&str, &str, u64), Empty> = Map::new("packet_receipts");
+
 /// The [`IperIbcModule`] is the default struct used in an [`IperApp`](crate::iper_app::IperApp) as an `IBC module` and contains all [`IbcApplication`].
 ///
 /// This structure implements the [`Module`] and [`Ibc`] `traits` from `cw-multi-test`.
@@ -54,7 +70,7 @@ impl IperIbcModule {
     ) -> AppResult<&Rc<RefCell<dyn IbcApplication>>> {
         self.applications
             .get(&name.clone().into())
-            .ok_or(anyhow!("application not found: {}", name.into()))
+            .ok_or_else(|| IperError::ApplicationNotFound { port: name.into() }.into())
     }
 
     pub(crate) fn open_channel<ExecC, QueryC>(
@@ -120,18 +136,25 @@ impl IperIbcModule {
         ExecC: CustomMsg + DeserializeOwned + 'static,
         QueryC: CustomQuery + DeserializeOwned + 'static,
     {
+        let application = match self.load_application(application) {
+            Ok(application) => application,
+            Err(err) => {
+                return InfallibleResult::Err(PacketReceiveFailing {
+                    error: err.to_string(),
+                    ack: None,
+                })
+            }
+        };
+
         let rc_storage = Rc::new(RefCell::new(storage));
 
-        self.load_application(application)
-            .unwrap()
-            .borrow()
-            .packet_receive(
-                api,
-                block,
-                &RouterWrapper::new(&router_closure!(router, api, rc_storage, block)),
-                rc_storage.clone(),
-                packet.clone(),
-            )
+        application.borrow().packet_receive(
+            api,
+            block,
+            &RouterWrapper::new(&router_closure!(router, api, rc_storage, block)),
+            rc_storage.clone(),
+            packet.clone(),
+        )
     }
 
     pub(crate) fn packet_ack<ExecC, QueryC>(
@@ -181,6 +204,30 @@ impl IperIbcModule {
             msg,
         )
     }
+
+    pub(crate) fn sudo_application<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        application: &str,
+        msg: Binary,
+    ) -> AppResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        let rc_storage = Rc::new(RefCell::new(storage));
+
+        self.load_application(application)?.borrow().sudo(
+            api,
+            block,
+            &RouterWrapper::new(&router_closure!(router, api, rc_storage, block)),
+            rc_storage.clone(),
+            msg,
+        )
+    }
 }
 
 impl Module for IperIbcModule {
@@ -201,7 +248,7 @@ impl Module for IperIbcModule {
         ExecC: CustomMsg + DeserializeOwned + 'static,
         QueryC: CustomQuery + DeserializeOwned + 'static,
     {
-        let channel = self.channels.borrow().get(msg.get_src_channel())?.clone();
+        let channel = self.channels.borrow().get(msg.get_src_channel()?)?.clone();
         let rc_storage = Rc::new(RefCell::new(storage));
 
         if let IbcPort::Module(name) = &channel.local.port {
@@ -217,7 +264,14 @@ impl Module for IperIbcModule {
                     channel,
                 )
         } else {
-            emit_packet_boxed(msg.into_packet(&sender, &channel)?, &rc_storage)?;
+            match msg.into_packet(&sender, &channel)? {
+                IbcPacketType::OutgoingPacket(packet) => {
+                    emit_outgoing_packet(packet, &rc_storage)?;
+                }
+                packet => {
+                    emit_packet_boxed(packet, &rc_storage)?;
+                }
+            }
             Ok(AppResponse::default())
         }
     }
@@ -228,9 +282,12 @@ impl Module for IperIbcModule {
         _storage: &dyn Storage,
         _querier: &dyn Querier,
         _block: &BlockInfo,
-        _request: Self::QueryT,
+        request: Self::QueryT,
     ) -> AppResult<Binary> {
-        todo!()
+        bail!(IperError::UnsupportedMsg {
+            module: "ibc".to_string(),
+            msg: format!("{:?}", request),
+        })
     }
 
     fn sudo<ExecC, QueryC>(
@@ -239,13 +296,16 @@ impl Module for IperIbcModule {
         _storage: &mut dyn Storage,
         _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
         _block: &BlockInfo,
-        _msg: Self::SudoT,
+        msg: Self::SudoT,
     ) -> AppResult<AppResponse>
     where
         ExecC: CustomMsg + DeserializeOwned + 'static,
         QueryC: CustomQuery + DeserializeOwned + 'static,
     {
-        todo!()
+        bail!(IperError::UnsupportedMsg {
+            module: "ibc".to_string(),
+            msg: format!("{:?}", msg),
+        })
     }
 }
 
@@ -317,25 +377,35 @@ impl OutgoingPacketRaw {
     }
 }
 
+/// `acknowledgment packet` returning to the chain that sent the original `packet`.
 #[cw_serde]
 pub struct AckPacket {
+    /// `ack` data.
     pub ack: Binary,
+    /// Original `packet`, as received by the destination chain.
     pub original_packet: IbcPacketReceiveMsg,
+    /// `true` if the destination chain handled the `packet` successfully.
     pub success: bool,
+    /// Relayer of the `ack`.
     pub relayer: Option<Addr>,
 }
 
+/// `timeout packet` returning to the chain that sent the original `packet`.
 #[cw_serde]
 pub struct TimeoutPacket {
+    /// Original `packet`, as it would have been received by the destination chain.
     pub original_packet: IbcPacketReceiveMsg,
+    /// Relayer of the `timeout`.
     pub relayer: Option<Addr>,
 }
 
 impl AckPacket {
+    /// Return the `channel-id` of the original `packet` on the chain that sent it.
     pub fn get_src_channel(&self) -> String {
         self.original_packet.packet.src.channel_id.clone()
     }
 
+    /// Convert into the [`IbcPacketAckMsg`] delivered to `contracts`.
     pub fn into_msg(self, relayer: Addr) -> IbcPacketAckMsg {
         IbcPacketAckMsg::new(
             IbcAcknowledgement::new(self.ack),
@@ -364,14 +434,33 @@ impl OutgoingPacket {
 pub(crate) fn emit_packet_boxed(
     packet: IbcPacketType,
     rc_storage: &Rc<RefCell<&mut dyn Storage>>,
-) -> AppResult<()> {
+) -> AppResult<u64> {
     let mut packets = PENDING_PACKETS
         .load(*rc_storage.borrow())
         .unwrap_or_default();
     let new_key = packets.last_key_value().map(|(k, _)| *k).unwrap_or(0) + 1;
     packets.insert(new_key, packet);
     PENDING_PACKETS.save(*rc_storage.borrow_mut(), &packets)?;
-    Ok(())
+    Ok(new_key)
+}
+
+/// Emit an outgoing `packet`, assigning it the next `sequence` of its source `channel`.
+pub(crate) fn emit_outgoing_packet(
+    packet: OutgoingPacket,
+    rc_storage: &Rc<RefCell<&mut dyn Storage>>,
+) -> AppResult<u64> {
+    let key = (packet.src.port_id.as_str(), packet.src.channel_id.as_str());
+
+    let sequence = NEXT_SEQUENCE_SEND
+        .may_load(*rc_storage.borrow(), key)?
+        .unwrap_or(1);
+    NEXT_SEQUENCE_SEND.save(*rc_storage.borrow_mut(), key, &(sequence + 1))?;
+
+    let packet_id = emit_packet_boxed(IbcPacketType::OutgoingPacket(packet), rc_storage)?;
+
+    PENDING_SEQUENCES.save(*rc_storage.borrow_mut(), packet_id, &sequence)?;
+
+    Ok(sequence)
 }
 
 pub(crate) fn emit_packet(packet: IbcPacketType, storage: &mut dyn Storage) -> AppResult<()> {
@@ -381,3 +470,22 @@ pub(crate) fn emit_packet(packet: IbcPacketType, storage: &mut dyn Storage) -> A
     PENDING_PACKETS.save(storage, &packets)?;
     Ok(())
 }
+
+/// Commitment of a `packet`, computed like `ibc-go`:
+///
+/// `sha256(timeout_timestamp || timeout_revision_number || timeout_revision_height || sha256(data))`
+pub(crate) fn packet_commitment(data: &Binary, timeout: &IbcTimeout) -> Binary {
+    let timestamp = timeout.timestamp().map(|t| t.nanos()).unwrap_or_default();
+    let (revision, height) = timeout
+        .block()
+        .map(|block| (block.revision, block.height))
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(timestamp.to_be_bytes());
+    hasher.update(revision.to_be_bytes());
+    hasher.update(height.to_be_bytes());
+    hasher.update(Sha256::digest(data.as_slice()));
+
+    Binary::from(hasher.finalize().to_vec())
+}
@@ -1,29 +1,36 @@
-use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+use std::{any::Any, cell::RefCell, collections::BTreeMap, rc::Rc};
 
 use anyhow::{anyhow, bail};
 use cosmwasm_std::{
-    testing::MockStorage, Addr, Api, Binary, CustomMsg, CustomQuery, Empty, IbcChannel,
-    IbcChannelConnectMsg, IbcChannelOpenMsg, IbcPacket, IbcPacketReceiveMsg, IbcPacketTimeoutMsg,
-    Storage,
+    from_json, testing::MockStorage, Addr, Api, Binary, BlockInfo, CustomMsg, CustomQuery, Empty,
+    Event, IbcChannel, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcPacket, IbcPacketReceiveMsg,
+    IbcPacketTimeoutMsg, StdAck, Storage, Timestamp,
 };
 use cw_multi_test::{
-    transactional, App, AppResponse, Bank, BankKeeper, Distribution, DistributionKeeper,
+    transactional, App, AppResponse, Bank, BankKeeper, Distribution, DistributionKeeper, Executor,
     FailingModule, Gov, GovFailingModule, MockApiBech32, Module, StakeKeeper, Staking, Stargate,
     StorageTransaction, Wasm, WasmKeeper,
 };
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
+    chain_helper::ChainHelper,
+    client::{next_client_id, ClientHeader, ClientStatus, LightClient, LIGHT_CLIENTS},
+    connection::{next_connection_id, ConnectionEnd, IBC_CONNECTIONS},
     contracts::{IbcContract, IperContract},
-    error::AppResult,
+    error::{AppResult, IperError},
+    fixture::{ChainFixture, EcosystemFixture},
     ibc::{
-        Channels, IbcChannelCreator, IbcChannelExt, IbcChannelStatus, IbcChannelWrapper, IbcPort,
+        Channelable, Channels, IbcChannelCreator, IbcChannelExt, IbcChannelStatus,
+        IbcChannelWrapper, IbcPort,
     },
     ibc_module::{
-        emit_packet, AckPacket, AckResponse, IbcPacketType, IperIbcModule, OutgoingPacket,
-        TimeoutPacket, PENDING_PACKETS,
+        emit_packet, packet_commitment, AckPacket, AckResponse, IbcPacketType, IperIbcModule,
+        OutgoingPacket, TimeoutPacket, PACKET_COMMITMENTS, PACKET_RECEIPTS, PENDING_PACKETS,
+        PENDING_SEQUENCES,
     },
     response::IntoResponse,
+    snapshot::{dump_storage, load_storage, AppSnapshot},
     stargate::IperStargateModule,
 };
 
@@ -68,6 +75,8 @@ where
     /// Stored `ibc channels`
     pub channels: SharedChannels,
     pub(crate) code_ids: BTreeMap<u64, Box<dyn IbcContract<CustomT::ExecT, CustomT::QueryT>>>,
+    /// All codes stored via [`IperApp`], with their name if stored via [`IperApp::store_named_ibc_code`].
+    pub(crate) stored_codes: BTreeMap<u64, Option<String>>,
 }
 
 impl<BankT, ApiT, StorageT, CustomT, WasmT, StakingT, DistrT, GovT, StargateT>
@@ -95,16 +104,89 @@ where
         if let Some(ibc) = contract.ibc {
             self.code_ids.insert(code_id, ibc);
         }
+        self.stored_codes.insert(code_id, None);
         code_id
     }
 
+    /// Store a contract under a `name`.
+    ///
+    /// Named codes can be stored again when loading an [`EcosystemFixture`] via [`IperApp::store_fixture_codes`].
+    pub fn store_named_ibc_code(
+        &mut self,
+        name: impl Into<String>,
+        contract: IperContract<CustomT::ExecT, CustomT::QueryT>,
+    ) -> u64 {
+        let code_id = self.store_ibc_code(contract);
+        self.stored_codes.insert(code_id, Some(name.into()));
+        code_id
+    }
+
+    /// Store the codes of this chain listed in an [`EcosystemFixture`], in the same order they have been stored originally.
+    ///
+    /// `contracts` maps the name of a code to its [`IperContract`].
+    /// Codes already stored with the same `code_id` and name are skipped.
+    ///
+    /// This function has to be called before [`Ecosystem::load_fixture`](crate::Ecosystem::load_fixture).
+    pub fn store_fixture_codes(
+        &mut self,
+        fixture: &EcosystemFixture,
+        mut contracts: impl FnMut(&str) -> Option<IperContract<CustomT::ExecT, CustomT::QueryT>>,
+    ) -> AppResult<()> {
+        let chain = fixture.get_chain(&self.chain_id)?;
+
+        for (code_id, name) in &chain.codes {
+            if let Some(stored) = self.stored_codes.get(code_id) {
+                if stored != name {
+                    bail!(IperError::FixtureMismatch {
+                        chain_id: self.chain_id.clone(),
+                        reason: format!(
+                            "code_id {} already stored with a different name: {:?}, expected: {:?}",
+                            code_id, stored, name
+                        ),
+                    })
+                }
+                continue;
+            }
+
+            let name = name.as_ref().ok_or_else(|| IperError::FixtureMismatch {
+                chain_id: self.chain_id.clone(),
+                reason: format!(
+                    "code_id {} has been stored without a name, unable to store it again",
+                    code_id
+                ),
+            })?;
+
+            let contract = contracts(name).ok_or_else(|| IperError::FixtureMismatch {
+                chain_id: self.chain_id.clone(),
+                reason: format!("contract not provided for code: {}", name),
+            })?;
+
+            let stored_code_id = self.store_named_ibc_code(name, contract);
+
+            if stored_code_id != *code_id {
+                bail!(IperError::FixtureMismatch {
+                    chain_id: self.chain_id.clone(),
+                    reason: format!(
+                        "code {} stored with code_id {}, expected: {}",
+                        name, stored_code_id, code_id
+                    ),
+                })
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get info about a specific `pending packet`.
     pub fn get_pending_packet(&self, packet_id: u64) -> AppResult<IbcPacketType> {
         let packets = PENDING_PACKETS.load(self.app.storage())?;
-        packets
-            .get(&packet_id)
-            .cloned()
-            .ok_or(anyhow!("Packet not found"))
+        packets.get(&packet_id).cloned().ok_or_else(|| {
+            IperError::PacketNotFound {
+                chain_id: self.chain_id.clone(),
+                packet_id,
+            }
+            .into()
+        })
     }
 
     /// Get all `pending packets`.
@@ -128,33 +210,151 @@ where
         let mut packets = PENDING_PACKETS.load(self.app.storage())?;
         packets.remove(&packet_id);
         PENDING_PACKETS.save(self.app.storage_mut(), &packets)?;
+        PENDING_SEQUENCES.remove(self.app.storage_mut(), packet_id);
         Ok(())
     }
 
     pub(crate) fn get_next_pending_packet(&self) -> AppResult<u64> {
         let packets = PENDING_PACKETS.load(self.app.storage())?;
-        packets
-            .first_key_value()
-            .map(|(k, _)| *k)
-            .ok_or(anyhow!("No pending packets"))
+        packets.first_key_value().map(|(k, _)| *k).ok_or_else(|| {
+            IperError::NoPendingPackets {
+                chain_id: self.chain_id.clone(),
+            }
+            .into()
+        })
+    }
+
+    /// Get all `light clients`, by `client_id`.
+    pub fn light_clients(&self) -> AppResult<BTreeMap<String, LightClient>> {
+        Ok(LIGHT_CLIENTS
+            .may_load(self.app.storage())?
+            .unwrap_or_default())
+    }
+
+    /// Get the `light client` tracking a specific counterparty chain.
+    pub fn light_client(&self, counterparty_chain_id: &str) -> AppResult<LightClient> {
+        self.light_clients()?
+            .into_values()
+            .find(|client| client.counterparty_chain_id == counterparty_chain_id)
+            .ok_or_else(|| {
+                IperError::ClientNotFound {
+                    chain_id: self.chain_id.clone(),
+                    counterparty_chain_id: counterparty_chain_id.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Create the `light client` tracking the counterparty chain, if not already created, returning its `client_id`.
+    pub(crate) fn create_light_client(
+        &mut self,
+        counterparty_chain_id: &str,
+        counterparty_header: &ClientHeader,
+        trusting_period: u64,
+    ) -> AppResult<String> {
+        if let Ok(client) = self.light_client(counterparty_chain_id) {
+            return Ok(client.client_id);
+        }
+
+        let mut clients = self.light_clients()?;
+        let client_id = next_client_id(self.app.storage_mut())?;
+
+        clients.insert(
+            client_id.clone(),
+            LightClient::new(
+                client_id.clone(),
+                counterparty_chain_id,
+                trusting_period,
+                counterparty_header,
+            ),
+        );
+
+        LIGHT_CLIENTS.save(self.app.storage_mut(), &clients)?;
+
+        Ok(client_id)
+    }
+
+    /// Update the `light client` tracking the counterparty chain with `counterparty_header`.
+    ///
+    /// Fails if the client is expired or frozen.
+    pub(crate) fn update_light_client(
+        &mut self,
+        counterparty_chain_id: &str,
+        counterparty_header: &ClientHeader,
+    ) -> AppResult<()> {
+        let mut client = self.light_client(counterparty_chain_id)?;
+
+        let status = client.status(self.app.block_info().time);
+
+        if status != ClientStatus::Active {
+            bail!(IperError::ClientNotActive {
+                chain_id: self.chain_id.clone(),
+                client_id: client.client_id,
+                status,
+            })
+        }
+
+        client.update(counterparty_header);
+
+        self.save_light_client(client)
+    }
+
+    /// Freeze the `light client` tracking the counterparty chain.
+    pub(crate) fn freeze_light_client(&mut self, counterparty_chain_id: &str) -> AppResult<()> {
+        let mut client = self.light_client(counterparty_chain_id)?;
+        client.freeze();
+        self.save_light_client(client)
+    }
+
+    fn save_light_client(&mut self, client: LightClient) -> AppResult<()> {
+        let mut clients = self.light_clients()?;
+        clients.insert(client.client_id.clone(), client);
+        LIGHT_CLIENTS.save(self.app.storage_mut(), &clients)?;
+        Ok(())
+    }
+
+    /// Get all `connections`, by `connection_id`.
+    pub fn connections(&self) -> AppResult<BTreeMap<String, ConnectionEnd>> {
+        Ok(IBC_CONNECTIONS
+            .may_load(self.app.storage())?
+            .unwrap_or_default())
+    }
+
+    /// Get a specific `connection`.
+    pub fn connection(&self, connection_id: &str) -> AppResult<ConnectionEnd> {
+        self.connections()?.remove(connection_id).ok_or_else(|| {
+            IperError::ConnectionNotFound {
+                chain_id: self.chain_id.clone(),
+                connection_id: connection_id.to_string(),
+            }
+            .into()
+        })
+    }
+
+    pub(crate) fn next_connection_id(&self) -> AppResult<String> {
+        Ok(next_connection_id(&self.connections()?))
+    }
+
+    pub(crate) fn save_connection(&mut self, connection: ConnectionEnd) -> AppResult<()> {
+        let mut connections = self.connections()?;
+        connections.insert(connection.connection_id.clone(), connection);
+        IBC_CONNECTIONS.save(self.app.storage_mut(), &connections)?;
+        Ok(())
     }
 
     pub(crate) fn open_channel(
         &mut self,
         local: &IbcChannelCreator,
         remote: &IbcChannelCreator,
-        sequence: Rc<RefCell<u64>>,
     ) -> AppResult<IbcChannelWrapper> {
-        let channel_wrapper = IbcChannelWrapper::new(local.clone(), remote.clone(), sequence);
+        let channel_wrapper = IbcChannelWrapper::new(local.clone(), remote.clone());
 
         let msg = IbcChannelOpenMsg::new_init(IbcChannel::new_from_creators(local, remote)?);
         match &local.port {
             IbcPort::Contract(contract) => {
                 let code_id = self.app.contract_data(contract)?.code_id;
-                let ibc_details = self
-                    .code_ids
-                    .get(&code_id)
-                    .ok_or(anyhow!("Code ID not found"))?;
+                let ibc_details =
+                    ibc_entry_points(&self.code_ids, &self.chain_id, contract, code_id)?;
 
                 self.app.use_contract(contract, |deps, env| {
                     ibc_details
@@ -183,7 +383,7 @@ where
     pub(crate) fn channel_connect(&mut self, channel_id: u64) -> AppResult<()> {
         let mut channels = self.channels.borrow_mut();
         let channel = channels.get_mut(channel_id)?;
-        let msg = match channel.status {
+        let msg = match &channel.status {
             IbcChannelStatus::Created => IbcChannelConnectMsg::new_ack(
                 IbcChannel::new_from_creators(&channel.local, &channel.remote)?,
                 channel.remote.version.clone(),
@@ -191,16 +391,18 @@ where
             IbcChannelStatus::Opening => IbcChannelConnectMsg::new_confirm(
                 IbcChannel::new_from_creators(&channel.local, &channel.remote)?,
             ),
-            _ => bail!("Invalid channel status"),
+            status => bail!(IperError::InvalidChannelStatus {
+                chain_id: self.chain_id.clone(),
+                channel: channel_id.as_channel_string(),
+                status: status.clone(),
+            }),
         };
 
         match &channel.local.port {
             IbcPort::Contract(contract) => {
                 let code_id = self.app.contract_data(contract)?.code_id;
-                let ibc_details = self
-                    .code_ids
-                    .get(&code_id)
-                    .ok_or(anyhow!("Code ID not found"))?;
+                let ibc_details =
+                    ibc_entry_points(&self.code_ids, &self.chain_id, contract, code_id)?;
 
                 self.app.use_contract(contract, |deps, env| {
                     ibc_details
@@ -229,10 +431,14 @@ where
         Ok(())
     }
 
-    pub(crate) fn incoming_packet(&mut self, packet: IbcPacketType) -> AppResult<MayResponse> {
+    pub(crate) fn incoming_packet(
+        &mut self,
+        packet: IbcPacketType,
+        sequence: Option<u64>,
+    ) -> AppResult<MayResponse> {
         match packet {
             IbcPacketType::AckPacket(packet) => Ok(MayResponse::Ok(self.packet_ack(packet)?)),
-            IbcPacketType::OutgoingPacket(packet) => self.packet_receive(packet),
+            IbcPacketType::OutgoingPacket(packet) => self.packet_receive(packet, sequence),
             IbcPacketType::OutgoinPacketRaw(packet) => {
                 let channel = self
                     .channels
@@ -240,32 +446,56 @@ where
                     .get(packet.src_channel.clone())?
                     .clone();
 
-                self.packet_receive(packet.into_full_packet(&channel)?)
+                self.packet_receive(packet.into_full_packet(&channel)?, sequence)
             }
             IbcPacketType::Timeout(packet) => Ok(MayResponse::Ok(self.packet_timeout(packet)?)),
-            IbcPacketType::CloseChannel { .. } => unimplemented!("Close channel is unimplemented"),
+            IbcPacketType::CloseChannel { channel_id } => bail!(IperError::UnsupportedMsg {
+                module: "ibc".to_string(),
+                msg: format!("CloseChannel {{ channel_id: {} }}", channel_id),
+            }),
         }
     }
 
-    pub(crate) fn packet_receive(&mut self, packet: OutgoingPacket) -> AppResult<MayResponse> {
-        let mut channels = self.channels.borrow_mut();
-
-        let channel = channels.get_mut(packet.dest.channel_id.clone())?;
-
-        *channel.sequence.borrow_mut() += 1;
+    /// `sequence` is assigned on the source chain when the `packet` is emitted, and tracked by the [`Ecosystem`](crate::Ecosystem).
+    pub(crate) fn packet_receive(
+        &mut self,
+        packet: OutgoingPacket,
+        sequence: Option<u64>,
+    ) -> AppResult<MayResponse> {
+        // The channels are not borrowed while calling the application, since it can send new packets
+        let (port, sequence, msg) = {
+            let mut channels = self.channels.borrow_mut();
+
+            let channel = channels.get_mut(packet.dest.channel_id.clone())?;
+
+            if channel.status != IbcChannelStatus::Connected {
+                bail!(IperError::ChannelNotOpen {
+                    chain_id: self.chain_id.clone(),
+                    channel: packet.dest.channel_id.clone(),
+                    status: channel.status.clone(),
+                })
+            }
 
-        let msg = IbcPacketReceiveMsg::new(
-            IbcPacket::new(
-                packet.data.clone(),
-                channel.remote.as_endpoint()?,
-                channel.local.as_endpoint()?,
-                *channel.sequence.borrow(),
-                packet.timeout.clone(),
-            ),
-            self.relayer.clone(),
-        );
+            let sequence = sequence.ok_or_else(|| IperError::SequenceNotAssigned {
+                chain_id: channel.remote.chain_id.clone(),
+                channel: packet.src.channel_id.clone(),
+            })?;
+
+            let msg = IbcPacketReceiveMsg::new(
+                IbcPacket::new(
+                    packet.data.clone(),
+                    channel.remote.as_endpoint()?,
+                    channel.local.as_endpoint()?,
+                    sequence,
+                    packet.timeout.clone(),
+                ),
+                self.relayer.clone(),
+            );
+
+            (channel.local.port.clone(), sequence, msg)
+        };
 
-        if let Err(err) = self.check_timeout(&packet) {
+        if let Err(err) = self.check_timeout(&packet, sequence) {
             emit_packet(
                 IbcPacketType::Timeout(TimeoutPacket {
                     original_packet: msg,
@@ -276,13 +506,13 @@ where
             return Ok(MayResponse::Err(err.to_string()));
         }
 
-        match &channel.local.port {
+        let dest = msg.packet.dest.clone();
+
+        let result = match &port {
             IbcPort::Contract(contract) => {
                 let code_id = self.app.contract_data(contract)?.code_id;
-                let ibc_details = self
-                    .code_ids
-                    .get(&code_id)
-                    .ok_or(anyhow!("Code ID not found"))?;
+                let ibc_details =
+                    ibc_entry_points(&self.code_ids, &self.chain_id, contract, code_id)?;
 
                 let mut ack: Option<Binary> = None;
 
@@ -297,17 +527,17 @@ where
                 if let Some(ack) = ack {
                     emit_packet(
                         IbcPacketType::AckPacket(AckPacket {
+                            // Contracts writing a `StdAck` report errors with `StdAck::Error`
+                            success: !matches!(from_json(&ack), Ok(StdAck::Error(_))),
                             ack,
                             original_packet: msg,
-                            // Mock as true for now. This field should not used on contract trigger on src chain
-                            success: true,
                             relayer: None,
                         }),
                         self.app.storage_mut(),
                     )?;
                 }
 
-                Ok(MayResponse::Ok(response))
+                MayResponse::Ok(response)
             }
             IbcPort::Module(name) => {
                 let (api, store, block, router) = self.app.use_parts();
@@ -351,25 +581,83 @@ where
                     )?;
                 }
 
-                Ok(result)
+                result
             }
-        }
+        };
+
+        PACKET_RECEIPTS.save(
+            self.app.storage_mut(),
+            (dest.port_id.as_str(), dest.channel_id.as_str(), sequence),
+            &Empty {},
+        )?;
+
+        Ok(result)
+    }
+
+    /// `sequence` assigned to an outgoing `pending packet` when it has been emitted, if any.
+    pub(crate) fn pending_sequence(&self, packet_id: u64) -> AppResult<Option<u64>> {
+        Ok(PENDING_SEQUENCES.may_load(self.app.storage(), packet_id)?)
+    }
+
+    /// Store the commitment of an outgoing `packet` once its `sequence` is assigned.
+    pub(crate) fn commit_packet(
+        &mut self,
+        packet_id: u64,
+        packet: &IbcPacketType,
+        sequence: u64,
+    ) -> AppResult<()> {
+        PENDING_SEQUENCES.remove(self.app.storage_mut(), packet_id);
+
+        let (port, channel, data, timeout) = match packet {
+            IbcPacketType::OutgoingPacket(packet) => (
+                &packet.src.port_id,
+                &packet.src.channel_id,
+                &packet.data,
+                &packet.timeout,
+            ),
+            IbcPacketType::OutgoinPacketRaw(packet) => (
+                &packet.src_port,
+                &packet.src_channel,
+                &packet.data,
+                &packet.timeout,
+            ),
+            _ => return Ok(()),
+        };
+
+        PACKET_COMMITMENTS.save(
+            self.app.storage_mut(),
+            (port.as_str(), channel.as_str(), sequence),
+            &packet_commitment(data, timeout),
+        )?;
+
+        Ok(())
+    }
+
+    /// Remove the commitment of a `packet` sent by this chain, once acked or timed out.
+    fn delete_commitment(&mut self, packet: &IbcPacket) {
+        PACKET_COMMITMENTS.remove(
+            self.app.storage_mut(),
+            (
+                packet.src.port_id.as_str(),
+                packet.src.channel_id.as_str(),
+                packet.sequence,
+            ),
+        );
     }
 
     pub(crate) fn packet_ack(&mut self, mut packet: AckPacket) -> AppResult<AppResponse> {
-        let channel = packet.get_src_channel();
+        let original_packet = packet.original_packet.packet.clone();
 
-        let channels = self.channels.borrow();
+        let channels = self.channels.clone();
+        let channels = channels.borrow();
 
-        let channel = channels.get(channel)?;
+        let channel = channels.get(packet.get_src_channel())?;
 
-        match &channel.local.port {
+        let response = match &channel.local.port {
             IbcPort::Contract(contract) => {
                 let code_id = self.app.contract_data(contract)?.code_id;
-                let ibc_details = self
-                    .code_ids
-                    .get(&code_id)
-                    .ok_or(anyhow!("Code ID not found"))?;
+                let ibc_details =
+                    ibc_entry_points(&self.code_ids, &self.chain_id, contract, code_id)?;
 
                 self.app.use_contract(contract, |deps, env| {
                     ibc_details
@@ -388,30 +676,33 @@ where
                         .packet_ack(&*api, write_cache, router, &*block, name, packet.clone())
                 })
             }
-        }
+        }?;
+
+        self.delete_commitment(&original_packet);
+
+        Ok(response)
     }
 
     pub(crate) fn packet_timeout(&mut self, packet: TimeoutPacket) -> AppResult<AppResponse> {
-        let channel = packet.original_packet.packet.src.channel_id.clone();
+        let original_packet = packet.original_packet.packet.clone();
 
-        let channels = self.channels.borrow();
+        let channels = self.channels.clone();
+        let channels = channels.borrow();
 
-        let channel = channels.get(channel)?;
+        let channel = channels.get(original_packet.src.channel_id.clone())?;
 
-        match &channel.local.port {
+        let response = match &channel.local.port {
             IbcPort::Contract(contract) => {
                 let code_id = self.app.contract_data(contract)?.code_id;
-                let ibc_details = self
-                    .code_ids
-                    .get(&code_id)
-                    .ok_or(anyhow!("Code ID not found"))?;
+                let ibc_details =
+                    ibc_entry_points(&self.code_ids, &self.chain_id, contract, code_id)?;
 
                 let msg = IbcPacketTimeoutMsg::new(
                     IbcPacket::new(
                         packet.original_packet.packet.data.clone(),
                         channel.local.as_endpoint()?,
                         channel.remote.as_endpoint()?,
-                        *channel.sequence.borrow(),
+                        packet.original_packet.packet.sequence,
                         packet.original_packet.packet.timeout.clone(),
                     ),
                     self.relayer.clone(),
@@ -432,38 +723,161 @@ where
                         .packet_timeout(&*api, write_cache, router, &*block, name, packet)
                 })
             }
+        }?;
+
+        self.delete_commitment(&original_packet);
+
+        Ok(response)
+    }
+
+    /// Return the current `revision_number` of the chain.
+    pub fn revision_number(&self) -> AppResult<u64> {
+        Ok(ChainHelper::load(self.app.storage())?.revision_number)
+    }
+
+    pub(crate) fn client_header(&self) -> AppResult<ClientHeader> {
+        let block = self.app.block_info();
+
+        Ok(ClientHeader {
+            revision_number: self.revision_number()?,
+            height: block.height,
+            time: block.time,
+        })
+    }
+
+    /// Increase the `revision_number` and reset the block height to `1`, returning the new `revision_number`.
+    pub(crate) fn upgrade(&mut self) -> AppResult<u64> {
+        let mut chain_helper = ChainHelper::load(self.app.storage())?;
+        chain_helper.revision_number += 1;
+        chain_helper.save(self.app.storage_mut())?;
+
+        self.app.update_block(|block| block.height = 1);
+
+        Ok(chain_helper.revision_number)
+    }
+
+    /// Return `true` if the chain has been halted by [`Ecosystem::halt_chain`](crate::Ecosystem::halt_chain).
+    pub fn is_halted(&self) -> AppResult<bool> {
+        Ok(ChainHelper::load(self.app.storage())?.halted)
+    }
+
+    /// Halt the chain: txs sent via [`Ecosystem::execute`](crate::Ecosystem::execute) fail and the block is not advanced anymore.
+    pub(crate) fn halt(&mut self) -> AppResult<()> {
+        let mut chain_helper = ChainHelper::load(self.app.storage())?;
+        chain_helper.halted = true;
+        chain_helper.save(self.app.storage_mut())
+    }
+
+    /// Resume the chain with a new block at `time`, or one second after the last block if `time` is not later.
+    pub(crate) fn resume(&mut self, time: Timestamp) -> AppResult<()> {
+        let mut chain_helper = ChainHelper::load(self.app.storage())?;
+        chain_helper.halted = false;
+        chain_helper.save(self.app.storage_mut())?;
+
+        self.app.update_block(|block| {
+            block.height += 1;
+            block.time = time.max(block.time.plus_seconds(1));
+        });
+
+        Ok(())
+    }
+
+    /// Advance the block by `blocks` blocks of `block_time` seconds each, unless the chain is halted.
+    pub(crate) fn produce_blocks(&mut self, blocks: u64, block_time: u64) -> AppResult<()> {
+        if !self.is_halted()? {
+            self.app.update_block(|block| {
+                block.height += blocks;
+                block.time = block.time.plus_seconds(blocks * block_time);
+            });
         }
+
+        Ok(())
+    }
+
+    /// Execute a privileged [`IperSudoMsg`] on the `IBC` or `Stargate` module.
+    ///
+    /// `cw-multi-test` fixes the `SudoT` of both modules to [`Empty`], so these msgs can't be sent via [`App::sudo`].
+    /// On error, the `storage` changes are reverted.
+    pub fn sudo_module(&mut self, msg: IperSudoMsg) -> AppResult<AppResponse> {
+        let (api, store, block, router) = self.app.use_parts();
+
+        transactional(&mut *store, |write_cache, _| match msg {
+            IperSudoMsg::IbcApplication { port, msg } => {
+                router
+                    .ibc
+                    .sudo_application(&*api, write_cache, router, &*block, &port, msg)
+            }
+            IperSudoMsg::StargateApplication { name, msg } => {
+                let stargate = (&router.stargate as &dyn Any)
+                    .downcast_ref::<IperStargateModule>()
+                    .ok_or(anyhow!("stargate module is not an IperStargateModule"))?;
+
+                stargate.sudo_application(&*api, write_cache, router, &*block, &name, msg)
+            }
+        })
     }
 
     pub(crate) fn get_next_channel_id(&self) -> u64 {
         self.channels.borrow().next_key()
     }
 
-    fn check_timeout(&self, packet: &OutgoingPacket) -> AppResult<()> {
-        let height = packet
-            .timeout
-            .block()
-            .map(|val| val.height)
-            .unwrap_or_default();
-
-        let nanos = packet
-            .timeout
-            .timestamp()
-            .map(|val| val.nanos())
-            .unwrap_or_default();
-
-        let invalid = match (height, nanos) {
-            (0, 0) => true,
-            (0, nanos) => self.app.block_info().time.nanos() > nanos,
-            (height, 0) => self.app.block_info().height > height,
-            (height, nanos) => {
-                !(self.app.block_info().time.nanos() > nanos
-                    && self.app.block_info().height > height)
+    /// Capture `storage`, `block` and `channels` of the [`IperApp`].
+    ///
+    /// Stored codes are kept in memory by the [`App`] and are not part of the snapshot.
+    pub(crate) fn snapshot(&self) -> AppSnapshot {
+        AppSnapshot {
+            storage: dump_storage(self.app.storage()),
+            block: self.app.block_info(),
+            channels: self.channels.borrow().clone(),
+        }
+    }
+
+    /// Restore a snapshot taken with [`IperApp::snapshot`].
+    pub(crate) fn restore(&mut self, snapshot: &AppSnapshot) {
+        load_storage(self.app.storage_mut(), &snapshot.storage);
+        self.app.set_block(snapshot.block.clone());
+        // Channels opened after the snapshot are removed
+        *self.channels.borrow_mut() = snapshot.channels.clone();
+    }
+
+    /// Export the state of the [`IperApp`] as [`ChainFixture`].
+    pub(crate) fn export_fixture(&self) -> AppResult<ChainFixture> {
+        Ok(ChainFixture {
+            chain_id: self.chain_id.clone(),
+            chain_prefix: ChainHelper::load(self.app.storage())?.chain_prefix,
+            codes: self.stored_codes.clone(),
+            block: self.app.block_info(),
+            storage: dump_storage(self.app.storage())
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+            channels: self.channels.borrow().export_fixture(),
+        })
+    }
+
+    fn check_timeout(&self, packet: &OutgoingPacket, sequence: u64) -> AppResult<()> {
+        let block = self.app.block_info();
+        let revision_number = self.revision_number()?;
+
+        let height = packet.timeout.block().filter(|val| val.height > 0);
+        let timestamp = packet.timeout.timestamp().filter(|val| val.nanos() > 0);
+
+        // Heights are compared as (revision, height) pairs
+        let invalid = match (height, timestamp) {
+            (None, None) => true,
+            (height, timestamp) => {
+                height
+                    .is_some_and(|val| (revision_number, block.height) > (val.revision, val.height))
+                    || timestamp.is_some_and(|val| block.time > val)
             }
         };
 
         if invalid {
-            bail!("Packet has timed out");
+            bail!(IperError::PacketTimedOut {
+                chain_id: self.chain_id.clone(),
+                channel: packet.dest.channel_id.clone(),
+                sequence,
+            });
         } else {
             Ok(())
         }
@@ -472,6 +886,7 @@ where
 
 pub trait IperAppRef {
     fn chain_id(&self) -> &str;
+    fn block_info(&self) -> BlockInfo;
     fn channel_connect(&mut self, channel_id: u64) -> AppResult<()>;
     fn get_next_channel_id(&self) -> u64;
     fn get_next_pending_packet(&self) -> AppResult<u64>;
@@ -481,12 +896,52 @@ pub trait IperAppRef {
         &mut self,
         local: &IbcChannelCreator,
         remote: &IbcChannelCreator,
-        sequence: Rc<RefCell<u64>>,
     ) -> AppResult<IbcChannelWrapper>;
-    fn incoming_packet(&mut self, packet: IbcPacketType) -> AppResult<MayResponse>;
+    fn incoming_packet(
+        &mut self,
+        packet: IbcPacketType,
+        sequence: Option<u64>,
+    ) -> AppResult<MayResponse>;
     fn remove_packet(&mut self, packet_id: u64) -> AppResult<()>;
+    fn pending_sequence(&self, packet_id: u64) -> AppResult<Option<u64>>;
+    fn commit_packet(
+        &mut self,
+        packet_id: u64,
+        packet: &IbcPacketType,
+        sequence: u64,
+    ) -> AppResult<()>;
     fn some_pending_packets(&self) -> bool;
     fn get_channel_info(&self, local_channel_id: String) -> AppResult<IbcChannelWrapper>;
+    fn snapshot(&self) -> AppSnapshot;
+    fn restore(&mut self, snapshot: &AppSnapshot);
+    fn chain_prefix(&self) -> AppResult<String>;
+    fn stored_codes(&self) -> &BTreeMap<u64, Option<String>>;
+    fn export_fixture(&self) -> AppResult<ChainFixture>;
+    fn execute(&mut self, sender: Addr, msg: &Binary) -> AppResult<AppResponse>;
+    fn light_client(&self, counterparty_chain_id: &str) -> AppResult<LightClient>;
+    fn create_light_client(
+        &mut self,
+        counterparty_chain_id: &str,
+        counterparty_header: &ClientHeader,
+        trusting_period: u64,
+    ) -> AppResult<String>;
+    fn update_light_client(
+        &mut self,
+        counterparty_chain_id: &str,
+        counterparty_header: &ClientHeader,
+    ) -> AppResult<()>;
+    fn freeze_light_client(&mut self, counterparty_chain_id: &str) -> AppResult<()>;
+    fn connection(&self, connection_id: &str) -> AppResult<ConnectionEnd>;
+    fn revision_number(&self) -> AppResult<u64>;
+    fn upgrade(&mut self) -> AppResult<u64>;
+    fn client_header(&self) -> AppResult<ClientHeader>;
+    fn is_halted(&self) -> AppResult<bool>;
+    fn halt(&mut self) -> AppResult<()>;
+    fn resume(&mut self, time: Timestamp) -> AppResult<()>;
+    fn produce_blocks(&mut self, blocks: u64, block_time: u64) -> AppResult<()>;
+    fn sudo_module(&mut self, msg: IperSudoMsg) -> AppResult<AppResponse>;
+    fn next_connection_id(&self) -> AppResult<String>;
+    fn save_connection(&mut self, connection: ConnectionEnd) -> AppResult<()>;
 }
 
 impl<BankT, ApiT, StorageT, CustomT, WasmT, StakingT, DistrT, GovT, StargateT> IperAppRef
@@ -518,6 +973,11 @@ where
     fn chain_id(&self) -> &str {
         &self.chain_id
     }
+
+    fn block_info(&self) -> BlockInfo {
+        self.app.block_info()
+    }
+
     fn channel_connect(&mut self, channel_id: u64) -> AppResult<()> {
         self.channel_connect(channel_id)
     }
@@ -539,19 +999,35 @@ where
         &mut self,
         local: &IbcChannelCreator,
         remote: &IbcChannelCreator,
-        sequence: Rc<RefCell<u64>>,
     ) -> AppResult<IbcChannelWrapper> {
-        self.open_channel(local, remote, sequence)
+        self.open_channel(local, remote)
     }
 
-    fn incoming_packet(&mut self, packet: IbcPacketType) -> AppResult<MayResponse> {
-        self.incoming_packet(packet)
+    fn incoming_packet(
+        &mut self,
+        packet: IbcPacketType,
+        sequence: Option<u64>,
+    ) -> AppResult<MayResponse> {
+        self.incoming_packet(packet, sequence)
     }
 
     fn remove_packet(&mut self, packet_id: u64) -> AppResult<()> {
         self.remove_packet(packet_id)
     }
 
+    fn pending_sequence(&self, packet_id: u64) -> AppResult<Option<u64>> {
+        self.pending_sequence(packet_id)
+    }
+
+    fn commit_packet(
+        &mut self,
+        packet_id: u64,
+        packet: &IbcPacketType,
+        sequence: u64,
+    ) -> AppResult<()> {
+        self.commit_packet(packet_id, packet, sequence)
+    }
+
     fn some_pending_packets(&self) -> bool {
         self.some_pending_packets()
     }
@@ -559,6 +1035,135 @@ where
     fn get_channel_info(&self, local_channel_id: String) -> AppResult<IbcChannelWrapper> {
         self.channels.borrow().get(local_channel_id).cloned()
     }
+
+    fn snapshot(&self) -> AppSnapshot {
+        self.snapshot()
+    }
+
+    fn restore(&mut self, snapshot: &AppSnapshot) {
+        self.restore(snapshot)
+    }
+
+    fn chain_prefix(&self) -> AppResult<String> {
+        Ok(ChainHelper::load(self.app.storage())?.chain_prefix)
+    }
+
+    fn stored_codes(&self) -> &BTreeMap<u64, Option<String>> {
+        &self.stored_codes
+    }
+
+    fn export_fixture(&self) -> AppResult<ChainFixture> {
+        self.export_fixture()
+    }
+
+    fn execute(&mut self, sender: Addr, msg: &Binary) -> AppResult<AppResponse> {
+        if self.is_halted()? {
+            bail!(IperError::ChainHalted {
+                chain_id: self.chain_id.clone()
+            })
+        }
+
+        self.app.execute(sender, from_json(msg)?)
+    }
+
+    fn light_client(&self, counterparty_chain_id: &str) -> AppResult<LightClient> {
+        self.light_client(counterparty_chain_id)
+    }
+
+    fn create_light_client(
+        &mut self,
+        counterparty_chain_id: &str,
+        counterparty_header: &ClientHeader,
+        trusting_period: u64,
+    ) -> AppResult<String> {
+        self.create_light_client(counterparty_chain_id, counterparty_header, trusting_period)
+    }
+
+    fn update_light_client(
+        &mut self,
+        counterparty_chain_id: &str,
+        counterparty_header: &ClientHeader,
+    ) -> AppResult<()> {
+        self.update_light_client(counterparty_chain_id, counterparty_header)
+    }
+
+    fn freeze_light_client(&mut self, counterparty_chain_id: &str) -> AppResult<()> {
+        self.freeze_light_client(counterparty_chain_id)
+    }
+
+    fn connection(&self, connection_id: &str) -> AppResult<ConnectionEnd> {
+        self.connection(connection_id)
+    }
+
+    fn revision_number(&self) -> AppResult<u64> {
+        self.revision_number()
+    }
+
+    fn upgrade(&mut self) -> AppResult<u64> {
+        self.upgrade()
+    }
+
+    fn client_header(&self) -> AppResult<ClientHeader> {
+        self.client_header()
+    }
+
+    fn is_halted(&self) -> AppResult<bool> {
+        self.is_halted()
+    }
+
+    fn halt(&mut self) -> AppResult<()> {
+        self.halt()
+    }
+
+    fn resume(&mut self, time: Timestamp) -> AppResult<()> {
+        self.resume(time)
+    }
+
+    fn produce_blocks(&mut self, blocks: u64, block_time: u64) -> AppResult<()> {
+        self.produce_blocks(blocks, block_time)
+    }
+
+    fn sudo_module(&mut self, msg: IperSudoMsg) -> AppResult<AppResponse> {
+        if self.is_halted()? {
+            bail!(IperError::ChainHalted {
+                chain_id: self.chain_id.clone()
+            })
+        }
+
+        self.sudo_module(msg)
+    }
+
+    fn next_connection_id(&self) -> AppResult<String> {
+        self.next_connection_id()
+    }
+
+    fn save_connection(&mut self, connection: ConnectionEnd) -> AppResult<()> {
+        self.save_connection(connection)
+    }
+}
+
+/// Return the `ibc entry points` of a `contract`.
+fn ibc_entry_points<'a, C, Q>(
+    code_ids: &'a BTreeMap<u64, Box<dyn IbcContract<C, Q>>>,
+    chain_id: &str,
+    contract: &Addr,
+    code_id: u64,
+) -> AppResult<&'a dyn IbcContract<C, Q>>
+where
+    C: CustomMsg,
+    Q: CustomQuery,
+{
+    code_ids
+        .get(&code_id)
+        .map(|ibc| ibc.as_ref())
+        .ok_or_else(|| {
+            IperError::MissingIbcEntryPoints {
+                chain_id: chain_id.to_string(),
+                contract: contract.to_string(),
+                code_id,
+            }
+            .into()
+        })
 }
 
 pub fn infallible_transactional<F, T, E>(
@@ -578,24 +1183,87 @@ where
     res
 }
 
+/// Privileged msg executed on the modules of an [`IperApp`] via [`IperApp::sudo_module`] or [`Ecosystem::sudo`](crate::Ecosystem::sudo).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IperSudoMsg {
+    /// Send `msg` to [`IbcApplication::sudo`](crate::IbcApplication::sudo) of the application bound to `port`.
+    IbcApplication {
+        /// Port of the [`IbcApplication`](crate::IbcApplication).
+        port: String,
+        /// Msg, usually `json` encoded.
+        msg: Binary,
+    },
+    /// Send `msg` to [`StargateApplication::stargate_sudo`](crate::StargateApplication::stargate_sudo) of the application named `name`.
+    StargateApplication {
+        /// Name of the [`StargateApplication`](crate::StargateApplication).
+        name: String,
+        /// Msg, usually `json` encoded.
+        msg: Binary,
+    },
+}
+
+/// Result that doesn't raise errors: on `Err`, the `storage` changes are reverted but the execution goes on,
+/// like a `packet` received with an error `ack`.
 #[derive(Debug, Clone)]
 pub enum InfallibleResult<T, E> {
+    /// Success, `storage` changes are committed.
     Ok(T),
+    /// Failure, `storage` changes are reverted.
     Err(E),
 }
 
 impl<T, E> InfallibleResult<T, E> {
+    /// Return `true` if [`InfallibleResult::Err`].
     pub fn is_err(&self) -> bool {
         matches!(self, InfallibleResult::Err(_))
     }
 
+    /// Return `true` if [`InfallibleResult::Ok`].
     pub fn is_ok(&self) -> bool {
         matches!(self, InfallibleResult::Ok(_))
     }
 }
 
-#[derive(Debug, Clone)]
+/// Result of a `packet` delivery.
+///
+/// Unlike [`AppResult`], an `Err` doesn't mean that the relay failed,
+/// but that the destination chain handled the `packet` returning an error (e.g. error `ack` or `timeout`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "MayResponseRepr", into = "MayResponseRepr")]
 pub enum MayResponse {
+    /// The `packet` has been handled successfully.
     Ok(AppResponse),
+    /// The `packet` handling returned an error.
     Err(String),
 }
+
+/// Serializable version of [`MayResponse`], since [`AppResponse`] doesn't implement `serde`.
+#[derive(Serialize, Deserialize)]
+enum MayResponseRepr {
+    Ok {
+        events: Vec<Event>,
+        data: Option<Binary>,
+    },
+    Err(String),
+}
+
+impl From<MayResponse> for MayResponseRepr {
+    fn from(value: MayResponse) -> Self {
+        match value {
+            MayResponse::Ok(response) => MayResponseRepr::Ok {
+                events: response.events,
+                data: response.data,
+            },
+            MayResponse::Err(err) => MayResponseRepr::Err(err),
+        }
+    }
+}
+
+impl From<MayResponseRepr> for MayResponse {
+    fn from(value: MayResponseRepr) -> Self {
+        match value {
+            MayResponseRepr::Ok { events, data } => MayResponse::Ok(AppResponse { events, data }),
+            MayResponseRepr::Err(err) => MayResponse::Err(err),
+        }
+    }
+}
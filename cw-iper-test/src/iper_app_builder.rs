@@ -7,9 +7,11 @@ use cw_multi_test::{
 };
 
 use crate::{
+    error::AppResult,
     ibc_application::IbcApplication,
     ibc_module::IperIbcModule,
     stargate::{IperStargateModule, StargateApplication},
+    stargate_applications::IbcCore,
 };
 
 /// Shorthcut of [`AppBuilder`] version for [`IperApp`](crate::iper_app::IperApp) that create an [`AppBuilder`] with:
@@ -25,6 +27,20 @@ use crate::{
 ///     .with_api(MockApiBech32::new("prefix"))
 pub struct IperAppBuilder;
 
+/// [`AppBuilder`] returned by [`IperAppBuilder::new`].
+pub(crate) type BaseAppBuilder = AppBuilder<
+    BankKeeper,
+    MockApiBech32,
+    MockStorage,
+    FailingModule<Empty, Empty, Empty>,
+    WasmKeeper<Empty, Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IperIbcModule,
+    GovFailingModule,
+    IperStargateModule,
+>;
+
 impl IperAppBuilder {
     #[allow(clippy::type_complexity)]
     #[allow(clippy::new_ret_no_self)]
@@ -61,12 +77,22 @@ impl IperAppBuilder {
 /// - `stargate`: [`IperStargateModule`].
 ///
 /// The function [`AppBuilderIperExt::with_ibc_app`] allow to insert a struct that implement both [`IbcApplication`] + [StargateApplication] inside [`IperIbcModule`] and [`IperStargateModule`].
+///
+/// The function [`AppBuilderIperExt::with_ibc_core`] allow to serve the `ibc core` gRPC queries.
 pub trait AppBuilderIperExt: Sized {
     /// insert a struct that implement both [`IbcApplication`] + [StargateApplication] inside the [`IperIbcModule`] and [`IperStargateModule`].
+    ///
+    /// Return an error if a `type_url` of the application is already handled by another [`StargateApplication`].
     fn with_ibc_app<T: IbcApplication + StargateApplication + 'static>(
         self,
         application: T,
-    ) -> Self;
+    ) -> AppResult<Self>;
+
+    /// Insert the [`StargateApplication`] serving the `ibc core` gRPC queries (`/ibc.core.channel.v1.Query/*` and `/ibc.core.connection.v1.Query/Connection`)
+    /// from the `channels` of the [`IperIbcModule`].
+    ///
+    /// Return an error if one of these `type_url` is already handled by another [`StargateApplication`].
+    fn with_ibc_core(self) -> AppResult<Self>;
 }
 
 impl<BankT, StorageT, CustomT: Module, WasmT, StakingT, DistrT, GovT> AppBuilderIperExt
@@ -88,17 +114,18 @@ where
     fn with_ibc_app<T: IbcApplication + StargateApplication + 'static>(
         mut self,
         application: T,
-    ) -> Self {
+    ) -> AppResult<Self> {
         let mut ibc = self.ibc;
         let mut stargate = self.stargate;
-        application.init(&self.api, &mut self.storage);
         let application = Rc::new(RefCell::new(application));
+        stargate.try_add_application(application.clone())?;
+
+        application.borrow().init(&self.api, &mut self.storage)?;
         let port_name = application.borrow().port_name();
 
-        ibc.applications.insert(port_name, application.clone());
-        stargate.try_add_application(application).unwrap();
+        ibc.applications.insert(port_name, application);
 
-        Self {
+        Ok(Self {
             api: self.api,
             block: self.block,
             storage: self.storage,
@@ -110,7 +137,27 @@ where
             ibc,
             gov: self.gov,
             stargate,
-        }
+        })
+    }
+
+    fn with_ibc_core(self) -> AppResult<Self> {
+        let mut stargate = self.stargate;
+        let application = IbcCore::new(self.ibc.channels.clone());
+        stargate.try_add_application(Rc::new(RefCell::new(application)))?;
+
+        Ok(Self {
+            api: self.api,
+            block: self.block,
+            storage: self.storage,
+            bank: self.bank,
+            wasm: self.wasm,
+            custom: self.custom,
+            staking: self.staking,
+            distribution: self.distribution,
+            ibc: self.ibc,
+            gov: self.gov,
+            stargate,
+        })
     }
 }
 
@@ -118,11 +165,14 @@ where
 /// - `api`:  [`MockApiBech32`];
 /// - `stargate`: [`IperStargateModule`].
 ///
-/// The function [`AppBuilderIperExt::with_stargate_app`] allow to insert a struct that implement [StargateApplication] inside [`IperStargateModule`].
+/// The function [`AppBuilderStargateExt::with_stargate_app`] allow to insert a struct that implement [StargateApplication] inside [`IperStargateModule`].
 
 pub trait AppBuilderStargateExt: Sized {
     /// Insert a struct that implement [StargateApplication] inside [`IperStargateModule`].
-    fn with_stargate_app<T: StargateApplication + 'static>(self, application: T) -> Self;
+    ///
+    /// Return an error if a `type_url` of the application is already handled by another [`StargateApplication`].
+    fn with_stargate_app<T: StargateApplication + 'static>(self, application: T)
+        -> AppResult<Self>;
 }
 
 impl<BankT, StorageT, CustomT: Module, WasmT, StakingT, DistrT, IbcT, GovT> AppBuilderStargateExt
@@ -141,12 +191,15 @@ impl<BankT, StorageT, CustomT: Module, WasmT, StakingT, DistrT, IbcT, GovT> AppB
 where
     StorageT: Storage,
 {
-    fn with_stargate_app<T: StargateApplication + 'static>(self, application: T) -> Self {
+    fn with_stargate_app<T: StargateApplication + 'static>(
+        self,
+        application: T,
+    ) -> AppResult<Self> {
         let mut stargate = self.stargate;
         let application = Rc::new(RefCell::new(application));
-        stargate.try_add_application(application.clone()).unwrap();
+        stargate.try_add_application(application)?;
 
-        Self {
+        Ok(Self {
             api: self.api,
             block: self.block,
             storage: self.storage,
@@ -158,6 +211,6 @@ where
             ibc: self.ibc,
             gov: self.gov,
             stargate,
-        }
+        })
     }
 }
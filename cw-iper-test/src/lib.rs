@@ -2,11 +2,19 @@
 
 #![deny(missing_docs)]
 
+// Allow the macros of `cw-iper-test-macros` to resolve `cw_iper_test` paths inside this crate.
+extern crate self as cw_iper_test;
+
 mod app_ext;
+mod assertions;
 mod chain_helper;
+mod client;
+mod connection;
 mod contracts;
 mod ecosystem;
 mod error;
+mod fixture;
+mod history;
 mod ibc;
 mod ibc_application;
 pub mod ibc_applications;
@@ -14,26 +22,57 @@ mod ibc_module;
 mod iper_app;
 mod iper_app_builder;
 mod middleware;
+mod record;
 mod response;
 mod router;
+mod snapshot;
+mod spec;
 mod stargate;
+pub mod stargate_applications;
+mod sync_ecosystem;
 
 pub use app_ext::AppExt;
+pub use assertions::{AsIperError, PacketAssertion};
 pub use chain_helper::ChainHelper;
+pub use client::{ClientStatus, LightClient, DEFAULT_TRUSTING_PERIOD};
+pub use connection::{ConnectionCounterparty, ConnectionEnd, ConnectionHandle, ConnectionState};
+#[doc(hidden)]
+pub use contracts::default_entry_points;
 pub use contracts::{ContractWrapperExt, IbcClosures, IperContract};
-pub use ecosystem::Ecosystem;
-pub use ibc::{IbcChannelCreator, IbcPort};
+pub use ecosystem::{Ecosystem, DEFAULT_MAX_RELAY_ITERATIONS};
+pub use error::{AppResult, IperError};
+pub use fixture::{ChainFixture, ChannelFixture, EcosystemFixture};
+pub use history::{
+    OriginTx, PacketStatus, RelayEventKind, RelayHistory, RelayHistoryEntry, RelayTreeNode,
+};
+pub use ibc::{IbcChannelCreator, IbcChannelStatus, IbcChannelWrapper, IbcPort};
 pub use ibc_application::{
     IbcApplication, IbcPortInterface, PacketReceiveFailing, PacketReceiveOk,
 };
-pub use ibc_module::IperIbcModule;
-pub use iper_app::{BaseIperApp, IperApp};
+pub use ibc_module::{AckPacket, IperIbcModule, TimeoutPacket};
+pub use iper_app::{BaseIperApp, InfallibleResult, IperApp, IperSudoMsg, MayResponse};
 pub use iper_app_builder::{AppBuilderIperExt, AppBuilderStargateExt, IperAppBuilder};
-pub use middleware::{AckSetting, MidRecFailing, MidRecOk, Middleware, MiddlewareResponse};
-pub use stargate::{IperStargateModule, StargateApplication, StargateName, StargateUrls};
+pub use middleware::{
+    AckSetting, IbcAndStargate, MidRecFailing, MidRecOk, Middleware, MiddlewareResponse,
+    MiddlewareStack,
+};
+pub use record::{RecordedAction, RecordedStep, Recording};
+pub use response::{BlockedPacket, RelayReport, RelayedResponse};
+pub use router::RouterWrapper;
+pub use spec::{
+    BalanceSpec, ChainSpec, ChannelEndSpec, ChannelSpec, ContractSpec, EcosystemSpec, PortSpec,
+    SpecChannel, SpecEcosystem, SpecRegistry,
+};
+pub use stargate::{
+    handle_stargate_msg, handle_stargate_query, IntoStargateResponse, IperStargateModule,
+    StargateApplication, StargateMsgCtx, StargateName, StargateQueryCtx, StargateResponse,
+    StargateUrls,
+};
+pub use sync_ecosystem::SyncEcosystem;
 
 pub use anyhow;
 pub use cw_multi_test;
+pub use prost;
 pub use serde_json;
 pub use strum;
 pub use strum_macros;
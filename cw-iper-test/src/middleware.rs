@@ -1,13 +1,13 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{any::type_name, cell::RefCell, rc::Rc};
 
 use cosmwasm_std::{
-    Addr, Api, Binary, BlockInfo, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcMsg,
-    IbcPacketReceiveMsg, Storage,
+    Addr, AnyMsg, Api, Binary, BlockInfo, GrpcQuery, IbcChannelConnectMsg, IbcChannelOpenMsg,
+    IbcMsg, IbcPacketReceiveMsg, Querier, Storage,
 };
 use cw_multi_test::AppResponse;
 
 use crate::{
-    error::AppResult,
+    error::{AppResult, IperError},
     ibc::IbcChannelWrapper,
     ibc_application::{IbcApplication, IbcPortInterface, PacketReceiveFailing, PacketReceiveOk},
     ibc_module::{AckPacket, TimeoutPacket},
@@ -17,6 +17,7 @@ use crate::{
     stargate::{StargateApplication, StargateName, StargateUrls},
 };
 
+/// Implemented by every [`IbcApplication`] that is also a [`StargateApplication`], like the inner application of a [`Middleware`].
 pub trait IbcAndStargate: IbcApplication + StargateApplication {}
 
 /// Enum rappresenting how the flow has to be controlled when triggering the `before` variant of [`Middleware`] functions.
@@ -35,7 +36,8 @@ pub enum MiddlewareResponse<S, C> {
 ///
 /// The core logic about []
 ///
-/// [`Middleware`] trait alredy implements [`IbcApplication`] and [`StargateApplication`] by default, so implementing [`Middleware`] implement also [`IbcApplication`] and [`StargateApplication`].
+/// A [`Middleware`] doesn't store the inner application: it's added on top of a [`MiddlewareStack`],
+/// which owns the inner application and implements [`IbcApplication`] and [`StargateApplication`].
 ///
 /// ## How it works
 ///
@@ -61,10 +63,18 @@ pub enum MiddlewareResponse<S, C> {
 /// Once [`Ics20`](crate::ibc_applications::Ics20) completes the execution of [`IbcApplication::packet_receive`], [`Middleware::mid_packet_receive_after`] in the
 /// [`IbcHook`](crate::ibc_applications::IbcHook) is triggered, where the smart contract is triggered to send the tokens. This functionality is
 /// managed in the `after` function as the [`Ics20`](crate::ibc_applications::Ics20) module must mint the tokens first.
+///
+/// ## Stargate msgs and queries
+///
+/// [`StargateApplication::stargate_msg`] and [`StargateApplication::stargate_query`] are wrapped in the same way by
+/// [`Middleware::mid_stargate_msg_before`]/[`Middleware::mid_stargate_msg_after`] and
+/// [`Middleware::mid_stargate_query_before`]/[`Middleware::mid_stargate_query_after`],
+/// allowing for example to intercept a stargate `MsgTransfer` like an [`IbcMsg::Transfer`].
+///
+/// A [`Middleware`] can register its own `type_urls` with [`Middleware::mid_msg_type_urls`] and [`Middleware::mid_query_type_urls`],
+/// next to the ones of the inner [`IbcApplication`]. Since the inner [`IbcApplication`] doesn't handle them,
+/// the `before` function has to return [`MiddlewareResponse::Stop`] for them.
 pub trait Middleware {
-    /// Return the inner [`IbcApplication`]
-    fn get_inner(&self) -> &dyn IbcAndStargate;
-
     /// Function triggered before the calling of inner [`IbcApplication::handle_outgoing_packet`].
     ///
     /// If the return type is [`MiddlewareResponse::Continue(IbcMsg)`], the returned [`IbcMsg`] will forwarded to the inner [`IbcApplication::handle_outgoing_packet`].
@@ -265,23 +275,117 @@ pub trait Middleware {
     ) -> AppResult<AppResponse> {
         Ok(AppResponse::default())
     }
+
+    /// `type_urls` of the [`AnyMsg`] handled by the [`Middleware`] itself, in addition to the ones of the inner [`IbcApplication`].
+    fn mid_msg_type_urls(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// `type_urls` of the [`GrpcQuery`] handled by the [`Middleware`] itself, in addition to the ones of the inner [`IbcApplication`].
+    fn mid_query_type_urls(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Function triggered before the calling of inner [`StargateApplication::stargate_msg`].
+    ///
+    /// If the return type is [`MiddlewareResponse::Continue(AnyMsg)`], the returned [`AnyMsg`] will forwarded to the inner [`StargateApplication::stargate_msg`].
+    #[allow(unused_variables)]
+    #[allow(clippy::too_many_arguments)]
+    fn mid_stargate_msg_before(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        sender: Addr,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: AnyMsg,
+    ) -> AppResult<MiddlewareResponse<AppResponse, AnyMsg>> {
+        Ok(MiddlewareResponse::Continue(msg))
+    }
+
+    /// Function triggered after [`StargateApplication::stargate_msg`] only if [`Middleware::mid_stargate_msg_before`] returned [`MiddlewareResponse::Continue`]
+    #[allow(unused_variables)]
+    #[allow(clippy::too_many_arguments)]
+    fn mid_stargate_msg_after(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        sender: Addr,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        original_msg: AnyMsg,
+        forwarded_msg: AnyMsg,
+        returning_reponse: AppResponse,
+    ) -> AppResult<AppResponse> {
+        Ok(AppResponse::default())
+    }
+
+    /// Function triggered before the calling of inner [`StargateApplication::stargate_query`].
+    ///
+    /// If the return type is [`MiddlewareResponse::Continue(GrpcQuery)`], the returned [`GrpcQuery`] will forwarded to the inner [`StargateApplication::stargate_query`].
+    #[allow(unused_variables)]
+    fn mid_stargate_query_before(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        storage: &dyn Storage,
+        querier: &dyn Querier,
+        request: GrpcQuery,
+    ) -> AppResult<MiddlewareResponse<Binary, GrpcQuery>> {
+        Ok(MiddlewareResponse::Continue(request))
+    }
+
+    /// Function triggered after [`StargateApplication::stargate_query`] only if [`Middleware::mid_stargate_query_before`] returned [`MiddlewareResponse::Continue`]
+    ///
+    /// Unlike the other `after` functions, the returned value replaces the response of the inner application.
+    #[allow(unused_variables)]
+    #[allow(clippy::too_many_arguments)]
+    fn mid_stargate_query_after(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        storage: &dyn Storage,
+        querier: &dyn Querier,
+        original_request: GrpcQuery,
+        forwarded_request: GrpcQuery,
+        returning_reponse: Binary,
+    ) -> AppResult<Binary> {
+        Ok(returning_reponse)
+    }
+}
+
+/// A [`Middleware`] of a [`MiddlewareStack`] with the application below it.
+struct MiddlewareLayer<M> {
+    middleware: M,
+    inner: Box<dyn IbcAndStargate>,
 }
 
-impl<T> IbcPortInterface for T
+impl<M> IbcPortInterface for MiddlewareLayer<M>
 where
-    T: Middleware,
+    M: Middleware,
 {
     fn port_name(&self) -> String {
-        self.get_inner().port_name()
+        self.inner.port_name()
     }
 }
 
-impl<T> IbcApplication for T
+impl<M> IbcApplication for MiddlewareLayer<M>
 where
-    T: Middleware + StargateUrls + 'static,
+    M: Middleware,
 {
-    fn init(&self, api: &cw_multi_test::MockApiBech32, storage: &mut dyn Storage) {
-        self.get_inner().init(api, storage)
+    fn init(&self, api: &cw_multi_test::MockApiBech32, storage: &mut dyn Storage) -> AppResult<()> {
+        self.inner.init(api, storage)
+    }
+
+    fn sudo(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: cosmwasm_std::Binary,
+    ) -> AppResult<AppResponse> {
+        self.inner.sudo(api, block, router, storage, msg)
     }
 
     fn handle_outgoing_packet(
@@ -294,7 +398,7 @@ where
         msg: IbcMsg,
         channel: IbcChannelWrapper,
     ) -> AppResult<AppResponse> {
-        match self.mid_handle_outgoing_packet(
+        match self.middleware.mid_handle_outgoing_packet(
             api,
             block,
             sender.clone(),
@@ -305,7 +409,7 @@ where
         )? {
             MiddlewareResponse::Stop(response) => Ok(response),
             MiddlewareResponse::Continue(next_msg) => {
-                let sub_response = self.get_inner().handle_outgoing_packet(
+                let sub_response = self.inner.handle_outgoing_packet(
                     api,
                     block,
                     sender.clone(),
@@ -315,7 +419,7 @@ where
                     channel.clone(),
                 )?;
 
-                let res = self.mid_handle_outgoing_packet_after(
+                let res = self.middleware.mid_handle_outgoing_packet_after(
                     api,
                     block,
                     sender,
@@ -340,7 +444,7 @@ where
         storage: Rc<RefCell<&mut dyn Storage>>,
         original_packet: IbcPacketReceiveMsg,
     ) -> InfallibleResult<PacketReceiveOk, PacketReceiveFailing> {
-        match self.mid_packet_receive_before(
+        match self.middleware.mid_packet_receive_before(
             api,
             block,
             router,
@@ -350,7 +454,7 @@ where
             InfallibleResult::Ok(res) => match res {
                 MiddlewareResponse::Stop(res) => InfallibleResult::Ok(res),
                 MiddlewareResponse::Continue(next_packet) => {
-                    let sub_response = self.get_inner().packet_receive(
+                    let sub_response = self.inner.packet_receive(
                         api,
                         block,
                         router,
@@ -358,7 +462,7 @@ where
                         next_packet.clone(),
                     );
 
-                    match self.mid_packet_receive_after(
+                    match self.middleware.mid_packet_receive_after(
                         api,
                         block,
                         router,
@@ -390,10 +494,16 @@ where
         storage: Rc<RefCell<&mut dyn Storage>>,
         msg: AckPacket,
     ) -> AppResult<AppResponse> {
-        match self.mid_packet_ack_before(api, block, router, storage.clone(), msg.clone())? {
+        match self.middleware.mid_packet_ack_before(
+            api,
+            block,
+            router,
+            storage.clone(),
+            msg.clone(),
+        )? {
             MiddlewareResponse::Stop(response) => Ok(response),
             MiddlewareResponse::Continue(next_packet) => {
-                let sub_response = self.get_inner().packet_ack(
+                let sub_response = self.inner.packet_ack(
                     api,
                     block,
                     router,
@@ -401,7 +511,7 @@ where
                     next_packet.clone(),
                 )?;
 
-                let res = self.mid_packet_ack_after(
+                let res = self.middleware.mid_packet_ack_after(
                     api,
                     block,
                     router,
@@ -423,18 +533,20 @@ where
         storage: Rc<RefCell<&mut dyn Storage>>,
         msg: TimeoutPacket,
     ) -> AppResult<AppResponse> {
-        match self.mid_packet_timeout_before(api, block, router, storage.clone(), msg.clone())? {
+        match self.middleware.mid_packet_timeout_before(
+            api,
+            block,
+            router,
+            storage.clone(),
+            msg.clone(),
+        )? {
             MiddlewareResponse::Stop(response) => Ok(response),
             MiddlewareResponse::Continue(next_packet) => {
-                let sub_response = self.get_inner().packet_timeout(
-                    api,
-                    block,
-                    router,
-                    storage.clone(),
-                    msg.clone(),
-                )?;
+                let sub_response =
+                    self.inner
+                        .packet_timeout(api, block, router, storage.clone(), msg.clone())?;
 
-                let res = self.mid_packet_timeout_after(
+                let res = self.middleware.mid_packet_timeout_after(
                     api,
                     block,
                     router,
@@ -456,18 +568,20 @@ where
         storage: Rc<RefCell<&mut dyn Storage>>,
         msg: IbcChannelOpenMsg,
     ) -> AppResult<AppResponse> {
-        match self.mid_open_channel_before(api, block, router, storage.clone(), msg.clone())? {
+        match self.middleware.mid_open_channel_before(
+            api,
+            block,
+            router,
+            storage.clone(),
+            msg.clone(),
+        )? {
             MiddlewareResponse::Stop(response) => Ok(response),
             MiddlewareResponse::Continue(next_msg) => {
-                let sub_response = self.get_inner().open_channel(
-                    api,
-                    block,
-                    router,
-                    storage.clone(),
-                    msg.clone(),
-                )?;
+                let sub_response =
+                    self.inner
+                        .open_channel(api, block, router, storage.clone(), msg.clone())?;
 
-                let res = self.mid_open_channel_after(
+                let res = self.middleware.mid_open_channel_after(
                     api,
                     block,
                     router,
@@ -490,17 +604,19 @@ where
         storage: Rc<RefCell<&mut dyn Storage>>,
         msg: IbcChannelConnectMsg,
     ) -> AppResult<AppResponse> {
-        match self.mid_channel_connect_before(api, block, router, storage.clone(), msg.clone())? {
+        match self.middleware.mid_channel_connect_before(
+            api,
+            block,
+            router,
+            storage.clone(),
+            msg.clone(),
+        )? {
             MiddlewareResponse::Stop(response) => Ok(response),
             MiddlewareResponse::Continue(next_msg) => {
-                let sub_response = self.get_inner().channel_connect(
-                    api,
-                    block,
-                    router,
-                    storage.clone(),
-                    msg.clone(),
-                )?;
-                let res = self.mid_channel_connect_after(
+                let sub_response =
+                    self.inner
+                        .channel_connect(api, block, router, storage.clone(), msg.clone())?;
+                let res = self.middleware.mid_channel_connect_after(
                     api,
                     block,
                     router,
@@ -516,18 +632,18 @@ where
     }
 }
 
-impl<T> StargateName for T
+impl<M> StargateName for MiddlewareLayer<M>
 where
-    T: Middleware,
+    M: Middleware,
 {
     fn stargate_name(&self) -> String {
-        self.get_inner().stargate_name()
+        self.inner.stargate_name()
     }
 }
 
-impl<T> StargateApplication for T
+impl<M> StargateApplication for MiddlewareLayer<M>
 where
-    T: Middleware + StargateUrls + 'static,
+    M: Middleware,
 {
     fn stargate_msg(
         &self,
@@ -539,51 +655,381 @@ where
         type_url: String,
         data: cosmwasm_std::Binary,
     ) -> AppResult<AppResponse> {
-        let res = self.get_inner().stargate_msg(
+        let msg = AnyMsg {
+            type_url,
+            value: data,
+        };
+
+        match self.middleware.mid_stargate_msg_before(
             api,
-            storage.clone(),
-            router,
             block,
-            sender,
-            type_url,
-            data,
-        )?;
+            sender.clone(),
+            router,
+            storage.clone(),
+            msg.clone(),
+        )? {
+            MiddlewareResponse::Stop(response) => Ok(response),
+            MiddlewareResponse::Continue(next_msg) => {
+                if !self.inner.is_msg_type_url(next_msg.type_url.clone()) {
+                    return Err(IperError::UnsupportedMsg {
+                        module: self.stargate_name(),
+                        msg: next_msg.type_url,
+                    }
+                    .into());
+                }
 
-        Ok(res)
+                let sub_response = self.inner.stargate_msg(
+                    api,
+                    storage.clone(),
+                    router,
+                    block,
+                    sender.clone(),
+                    next_msg.type_url.clone(),
+                    next_msg.value.clone(),
+                )?;
+
+                let res = self.middleware.mid_stargate_msg_after(
+                    api,
+                    block,
+                    sender,
+                    router,
+                    storage,
+                    msg,
+                    next_msg,
+                    sub_response.clone(),
+                )?;
+
+                Ok(res.merge(sub_response))
+            }
+        }
     }
 
     fn stargate_query(
         &self,
         api: &dyn Api,
         storage: &dyn Storage,
-        querier: &dyn cosmwasm_std::Querier,
+        querier: &dyn Querier,
         block: &BlockInfo,
-        request: cosmwasm_std::GrpcQuery,
+        request: GrpcQuery,
     ) -> AppResult<cosmwasm_std::Binary> {
-        self.get_inner()
-            .stargate_query(api, storage, querier, block, request)
+        match self.middleware.mid_stargate_query_before(
+            api,
+            block,
+            storage,
+            querier,
+            request.clone(),
+        )? {
+            MiddlewareResponse::Stop(response) => Ok(response),
+            MiddlewareResponse::Continue(next_request) => {
+                if !self.inner.is_query_type_url(next_request.path.clone()) {
+                    return Err(IperError::UnsupportedMsg {
+                        module: self.stargate_name(),
+                        msg: next_request.path,
+                    }
+                    .into());
+                }
+
+                let sub_response = self.inner.stargate_query(
+                    api,
+                    storage,
+                    querier,
+                    block,
+                    next_request.clone(),
+                )?;
+
+                self.middleware.mid_stargate_query_after(
+                    api,
+                    block,
+                    storage,
+                    querier,
+                    request,
+                    next_request,
+                    sub_response,
+                )
+            }
+        }
+    }
+
+    fn stargate_sudo(
+        &self,
+        api: &dyn Api,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        router: &RouterWrapper,
+        block: &BlockInfo,
+        msg: cosmwasm_std::Binary,
+    ) -> AppResult<AppResponse> {
+        self.inner.stargate_sudo(api, storage, router, block, msg)
     }
 }
 
-impl<T> StargateUrls for T
+impl<M> StargateUrls for MiddlewareLayer<M>
 where
-    T: Middleware,
+    M: Middleware,
 {
     fn is_query_type_url(&self, type_url: String) -> bool {
-        self.get_inner().is_query_type_url(type_url)
+        self.middleware.mid_query_type_urls().contains(&type_url)
+            || self.inner.is_query_type_url(type_url)
     }
 
     fn is_msg_type_url(&self, type_url: String) -> bool {
-        self.get_inner().is_msg_type_url(type_url)
+        self.middleware.mid_msg_type_urls().contains(&type_url)
+            || self.inner.is_msg_type_url(type_url)
     }
 
     fn type_urls(&self) -> Vec<String> {
-        self.get_inner().type_urls()
+        let mut type_urls = self.inner.type_urls();
+        type_urls.extend(self.middleware.mid_msg_type_urls());
+        type_urls.extend(self.middleware.mid_query_type_urls());
+        type_urls
     }
 }
 
 impl<T> IbcAndStargate for T where T: IbcApplication + StargateApplication {}
 
+/// Builder of a stack of [`Middleware`], wrapping a base [`IbcApplication`] without nesting the constructors by hand.
+///
+/// The stack owns the application below each [`Middleware`], so a [`Middleware`] is added by value:
+/// ```ignore
+/// let stack = MiddlewareStack::new(Ics20)
+///     .with(RateLimit::new(100))
+///     .with(IbcHook);
+///
+/// assert_eq!(stack.layers(), vec!["IbcHook", "RateLimit", "Ics20"]);
+///
+/// let app = IperAppBuilder::new("osmo").with_ibc_app(stack)?;
+/// ```
+///
+/// The last added layer is the outermost one: its `before` functions are called first and its `after` functions last.
+/// The full order is returned by [`MiddlewareStack::call_order`].
+pub struct MiddlewareStack {
+    top: Box<dyn IbcAndStargate>,
+    layers: Vec<String>,
+}
+
+impl MiddlewareStack {
+    /// Create a stack with only the base [`IbcApplication`].
+    pub fn new<T: IbcAndStargate + 'static>(application: T) -> Self {
+        Self {
+            top: Box::new(application),
+            layers: vec![short_type_name::<T>()],
+        }
+    }
+
+    /// Add a [`Middleware`] on top of the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `type_url` registered by the [`Middleware`] is already handled by a layer below.
+    /// Use [`MiddlewareStack::try_with`] to get an error instead.
+    pub fn with<M: Middleware + 'static>(self, middleware: M) -> Self {
+        self.try_with(middleware)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as [`MiddlewareStack::with`], returning an error if a `type_url` registered by the [`Middleware`] is already handled by a layer below.
+    pub fn try_with<M: Middleware + 'static>(mut self, middleware: M) -> AppResult<Self> {
+        let below = self.top.type_urls();
+
+        for type_url in middleware
+            .mid_msg_type_urls()
+            .into_iter()
+            .chain(middleware.mid_query_type_urls())
+        {
+            if below.contains(&type_url) {
+                return Err(IperError::DuplicatedTypeUrl { type_url }.into());
+            }
+        }
+
+        self.layers.push(short_type_name::<M>());
+
+        Ok(Self {
+            top: Box::new(MiddlewareLayer {
+                middleware,
+                inner: self.top,
+            }),
+            layers: self.layers,
+        })
+    }
+
+    /// Names of the layers, from the outermost [`Middleware`] to the base [`IbcApplication`].
+    pub fn layers(&self) -> Vec<String> {
+        self.layers.iter().rev().cloned().collect()
+    }
+
+    /// Order in which the layers are called for every function of the [`IbcApplication`] and [`StargateApplication`],
+    /// as `{layer}::before`, `{base}` and `{layer}::after`.
+    pub fn call_order(&self) -> Vec<String> {
+        let (base, middlewares) = self.layers.split_first().unwrap();
+
+        middlewares
+            .iter()
+            .rev()
+            .map(|layer| format!("{}::before", layer))
+            .chain([base.clone()])
+            .chain(middlewares.iter().map(|layer| format!("{}::after", layer)))
+            .collect()
+    }
+}
+
+impl IbcPortInterface for MiddlewareStack {
+    fn port_name(&self) -> String {
+        self.top.port_name()
+    }
+}
+
+impl IbcApplication for MiddlewareStack {
+    fn init(&self, api: &cw_multi_test::MockApiBech32, storage: &mut dyn Storage) -> AppResult<()> {
+        self.top.init(api, storage)
+    }
+
+    fn sudo(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: Binary,
+    ) -> AppResult<AppResponse> {
+        self.top.sudo(api, block, router, storage, msg)
+    }
+
+    fn handle_outgoing_packet(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        sender: Addr,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: IbcMsg,
+        channel: IbcChannelWrapper,
+    ) -> AppResult<AppResponse> {
+        self.top
+            .handle_outgoing_packet(api, block, sender, router, storage, msg, channel)
+    }
+
+    fn packet_receive(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: IbcPacketReceiveMsg,
+    ) -> InfallibleResult<PacketReceiveOk, PacketReceiveFailing> {
+        self.top.packet_receive(api, block, router, storage, msg)
+    }
+
+    fn packet_ack(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: AckPacket,
+    ) -> AppResult<AppResponse> {
+        self.top.packet_ack(api, block, router, storage, msg)
+    }
+
+    fn packet_timeout(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: TimeoutPacket,
+    ) -> AppResult<AppResponse> {
+        self.top.packet_timeout(api, block, router, storage, msg)
+    }
+
+    fn open_channel(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: IbcChannelOpenMsg,
+    ) -> AppResult<AppResponse> {
+        self.top.open_channel(api, block, router, storage, msg)
+    }
+
+    fn channel_connect(
+        &self,
+        api: &dyn Api,
+        block: &BlockInfo,
+        router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: IbcChannelConnectMsg,
+    ) -> AppResult<AppResponse> {
+        self.top.channel_connect(api, block, router, storage, msg)
+    }
+}
+
+impl StargateName for MiddlewareStack {
+    fn stargate_name(&self) -> String {
+        self.top.stargate_name()
+    }
+}
+
+impl StargateUrls for MiddlewareStack {
+    fn is_query_type_url(&self, type_url: String) -> bool {
+        self.top.is_query_type_url(type_url)
+    }
+
+    fn is_msg_type_url(&self, type_url: String) -> bool {
+        self.top.is_msg_type_url(type_url)
+    }
+
+    fn type_urls(&self) -> Vec<String> {
+        self.top.type_urls()
+    }
+}
+
+impl StargateApplication for MiddlewareStack {
+    fn stargate_msg(
+        &self,
+        api: &dyn Api,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        router: &RouterWrapper,
+        block: &BlockInfo,
+        sender: Addr,
+        type_url: String,
+        data: Binary,
+    ) -> AppResult<AppResponse> {
+        self.top
+            .stargate_msg(api, storage, router, block, sender, type_url, data)
+    }
+
+    fn stargate_query(
+        &self,
+        api: &dyn Api,
+        storage: &dyn Storage,
+        querier: &dyn Querier,
+        block: &BlockInfo,
+        request: GrpcQuery,
+    ) -> AppResult<Binary> {
+        self.top
+            .stargate_query(api, storage, querier, block, request)
+    }
+
+    fn stargate_sudo(
+        &self,
+        api: &dyn Api,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        router: &RouterWrapper,
+        block: &BlockInfo,
+        msg: Binary,
+    ) -> AppResult<AppResponse> {
+        self.top.stargate_sudo(api, storage, router, block, msg)
+    }
+}
+
+/// Name of the type without the module path and the generics.
+fn short_type_name<T>() -> String {
+    let name = type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+
+    name.rsplit("::").next().unwrap_or(name).to_string()
+}
+
 /// Define how the ack has to be handled
 pub enum AckSetting {
     /// The returned value override the ack returned from inner application.
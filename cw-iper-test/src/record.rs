@@ -0,0 +1,101 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use cosmwasm_std::{Addr, Binary};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::AppResult,
+    ibc_module::IbcPacketType,
+    iper_app::{IperSudoMsg, MayResponse},
+};
+
+/// Sequence of user txs and relay actions run against an [`Ecosystem`](crate::Ecosystem),
+/// created with [`Ecosystem::start_recording`](crate::Ecosystem::start_recording) and [`Ecosystem::stop_recording`](crate::Ecosystem::stop_recording).
+///
+/// A recording can be replayed on a fresh [`Ecosystem`](crate::Ecosystem) with [`Ecosystem::replay`](crate::Ecosystem::replay):
+/// ```ignore
+/// eco.start_recording()?;
+///
+/// eco.execute("neutron", sender, msg)?;
+/// eco.relay_all_packets()?;
+///
+/// eco.stop_recording()?.save("recordings/transfer.json")?;
+///
+/// // Later, on a fresh ecosystem built with the same setup
+/// fresh_eco.replay(&Recording::load("recordings/transfer.json")?)?;
+/// ```
+///
+/// Only txs sent via [`Ecosystem::execute`](crate::Ecosystem::execute) and [`Ecosystem::sudo`](crate::Ecosystem::sudo) are recorded,
+/// txs sent directly on the inner [`App`](cw_multi_test::App) are not.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Recording {
+    /// Recorded steps, in execution order.
+    pub steps: Vec<RecordedStep>,
+}
+
+impl Recording {
+    /// Serialize the recording as `json`.
+    pub fn to_json(&self) -> AppResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserialize a recording from `json`.
+    pub fn from_json(json: &str) -> AppResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Save the recording as `json` file.
+    pub fn save(&self, path: impl AsRef<Path>) -> AppResult<()> {
+        Ok(fs::write(path, self.to_json()?)?)
+    }
+
+    /// Load a recording from a `json` file.
+    pub fn load(path: impl AsRef<Path>) -> AppResult<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+/// Single step of a [`Recording`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RecordedStep {
+    /// Action executed.
+    pub action: RecordedAction,
+    /// Result of the action.
+    ///
+    /// For [`RecordedAction::Relay`], it is the result of the `packet` delivery,
+    /// [`MayResponse::Err`] also if the relay itself failed.
+    pub response: MayResponse,
+    /// Pending `packets` of each chain after the action.
+    pub pending_packets: BTreeMap<String, BTreeMap<u64, IbcPacketType>>,
+}
+
+/// Action of a [`RecordedStep`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordedAction {
+    /// Tx sent via [`Ecosystem::execute`](crate::Ecosystem::execute).
+    Execute {
+        /// `chain_id` of the chain where the tx has been sent.
+        chain_id: String,
+        /// Sender of the tx.
+        sender: Addr,
+        /// `json` encoded `CosmosMsg`.
+        msg: Binary,
+    },
+    /// `packet` relayed via [`Ecosystem::relay_packet`](crate::Ecosystem::relay_packet)
+    /// (also when called by [`Ecosystem::relay_next_packet`](crate::Ecosystem::relay_next_packet) or [`Ecosystem::relay_all_packets`](crate::Ecosystem::relay_all_packets)).
+    Relay {
+        /// `chain_id` of the chain where the `packet` was pending.
+        chain_id: String,
+        /// Id of the pending `packet`.
+        packet_id: u64,
+    },
+    /// Privileged msg sent via [`Ecosystem::sudo`](crate::Ecosystem::sudo).
+    Sudo {
+        /// `chain_id` of the chain where the msg has been sent.
+        chain_id: String,
+        /// The privileged msg.
+        msg: IperSudoMsg,
+    },
+}
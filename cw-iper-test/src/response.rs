@@ -4,18 +4,122 @@ use cosmwasm_std::{
 use cw_multi_test::AppResponse;
 
 use crate::{
-    error::AppResult,
+    error::{AppResult, IperError},
+    history::RelayEventKind,
     ibc_application::{PacketReceiveFailing, PacketReceiveOk},
     ibc_module::IbcPacketType,
-    iper_app::InfallibleResult,
+    iper_app::{InfallibleResult, MayResponse},
 };
 
+/// Single `packet` delivery of a [`RelayReport`].
+///
+/// As in [`RelayHistoryEntry`](crate::RelayHistoryEntry), `src` and `dest` fields always refer to the direction of the original `packet`,
+/// also for [`RelayEventKind::Ack`] and [`RelayEventKind::Timeout`] deliveries.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct RelayedResponse {
+    /// `packet` relayed.
     pub packet: IbcPacketType,
-    pub dest_response: AppResponse,
+    /// Kind of delivery.
+    pub kind: RelayEventKind,
+    /// `chain_id` of the chain that sent the original `packet`.
+    pub src_chain_id: String,
+    /// `channel-id` on the chain that sent the original `packet`.
+    pub src_channel: String,
+    /// `chain_id` of the chain that received the original `packet`.
+    pub dest_chain_id: String,
+    /// `channel-id` on the chain that received the original `packet`.
+    pub dest_channel: String,
+    /// `packet` sequence.
+    pub sequence: Option<u64>,
+    /// `acknowledgement` written on the destination chain, if any.
     pub ack: Option<Binary>,
-    pub src_response: Option<AppResponse>,
+    /// Whether the `acknowledgement` is a success, if any.
+    pub ack_success: Option<bool>,
+    /// Response of the destination chain on `packet` receive.
+    pub dest_response: Option<MayResponse>,
+    /// Response of the source chain on `acknowledgement` / `timeout`.
+    pub src_response: Option<MayResponse>,
+    /// Response of the chain where the delivery happened.
+    pub response: MayResponse,
+}
+
+/// Report of all `packets` relayed by [`Ecosystem::relay_all_packets`](crate::Ecosystem::relay_all_packets), in relay order.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct RelayReport {
+    /// Deliveries, in relay order.
+    pub relayed: Vec<RelayedResponse>,
+    /// `packets` left pending because they can't be delivered.
+    pub blocked: Vec<BlockedPacket>,
+}
+
+/// Pending `packet` skipped by [`Ecosystem::relay_all_packets`](crate::Ecosystem::relay_all_packets), since it can't be delivered.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BlockedPacket {
+    /// `chain_id` of the chain where the `packet` is pending.
+    pub chain_id: String,
+    /// Id of the pending `packet`.
+    pub packet_id: u64,
+    /// `packet` pending.
+    pub packet: IbcPacketType,
+    /// Why the `packet` can't be delivered.
+    pub reason: IperError,
+}
+
+impl RelayReport {
+    /// Number of deliveries.
+    pub fn len(&self) -> usize {
+        self.relayed.len()
+    }
+
+    /// Return `true` if no `packets` have been relayed.
+    pub fn is_empty(&self) -> bool {
+        self.relayed.is_empty()
+    }
+
+    /// Return all deliveries.
+    pub fn iter(&self) -> impl Iterator<Item = &RelayedResponse> {
+        self.relayed.iter()
+    }
+
+    /// Return all deliveries of a specific kind.
+    pub fn of_kind(&self, kind: RelayEventKind) -> Vec<&RelayedResponse> {
+        self.relayed
+            .iter()
+            .filter(|relayed| relayed.kind == kind)
+            .collect()
+    }
+
+    /// Return all deliveries related to a `packet`, identified by the `chain_id` and `channel` of one of the two sides and its `sequence`.
+    pub fn find(&self, chain_id: &str, channel: &str, sequence: u64) -> Vec<&RelayedResponse> {
+        self.relayed
+            .iter()
+            .filter(|relayed| {
+                relayed.sequence == Some(sequence)
+                    && ((relayed.src_chain_id == chain_id && relayed.src_channel == channel)
+                        || (relayed.dest_chain_id == chain_id && relayed.dest_channel == channel))
+            })
+            .collect()
+    }
+
+    /// Return the responses of all deliveries, in relay order.
+    pub fn responses(&self) -> Vec<&MayResponse> {
+        self.relayed
+            .iter()
+            .map(|relayed| &relayed.response)
+            .collect()
+    }
+}
+
+impl IntoIterator for RelayReport {
+    type Item = RelayedResponse;
+    type IntoIter = std::vec::IntoIter<RelayedResponse>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.relayed.into_iter()
+    }
 }
 
 pub trait IntoResponse<T> {
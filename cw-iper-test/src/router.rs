@@ -109,23 +109,23 @@ pub enum TryUseRouterResponse {
 }
 
 /// Alternative version of [`CosmosRouter`](cw_multi_test::CosmosRouter) interface.
-/// 
-/// Inside the 
+///
+/// Inside the
 /// [`IbcApplication`](crate::ibc_application::IbcApplication) and [`StargateApplication`](crate::stargate::StargateApplication),
 /// this version of [`CosmosRouter`](cw_multi_test::CosmosRouter) is used because both
 /// [`IbcApplication`](crate::ibc_application::IbcApplication) and [`StargateApplication`](crate::stargate::StargateApplication)
 /// needs to be vtable compatible.
-/// 
+///
 /// Passing the default [`CosmosRouter`](cw_multi_test::CosmosRouter) as argument of a function require to implements two generic type like [`Module::execute`](cw_multi_test::Module).
 /// Since [`IbcApplication`](crate::ibc_application::IbcApplication) and [`StargateApplication`](crate::stargate::StargateApplication),
 /// need to be vtable compatible, the default [`CosmosRouter`](cw_multi_test::CosmosRouter) cannot be used.
-/// 
+///
 /// ```ignore
 /// impl Module for IperStargateModule {
 ///     type ExecT = AnyMsg;
 ///     type QueryT = GrpcQuery;
 ///     type SudoT = Empty;
-/// 
+///
 ///     // <ExecC, QueryC> lead to Module to be not vtable compatible
 ///     fn execute<ExecC, QueryC>(
 ///         &self,
@@ -143,18 +143,20 @@ pub enum TryUseRouterResponse {
 ///         ...
 ///     }
 /// ```
-/// 
-/// 
-/// 
+///
+///
+///
 pub struct RouterWrapper<'a> {
     closure: &'a dyn Fn(UseRouter) -> AppResult<UseRouterResponse>,
 }
 
 impl<'a> RouterWrapper<'a> {
+    /// Create a [`RouterWrapper`] from a closure, usually built with the `router_closure` macro.
     pub fn new(closure: &'a dyn Fn(UseRouter) -> AppResult<UseRouterResponse>) -> Self {
         Self { closure }
     }
 
+    /// Query the chain with a `QueryRequest`.
     pub fn query<T: Serialize, R: DeserializeOwned>(&self, query: T) -> AppResult<R> {
         let res = (self.closure)(UseRouter::Query {
             b64_request: to_json_binary(&query).unwrap(),
@@ -168,6 +170,7 @@ impl<'a> RouterWrapper<'a> {
         }
     }
 
+    /// Execute a `CosmosMsg`.
     pub fn execute<T: Serialize>(&self, sender: Addr, comsos_msg: T) -> AppResult<AppResponse> {
         let res = (self.closure)(UseRouter::Exec {
             sender_msg: sender,
@@ -180,6 +183,7 @@ impl<'a> RouterWrapper<'a> {
         }
     }
 
+    /// Execute a `SudoMsg`.
     pub fn sudo(&self, msg: SudoMsg) -> AppResult<AppResponse> {
         let res = (self.closure)(UseRouter::Sudo { msg })?;
 
@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{BlockInfo, Order, Storage};
+
+use crate::{history::RelayHistory, ibc::Channels};
+
+/// Snapshot of the state of a single [`IperApp`](crate::IperApp).
+#[derive(Clone)]
+pub struct AppSnapshot {
+    pub storage: Vec<(Vec<u8>, Vec<u8>)>,
+    pub block: BlockInfo,
+    pub channels: Channels,
+}
+
+/// Snapshot of the state of a whole [`Ecosystem`](crate::Ecosystem).
+#[derive(Clone)]
+pub(crate) struct EcosystemSnapshot {
+    pub apps: BTreeMap<String, AppSnapshot>,
+    pub history: RelayHistory,
+}
+
+pub(crate) fn dump_storage(storage: &dyn Storage) -> Vec<(Vec<u8>, Vec<u8>)> {
+    storage.range(None, None, Order::Ascending).collect()
+}
+
+/// Replace the whole content of `storage` with `data`.
+pub(crate) fn load_storage(storage: &mut dyn Storage, data: &[(Vec<u8>, Vec<u8>)]) {
+    let keys: Vec<Vec<u8>> = storage
+        .range(None, None, Order::Ascending)
+        .map(|(key, _)| key)
+        .collect();
+
+    for key in keys {
+        storage.remove(&key);
+    }
+
+    for (key, value) in data {
+        storage.set(key, value);
+    }
+}
@@ -0,0 +1,471 @@
+use std::{cell::RefCell, collections::BTreeMap, fs, path::Path, rc::Rc};
+
+use anyhow::{anyhow, bail};
+use cosmwasm_std::{Addr, Coin, Empty, IbcOrder};
+use cw_multi_test::{no_init, BankSudo, Executor, SudoMsg};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_ext::AppExt,
+    connection::ConnectionHandle,
+    contracts::IperContract,
+    ecosystem::Ecosystem,
+    error::{AppResult, IperError},
+    ibc::IbcPort,
+    ibc_application::IbcApplication,
+    iper_app::BaseIperApp,
+    iper_app_builder::{AppBuilderIperExt, AppBuilderStargateExt, BaseAppBuilder, IperAppBuilder},
+    stargate::StargateApplication,
+};
+
+type AppFactory = Box<dyn Fn(BaseAppBuilder) -> AppResult<BaseAppBuilder>>;
+type ContractFactory = Box<dyn Fn() -> IperContract<Empty>>;
+
+/// Declarative description of an [`Ecosystem`], usually deserialized from `json`:
+/// ```ignore
+/// let spec = EcosystemSpec::from_json(r#"{
+///     "chains": [
+///         {
+///             "chain_id": "osmosis",
+///             "prefix": "osmo",
+///             "ibc_apps": ["ics20"],
+///             "balances": [{ "account": "owner", "coins": [{ "denom": "uosmo", "amount": "1000" }] }],
+///             "contracts": [{ "name": "counter_osmosis", "code": "counter", "msg": {} }]
+///         },
+///         { "chain_id": "neutron", "prefix": "neutron", "ibc_apps": ["ics20"] }
+///     ],
+///     "channels": [
+///         {
+///             "name": "transfer",
+///             "a": { "chain_id": "neutron", "port": { "module": "transfer" } },
+///             "b": { "chain_id": "osmosis", "port": { "module": "transfer" } }
+///         }
+///     ]
+/// }"#)?;
+///
+/// let registry = SpecRegistry::default()
+///     .with_ibc_app("ics20", || Ics20)
+///     .with_contract("counter", counter_contract);
+///
+/// let env = spec.build(&registry)?;
+///
+/// let counter = env.contract("counter_osmosis")?;
+/// let channel = env.channel("transfer")?;
+/// ```
+///
+/// `apps` and `contracts` can't be serialized, so they are referenced by name and resolved from a [`SpecRegistry`].
+/// Chains are built with [`IperAppBuilder`], so only [`BaseIperApp`]s are supported.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EcosystemSpec {
+    /// Chains of the [`Ecosystem`].
+    pub chains: Vec<ChainSpec>,
+    /// `channels` to open once all chains and `contracts` are ready.
+    #[serde(default)]
+    pub channels: Vec<ChannelSpec>,
+    /// Trusting period of the light clients, see [`Ecosystem::with_trusting_period`].
+    #[serde(default)]
+    pub trusting_period: Option<u64>,
+}
+
+/// Chain inside an [`EcosystemSpec`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ChainSpec {
+    /// `chain_id` of the chain.
+    pub chain_id: String,
+    /// `bech32` prefix of the chain.
+    pub prefix: String,
+    /// Names of the [`IbcApplication`]s registered with [`SpecRegistry::with_ibc_app`].
+    #[serde(default)]
+    pub ibc_apps: Vec<String>,
+    /// Names of the [`StargateApplication`]s registered with [`SpecRegistry::with_stargate_app`].
+    #[serde(default)]
+    pub stargate_apps: Vec<String>,
+    /// Genesis balances.
+    #[serde(default)]
+    pub balances: Vec<BalanceSpec>,
+    /// `contracts` to store and instantiate, in order.
+    #[serde(default)]
+    pub contracts: Vec<ContractSpec>,
+}
+
+/// Genesis balance inside a [`ChainSpec`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BalanceSpec {
+    /// Account name, converted to an address with `addr_make`.
+    pub account: String,
+    /// Coins minted to the account.
+    pub coins: Vec<Coin>,
+}
+
+/// `contract` inside a [`ChainSpec`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ContractSpec {
+    /// Name of the instance, used as handle in [`SpecEcosystem::contract`] and [`PortSpec::Contract`].
+    /// Names have to be unique across the whole [`EcosystemSpec`].
+    pub name: String,
+    /// Name of the code registered with [`SpecRegistry::with_contract`].
+    /// Each code is stored once per chain.
+    pub code: String,
+    /// `InstantiateMsg` of the `contract`.
+    pub msg: serde_json::Value,
+    /// Account name of the sender, converted to an address with `addr_make`.
+    #[serde(default = "default_sender")]
+    pub sender: String,
+    /// Account name of the admin, converted to an address with `addr_make`.
+    #[serde(default)]
+    pub admin: Option<String>,
+    /// Funds sent with the instantiation.
+    #[serde(default)]
+    pub funds: Vec<Coin>,
+    /// Label of the `contract`. Default is the `name`.
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// `channel` inside an [`EcosystemSpec`].
+///
+/// The `channel` is opened on the `connection` between the two chains,
+/// created with [`Ecosystem::open_connection`] the first time it's needed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ChannelSpec {
+    /// Name of the `channel`, used as handle in [`SpecEcosystem::channel`].
+    pub name: String,
+    /// Side of the `channel` on the first chain.
+    pub a: ChannelEndSpec,
+    /// Side of the `channel` on the second chain.
+    pub b: ChannelEndSpec,
+    /// Packet `order`. Default is [`IbcOrder::Unordered`].
+    #[serde(default = "default_order")]
+    pub order: IbcOrder,
+    /// Channel `version`. Default is `ics20-1`.
+    #[serde(default = "default_version")]
+    pub version: String,
+}
+
+/// Side of a [`ChannelSpec`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ChannelEndSpec {
+    /// `chain_id` of the chain.
+    pub chain_id: String,
+    /// `port` of the `channel`.
+    pub port: PortSpec,
+}
+
+/// `port` of a [`ChannelEndSpec`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortSpec {
+    /// Name of a `contract` of the [`EcosystemSpec`].
+    Contract(String),
+    /// `port` name of an [`IbcApplication`].
+    Module(String),
+}
+
+/// Opened `channel` of a [`SpecEcosystem`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SpecChannel {
+    /// `connection` of the `channel`.
+    pub connection: ConnectionHandle,
+    /// `chain_id` of the first chain.
+    pub chain_a: String,
+    /// `channel-id` on the first chain.
+    pub channel_a: String,
+    /// `chain_id` of the second chain.
+    pub chain_b: String,
+    /// `channel-id` on the second chain.
+    pub channel_b: String,
+}
+
+/// Registry of the `apps` and `contracts` referenced by name in an [`EcosystemSpec`].
+#[derive(Default)]
+pub struct SpecRegistry {
+    ibc_apps: BTreeMap<String, AppFactory>,
+    stargate_apps: BTreeMap<String, AppFactory>,
+    contracts: BTreeMap<String, ContractFactory>,
+}
+
+impl SpecRegistry {
+    /// Register an [`IbcApplication`]. `factory` is called once for each chain using it.
+    pub fn with_ibc_app<T: IbcApplication + StargateApplication + 'static>(
+        mut self,
+        name: impl Into<String>,
+        factory: impl Fn() -> T + 'static,
+    ) -> Self {
+        self.ibc_apps.insert(
+            name.into(),
+            Box::new(move |builder| builder.with_ibc_app(factory())),
+        );
+        self
+    }
+
+    /// Register a [`StargateApplication`]. `factory` is called once for each chain using it.
+    pub fn with_stargate_app<T: StargateApplication + 'static>(
+        mut self,
+        name: impl Into<String>,
+        factory: impl Fn() -> T + 'static,
+    ) -> Self {
+        self.stargate_apps.insert(
+            name.into(),
+            Box::new(move |builder| builder.with_stargate_app(factory())),
+        );
+        self
+    }
+
+    /// Register a `contract` code. `factory` is called once for each chain storing it.
+    pub fn with_contract(
+        mut self,
+        name: impl Into<String>,
+        factory: impl Fn() -> IperContract<Empty> + 'static,
+    ) -> Self {
+        self.contracts.insert(name.into(), Box::new(factory));
+        self
+    }
+}
+
+/// [`Ecosystem`] built from an [`EcosystemSpec`], with named handles to chains, `contracts` and `channels`.
+#[non_exhaustive]
+pub struct SpecEcosystem {
+    /// The [`Ecosystem`].
+    pub eco: Ecosystem,
+    /// Chains, by `chain_id`.
+    pub chains: BTreeMap<String, Rc<RefCell<BaseIperApp>>>,
+    /// `contracts` addresses, by name.
+    pub contracts: BTreeMap<String, Addr>,
+    /// `channels`, by name.
+    pub channels: BTreeMap<String, SpecChannel>,
+}
+
+impl SpecEcosystem {
+    /// Return a chain by `chain_id`.
+    pub fn chain(&self, chain_id: &str) -> AppResult<Rc<RefCell<BaseIperApp>>> {
+        self.chains.get(chain_id).cloned().ok_or_else(|| {
+            IperError::ChainNotFound {
+                chain_id: chain_id.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Return the address of a `contract` by name.
+    pub fn contract(&self, name: &str) -> AppResult<Addr> {
+        self.contracts
+            .get(name)
+            .cloned()
+            .ok_or(anyhow!("contract not found in spec: {}", name))
+    }
+
+    /// Return a `channel` by name.
+    pub fn channel(&self, name: &str) -> AppResult<&SpecChannel> {
+        self.channels
+            .get(name)
+            .ok_or(anyhow!("channel not found in spec: {}", name))
+    }
+}
+
+impl EcosystemSpec {
+    /// Deserialize a spec from `json`.
+    pub fn from_json(json: &str) -> AppResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a spec from a `json` file.
+    pub fn load(path: impl AsRef<Path>) -> AppResult<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Build the [`Ecosystem`], resolving `apps` and `contracts` from `registry`.
+    ///
+    /// Chains are built in order, then for each chain the genesis balances are minted and the `contracts` are instantiated.
+    /// Finally the `channels` are opened.
+    pub fn build(&self, registry: &SpecRegistry) -> AppResult<SpecEcosystem> {
+        let mut eco = Ecosystem::default();
+        let mut chains = BTreeMap::new();
+
+        if let Some(trusting_period) = self.trusting_period {
+            eco = eco.with_trusting_period(trusting_period);
+        }
+
+        let mut contracts = BTreeMap::new();
+
+        for spec in &self.chains {
+            // `MockApiBech32` requires a `'static` prefix
+            let prefix: &'static str = Box::leak(spec.prefix.clone().into_boxed_str());
+
+            let mut builder = IperAppBuilder::new(prefix);
+
+            for name in &spec.ibc_apps {
+                let factory = registry
+                    .ibc_apps
+                    .get(name)
+                    .ok_or(anyhow!("ibc app not registered: {}", name))?;
+                builder = factory(builder)?;
+            }
+
+            for name in &spec.stargate_apps {
+                let factory = registry
+                    .stargate_apps
+                    .get(name)
+                    .ok_or(anyhow!("stargate app not registered: {}", name))?;
+                builder = factory(builder)?;
+            }
+
+            let app = builder.build(no_init).into_iper_app(&spec.chain_id)?;
+
+            eco = eco.add_app(app.clone());
+
+            let mut app_ref = app.borrow_mut();
+
+            for balance in &spec.balances {
+                let to_address = app_ref.app.api().addr_make(&balance.account);
+
+                app_ref.app.sudo(SudoMsg::Bank(BankSudo::Mint {
+                    to_address: to_address.to_string(),
+                    amount: balance.coins.clone(),
+                }))?;
+            }
+
+            let mut code_ids: BTreeMap<&str, u64> = BTreeMap::new();
+
+            for contract in &spec.contracts {
+                if contracts.contains_key(&contract.name) {
+                    bail!("duplicated contract name in spec: {}", contract.name)
+                }
+
+                let code_id = match code_ids.get(contract.code.as_str()) {
+                    Some(code_id) => *code_id,
+                    None => {
+                        let factory = registry
+                            .contracts
+                            .get(&contract.code)
+                            .ok_or(anyhow!("contract not registered: {}", contract.code))?;
+
+                        let code_id = app_ref.store_named_ibc_code(&contract.code, factory());
+                        code_ids.insert(&contract.code, code_id);
+                        code_id
+                    }
+                };
+
+                let sender = app_ref.app.api().addr_make(&contract.sender);
+                let admin = contract
+                    .admin
+                    .as_ref()
+                    .map(|admin| app_ref.app.api().addr_make(admin).to_string());
+
+                let addr = app_ref.app.instantiate_contract(
+                    code_id,
+                    sender,
+                    &contract.msg,
+                    &contract.funds,
+                    contract.label.clone().unwrap_or(contract.name.clone()),
+                    admin,
+                )?;
+
+                contracts.insert(contract.name.clone(), addr);
+            }
+
+            drop(app_ref);
+
+            chains.insert(spec.chain_id.clone(), app);
+        }
+
+        let mut connections: BTreeMap<(String, String), ConnectionHandle> = BTreeMap::new();
+        let mut channels = BTreeMap::new();
+
+        for spec in &self.channels {
+            // Channels between the same chains share a connection, whatever side they are declared from
+            let key = if spec.a.chain_id <= spec.b.chain_id {
+                (spec.a.chain_id.clone(), spec.b.chain_id.clone())
+            } else {
+                (spec.b.chain_id.clone(), spec.a.chain_id.clone())
+            };
+
+            let connection = match connections.get(&key) {
+                Some(connection) => connection.clone(),
+                None => {
+                    let connection = eco.open_connection(&spec.a.chain_id, &spec.b.chain_id)?;
+                    connections.insert(key, connection.clone());
+                    connection
+                }
+            };
+
+            let connection = if connection.chain_a == spec.a.chain_id {
+                connection
+            } else {
+                connection.reversed()
+            };
+
+            let channel_a = next_channel_id(&chains, &spec.a.chain_id)?;
+            let channel_b = next_channel_id(&chains, &spec.b.chain_id)?;
+
+            eco.open_ibc_channel_on(
+                &connection,
+                spec.a.port.to_port(&contracts)?,
+                spec.b.port.to_port(&contracts)?,
+                spec.order.clone(),
+                &spec.version,
+            )?;
+
+            channels.insert(
+                spec.name.clone(),
+                SpecChannel {
+                    connection,
+                    chain_a: spec.a.chain_id.clone(),
+                    channel_a,
+                    chain_b: spec.b.chain_id.clone(),
+                    channel_b,
+                },
+            );
+        }
+
+        Ok(SpecEcosystem {
+            eco,
+            chains,
+            contracts,
+            channels,
+        })
+    }
+}
+
+impl PortSpec {
+    fn to_port(&self, contracts: &BTreeMap<String, Addr>) -> AppResult<IbcPort> {
+        match self {
+            PortSpec::Contract(name) => contracts
+                .get(name)
+                .map(|addr| IbcPort::Contract(addr.clone()))
+                .ok_or(anyhow!("contract not found in spec: {}", name)),
+            PortSpec::Module(name) => Ok(IbcPort::Module(name.clone())),
+        }
+    }
+}
+
+fn next_channel_id(
+    chains: &BTreeMap<String, Rc<RefCell<BaseIperApp>>>,
+    chain_id: &str,
+) -> AppResult<String> {
+    let app = chains
+        .get(chain_id)
+        .ok_or_else(|| IperError::ChainNotFound {
+            chain_id: chain_id.to_string(),
+        })?;
+
+    Ok(format!("channel-{}", app.borrow().get_next_channel_id()))
+}
+
+fn default_sender() -> String {
+    "owner".to_string()
+}
+
+fn default_order() -> IbcOrder {
+    IbcOrder::Unordered
+}
+
+fn default_version() -> String {
+    "ics20-1".to_string()
+}
@@ -1,16 +1,20 @@
 use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use cosmwasm_std::{
     Addr, AnyMsg, Api, Binary, BlockInfo, CustomMsg, CustomQuery, Empty, GrpcQuery, Querier,
     Storage,
 };
 use cw_multi_test::{AppResponse, CosmosRouter, Module, Stargate};
+use prost::{Message, Name};
 use serde::de::DeserializeOwned;
 
 use crate::router::{RouterWrapper, UseRouter, UseRouterResponse};
 
-use crate::{error::AppResult, router_closure};
+use crate::{
+    error::{AppResult, IperError},
+    router_closure,
+};
 
 use cosmwasm_std::from_json;
 
@@ -44,7 +48,7 @@ impl IperStargateModule {
                 return Ok(application);
             }
         }
-        bail!("application not found")
+        bail!(IperError::UnhandledTypeUrl { type_url })
     }
 
     fn get_application_by_query_type_url(
@@ -56,7 +60,7 @@ impl IperStargateModule {
                 return Ok(application);
             }
         }
-        bail!("application not found")
+        bail!(IperError::UnhandledTypeUrl { type_url })
     }
 
     /// Try add a StargateApplication.
@@ -75,7 +79,7 @@ impl IperStargateModule {
                         .borrow()
                         .is_query_type_url(type_url.clone())
                 {
-                    bail!("Dupplicated type_url among applications: {}", type_url)
+                    bail!(IperError::DuplicatedTypeUrl { type_url })
                 }
             }
         }
@@ -86,6 +90,37 @@ impl IperStargateModule {
 
         Ok(())
     }
+
+    pub(crate) fn sudo_application<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        name: &str,
+        msg: Binary,
+    ) -> AppResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        let application =
+            self.applications
+                .get(name)
+                .ok_or_else(|| IperError::StargateApplicationNotFound {
+                    name: name.to_string(),
+                })?;
+
+        let rc_storage = Rc::new(RefCell::new(storage));
+
+        application.borrow().stargate_sudo(
+            api,
+            rc_storage.clone(),
+            &RouterWrapper::new(&router_closure!(router, api, rc_storage, block)),
+            block,
+            msg,
+        )
+    }
 }
 
 impl Module for IperStargateModule {
@@ -144,13 +179,16 @@ impl Module for IperStargateModule {
         _storage: &mut dyn Storage,
         _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
         _block: &BlockInfo,
-        _msg: Self::SudoT,
+        msg: Self::SudoT,
     ) -> AppResult<AppResponse>
     where
         ExecC: CustomMsg + DeserializeOwned + 'static,
         QueryC: CustomQuery + DeserializeOwned + 'static,
     {
-        unimplemented!("Stargate Sudo is unimplemented")
+        bail!(IperError::UnsupportedMsg {
+            module: "stargate".to_string(),
+            msg: format!("{:?}", msg),
+        })
     }
 }
 
@@ -164,7 +202,8 @@ impl Stargate for IperStargateModule {}
 /// [`IperStargateModule`] will invoke a function implemented by this trait under the following conditions:
 ///
 /// - **stargate_msg**: A [`AnyMsg`] is targetting this [`StargateApplication`] for execution;
-/// - **stargate_query**: A [`GrpcQuery`] is targetting this [`StargateApplication`] for query.
+/// - **stargate_query**: A [`GrpcQuery`] is targetting this [`StargateApplication`] for query;
+/// - **stargate_sudo**: A privileged `msg` is targetting this [`StargateApplication`].
 ///
 /// ## Implementation of the trait:
 /// In order to be implemented, the struct has to implement both [`StargateUrls`] + [`StargateName`]
@@ -183,7 +222,29 @@ impl Stargate for IperStargateModule {}
 /// }
 /// #[urls]
 /// pub enum Ics20QueryUrls {}
+/// ```
+///
+/// ## Typed implementation:
+/// The `stargate_application` `proc_macro_attribute` implements all the traits from typed handlers,
+/// decoding the requests and encoding the responses as protobuf:
+/// ```ignore
+/// #[stargate_application(name = "ics20")]
+/// impl Ics20 {
+///     #[msg]
+///     fn transfer(&self, ctx: StargateMsgCtx, msg: MsgTransfer) -> AppResult<MsgTransferResponse> {
+///         ...
+///     }
 ///
+///     #[query("/ibc.applications.transfer.v1.Query/Params")]
+///     fn params(
+///         &self,
+///         ctx: StargateQueryCtx,
+///         request: QueryParamsRequest,
+///     ) -> AppResult<QueryParamsResponse> {
+///         ...
+///     }
+/// }
+/// ```
 pub trait StargateApplication: StargateUrls + StargateName {
     /// A [`AnyMsg`] is targetting this [`StargateApplication`] for execution;
     #[allow(clippy::too_many_arguments)]
@@ -207,6 +268,24 @@ pub trait StargateApplication: StargateUrls + StargateName {
         block: &BlockInfo,
         request: GrpcQuery,
     ) -> AppResult<Binary>;
+
+    /// A privileged `msg` is targetting this [`StargateApplication`] via [`IperSudoMsg::StargateApplication`](crate::IperSudoMsg::StargateApplication).
+    ///
+    /// By default, the `msg` is not supported.
+    fn stargate_sudo(
+        &self,
+        _api: &dyn Api,
+        _storage: Rc<RefCell<&mut dyn Storage>>,
+        _router: &RouterWrapper,
+        _block: &BlockInfo,
+        msg: Binary,
+    ) -> AppResult<AppResponse> {
+        Err(IperError::UnsupportedMsg {
+            module: self.stargate_name(),
+            msg: format!("sudo {}", msg),
+        }
+        .into())
+    }
 }
 
 /// Trait required by [`StargateApplication`] for the managment of `type_urls`.
@@ -226,3 +305,107 @@ pub trait StargateName {
     /// Return the name the [`StargateApplication`].
     fn stargate_name(&self) -> String;
 }
+
+/// Context of a typed `stargate msg` handler, implemented with the `stargate_application` macro.
+pub struct StargateMsgCtx<'a, 's> {
+    /// [`Api`] of the chain.
+    pub api: &'a dyn Api,
+    /// Storage of the [`IperStargateModule`].
+    pub storage: Rc<RefCell<&'s mut dyn Storage>>,
+    /// Router of the chain.
+    pub router: &'a RouterWrapper<'a>,
+    /// Current block.
+    pub block: &'a BlockInfo,
+    /// Sender of the msg.
+    pub sender: Addr,
+}
+
+/// Context of a typed `stargate query` handler, implemented with the `stargate_application` macro.
+pub struct StargateQueryCtx<'a> {
+    /// [`Api`] of the chain.
+    pub api: &'a dyn Api,
+    /// Storage of the [`IperStargateModule`].
+    pub storage: &'a dyn Storage,
+    /// Querier of the chain.
+    pub querier: &'a dyn Querier,
+    /// Current block.
+    pub block: &'a BlockInfo,
+}
+
+/// Response of a typed `stargate msg` handler that also returns `events`,
+/// like the ones of the messages executed with the [`RouterWrapper`].
+///
+/// `data` is encoded into [`AppResponse::data`].
+pub struct StargateResponse<M> {
+    /// Response, whose `data` is replaced by the encoded `data`.
+    pub response: AppResponse,
+    /// Protobuf response of the msg.
+    pub data: M,
+}
+
+impl<M> StargateResponse<M> {
+    /// Create a new [`StargateResponse`].
+    pub fn new(response: AppResponse, data: M) -> Self {
+        Self { response, data }
+    }
+}
+
+/// Types returned by a typed `stargate msg` handler:
+/// - a protobuf [`Message`], encoded into [`AppResponse::data`];
+/// - a [`StargateResponse`].
+pub trait IntoStargateResponse {
+    /// Convert into the [`AppResponse`] returned by [`StargateApplication::stargate_msg`].
+    fn into_app_response(self) -> AppResponse;
+}
+
+impl<M: Message> IntoStargateResponse for M {
+    fn into_app_response(self) -> AppResponse {
+        AppResponse {
+            events: vec![],
+            data: Some(Binary::from(self.encode_to_vec())),
+        }
+    }
+}
+
+impl<M: Message> IntoStargateResponse for StargateResponse<M> {
+    fn into_app_response(self) -> AppResponse {
+        AppResponse {
+            data: Some(Binary::from(self.data.encode_to_vec())),
+            ..self.response
+        }
+    }
+}
+
+/// Decode `data` as `M` and pass it to a typed `stargate msg` handler.
+///
+/// Used by the code generated by the `stargate_application` macro.
+pub fn handle_stargate_msg<M, R>(
+    data: &Binary,
+    handler: impl FnOnce(M) -> AppResult<R>,
+) -> AppResult<AppResponse>
+where
+    M: Message + Name + Default,
+    R: IntoStargateResponse,
+{
+    let msg = M::decode(data.as_slice())
+        .map_err(|err| anyhow!("failed to decode {}: {}", M::type_url(), err))?;
+
+    Ok(handler(msg)?.into_app_response())
+}
+
+/// Decode the `data` of a [`GrpcQuery`] as `M`, pass it to a typed `stargate query` handler and encode the response.
+///
+/// Used by the code generated by the `stargate_application` macro.
+pub fn handle_stargate_query<M, R>(
+    data: &Binary,
+    handler: impl FnOnce(M) -> AppResult<R>,
+) -> AppResult<Binary>
+where
+    M: Message + Name + Default,
+    R: Message,
+{
+    let request = M::decode(data.as_slice())
+        .map_err(|err| anyhow!("failed to decode {}: {}", M::type_url(), err))?;
+
+    Ok(Binary::from(handler(request)?.encode_to_vec()))
+}
@@ -0,0 +1,476 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use cosmwasm_std::{
+    from_json, storage_keys::to_length_prefixed, to_json_vec, Addr, BankMsg, Binary, Coin,
+    ContractInfoResponse, ContractResult, CosmosMsg, Empty, Order, QuerierWrapper, QueryRequest,
+    StakingMsg, StdResult, Storage, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw_iper_test_macros::stargate_application;
+use cw_multi_test::ContractData;
+use cw_storage_plus::Map;
+use ibc_proto::cosmos::{
+    bank::v1beta1::{
+        DenomUnit, Metadata, MsgSend, MsgSendResponse, QueryAllBalancesRequest,
+        QueryAllBalancesResponse, QueryBalanceRequest, QueryBalanceResponse,
+        QueryDenomMetadataRequest, QueryDenomMetadataResponse, QueryDenomsMetadataRequest,
+        QueryDenomsMetadataResponse, QuerySupplyOfRequest, QuerySupplyOfResponse,
+    },
+    base::v1beta1::Coin as ProtoCoin,
+    staking::v1beta1::{MsgDelegate, MsgDelegateResponse},
+};
+use prost::{Message, Name};
+
+use crate::{
+    error::{AppResult, IperError},
+    ibc_applications::Ics20Helper,
+    stargate::{StargateMsgCtx, StargateQueryCtx, StargateResponse},
+};
+
+use super::wasm_v1::{
+    AccessConfig, ContractInfo, MsgExecuteContract, MsgExecuteContractResponse,
+    MsgInstantiateContract, MsgInstantiateContractResponse, QueryCodeInfoRequest,
+    QueryCodeInfoResponse, QueryContractInfoRequest, QueryContractInfoResponse,
+    QueryContractsByCodeRequest, QueryContractsByCodeResponse, QueryRawContractStateRequest,
+    QueryRawContractStateResponse, QuerySmartContractStateRequest, QuerySmartContractStateResponse,
+};
+
+const DENOM_METADATA: Map<&str, Binary> = Map::new("cosmos_sdk_denom_metadata");
+
+/// `AccessType::ACCESS_TYPE_EVERYBODY` of `cosmwasm.wasm.v1`.
+const ACCESS_TYPE_EVERYBODY: i32 = 3;
+
+/// [`StargateApplication`](crate::StargateApplication) handling the core Cosmos SDK msgs,
+/// forwarding them to the `BankKeeper`, `StakeKeeper` and `WasmKeeper` of the [`App`](cw_multi_test::App).
+///
+/// Supported msgs:
+/// - `/cosmos.bank.v1beta1.MsgSend`;
+/// - `/cosmos.staking.v1beta1.MsgDelegate`;
+/// - `/cosmwasm.wasm.v1.MsgExecuteContract`;
+/// - `/cosmwasm.wasm.v1.MsgInstantiateContract`.
+///
+/// The signer of each msg has to be the sender of the `AnyMsg`.
+///
+/// Supported queries:
+/// - `/cosmos.bank.v1beta1.Query/Balance`;
+/// - `/cosmos.bank.v1beta1.Query/AllBalances`;
+/// - `/cosmos.bank.v1beta1.Query/SupplyOf`;
+/// - `/cosmos.bank.v1beta1.Query/DenomMetadata`;
+/// - `/cosmos.bank.v1beta1.Query/DenomsMetadata`;
+/// - `/cosmwasm.wasm.v1.Query/ContractInfo`;
+/// - `/cosmwasm.wasm.v1.Query/SmartContractState`;
+/// - `/cosmwasm.wasm.v1.Query/RawContractState`;
+/// - `/cosmwasm.wasm.v1.Query/CodeInfo`;
+/// - `/cosmwasm.wasm.v1.Query/ContractsByCode`.
+///
+/// The wasm queries are served through the wasm querier: `ContractInfo.label` is always empty,
+/// `ContractInfo.created` is always [`None`] and every code can be instantiated by everybody.
+/// `ContractsByCode` reads the contracts saved by the `WasmKeeper`: the ones created in the same block are ordered by address.
+///
+/// Pagination is ignored, all the results are returned in a single page.
+///
+/// Denom metadata is set with [`CosmosSdk::set_denom_metadata`].
+/// `ibc/{hash}` vouchers received by [`Ics20`](crate::ibc_applications::Ics20) get a default metadata
+/// built from their trace, like `ibc-go` does.
+#[derive(Default, Clone)]
+pub struct CosmosSdk;
+
+#[stargate_application(name = "cosmos_sdk")]
+impl CosmosSdk {
+    #[msg]
+    fn send(
+        &self,
+        ctx: StargateMsgCtx,
+        msg: MsgSend,
+    ) -> AppResult<StargateResponse<MsgSendResponse>> {
+        ensure_signer::<MsgSend>(&ctx.sender, &msg.from_address)?;
+
+        let response = ctx.router.execute(
+            ctx.sender,
+            CosmosMsg::<Empty>::Bank(BankMsg::Send {
+                to_address: msg.to_address,
+                amount: into_coins(msg.amount)?,
+            }),
+        )?;
+
+        Ok(StargateResponse::new(response, MsgSendResponse {}))
+    }
+
+    #[msg]
+    fn delegate(
+        &self,
+        ctx: StargateMsgCtx,
+        msg: MsgDelegate,
+    ) -> AppResult<StargateResponse<MsgDelegateResponse>> {
+        ensure_signer::<MsgDelegate>(&ctx.sender, &msg.delegator_address)?;
+
+        let amount = msg.amount.ok_or(anyhow!("missing delegation amount"))?;
+
+        let response = ctx.router.execute(
+            ctx.sender,
+            CosmosMsg::<Empty>::Staking(StakingMsg::Delegate {
+                validator: msg.validator_address,
+                amount: into_coin(amount)?,
+            }),
+        )?;
+
+        Ok(StargateResponse::new(response, MsgDelegateResponse {}))
+    }
+
+    #[msg]
+    fn execute_contract(
+        &self,
+        ctx: StargateMsgCtx,
+        msg: MsgExecuteContract,
+    ) -> AppResult<StargateResponse<MsgExecuteContractResponse>> {
+        ensure_signer::<MsgExecuteContract>(&ctx.sender, &msg.sender)?;
+
+        let response = ctx.router.execute(
+            ctx.sender,
+            CosmosMsg::<Empty>::Wasm(WasmMsg::Execute {
+                contract_addr: msg.contract,
+                msg: Binary::from(msg.msg),
+                funds: into_coins(msg.funds)?,
+            }),
+        )?;
+
+        // The WasmKeeper already sets the data encoded as MsgExecuteContractResponse
+        let data = MsgExecuteContractResponse::decode(
+            response.data.clone().unwrap_or_default().as_slice(),
+        )?;
+
+        Ok(StargateResponse::new(response, data))
+    }
+
+    #[msg]
+    fn instantiate_contract(
+        &self,
+        ctx: StargateMsgCtx,
+        msg: MsgInstantiateContract,
+    ) -> AppResult<StargateResponse<MsgInstantiateContractResponse>> {
+        ensure_signer::<MsgInstantiateContract>(&ctx.sender, &msg.sender)?;
+
+        let response = ctx.router.execute(
+            ctx.sender,
+            CosmosMsg::<Empty>::Wasm(WasmMsg::Instantiate {
+                admin: Some(msg.admin).filter(|admin| !admin.is_empty()),
+                code_id: msg.code_id,
+                msg: Binary::from(msg.msg),
+                funds: into_coins(msg.funds)?,
+                label: msg.label,
+            }),
+        )?;
+
+        // The WasmKeeper already sets the data encoded as MsgInstantiateContractResponse
+        let data = MsgInstantiateContractResponse::decode(
+            response.data.clone().unwrap_or_default().as_slice(),
+        )?;
+
+        Ok(StargateResponse::new(response, data))
+    }
+
+    #[query("/cosmos.bank.v1beta1.Query/Balance")]
+    fn balance(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryBalanceRequest,
+    ) -> AppResult<QueryBalanceResponse> {
+        let balance = QuerierWrapper::<Empty>::new(ctx.querier)
+            .query_balance(request.address, request.denom)?;
+
+        Ok(QueryBalanceResponse {
+            balance: Some(from_coin(balance)),
+        })
+    }
+
+    #[query("/cosmos.bank.v1beta1.Query/AllBalances")]
+    fn all_balances(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryAllBalancesRequest,
+    ) -> AppResult<QueryAllBalancesResponse> {
+        let address = ctx.api.addr_validate(&request.address)?;
+        let balances = load_bank_balances(ctx.storage, &address)?;
+
+        Ok(QueryAllBalancesResponse {
+            balances: balances.into_iter().map(from_coin).collect(),
+            pagination: None,
+        })
+    }
+
+    #[query("/cosmos.bank.v1beta1.Query/SupplyOf")]
+    fn supply_of(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QuerySupplyOfRequest,
+    ) -> AppResult<QuerySupplyOfResponse> {
+        let supply = QuerierWrapper::<Empty>::new(ctx.querier).query_supply(request.denom)?;
+
+        Ok(QuerySupplyOfResponse {
+            amount: Some(from_coin(supply)),
+        })
+    }
+
+    #[query("/cosmos.bank.v1beta1.Query/DenomMetadata")]
+    fn denom_metadata(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryDenomMetadataRequest,
+    ) -> AppResult<QueryDenomMetadataResponse> {
+        let metadata = Self::load_denom_metadata(ctx.storage, &request.denom)?
+            .ok_or(anyhow!("client metadata for denom {}", request.denom))?;
+
+        Ok(QueryDenomMetadataResponse {
+            metadata: Some(metadata),
+        })
+    }
+
+    #[query("/cosmos.bank.v1beta1.Query/DenomsMetadata")]
+    fn denoms_metadata(
+        &self,
+        ctx: StargateQueryCtx,
+        _request: QueryDenomsMetadataRequest,
+    ) -> AppResult<QueryDenomsMetadataResponse> {
+        let mut metadatas = Ics20Helper::incoming_vouchers(ctx.storage)?
+            .into_iter()
+            .filter(|(denom, _)| !DENOM_METADATA.has(ctx.storage, denom))
+            .map(|(denom, trace)| voucher_metadata(&denom, &trace))
+            .collect::<Vec<_>>();
+
+        for data in DENOM_METADATA
+            .range(ctx.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, data)| data))
+            .collect::<StdResult<Vec<_>>>()?
+        {
+            metadatas.push(Metadata::decode(data.as_slice())?);
+        }
+
+        metadatas.sort_by(|a, b| a.base.cmp(&b.base));
+
+        Ok(QueryDenomsMetadataResponse {
+            metadatas,
+            pagination: None,
+        })
+    }
+
+    #[query("/cosmwasm.wasm.v1.Query/ContractInfo")]
+    fn contract_info(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryContractInfoRequest,
+    ) -> AppResult<QueryContractInfoResponse> {
+        let address = ctx.api.addr_validate(&request.address)?;
+
+        let contract =
+            QuerierWrapper::<Empty>::new(ctx.querier).query_wasm_contract_info(&address)?;
+
+        Ok(QueryContractInfoResponse {
+            address: address.to_string(),
+            contract_info: Some(proto_contract_info(contract)),
+        })
+    }
+
+    #[query("/cosmwasm.wasm.v1.Query/SmartContractState")]
+    fn smart_contract_state(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QuerySmartContractStateRequest,
+    ) -> AppResult<QuerySmartContractStateResponse> {
+        let query = to_json_vec(&QueryRequest::<Empty>::Wasm(WasmQuery::Smart {
+            contract_addr: request.address,
+            msg: Binary::from(request.query_data),
+        }))?;
+
+        // Forwarded as raw query, since the response is returned as bytes without deserializing it
+        let data = match ctx.querier.raw_query(&query) {
+            SystemResult::Ok(ContractResult::Ok(data)) => data,
+            SystemResult::Ok(ContractResult::Err(err)) => return Err(anyhow!(err)),
+            SystemResult::Err(err) => return Err(anyhow!(err)),
+        };
+
+        Ok(QuerySmartContractStateResponse {
+            data: data.to_vec(),
+        })
+    }
+
+    #[query("/cosmwasm.wasm.v1.Query/RawContractState")]
+    fn raw_contract_state(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryRawContractStateRequest,
+    ) -> AppResult<QueryRawContractStateResponse> {
+        let data = QuerierWrapper::<Empty>::new(ctx.querier)
+            .query_wasm_raw(request.address, request.query_data)?;
+
+        Ok(QueryRawContractStateResponse {
+            data: data.unwrap_or_default(),
+        })
+    }
+
+    #[query("/cosmwasm.wasm.v1.Query/CodeInfo")]
+    fn code_info(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryCodeInfoRequest,
+    ) -> AppResult<QueryCodeInfoResponse> {
+        let code_info =
+            QuerierWrapper::<Empty>::new(ctx.querier).query_wasm_code_info(request.code_id)?;
+
+        Ok(QueryCodeInfoResponse {
+            code_id: code_info.code_id,
+            creator: code_info.creator.to_string(),
+            checksum: code_info.checksum.as_slice().to_vec(),
+            instantiate_permission: Some(AccessConfig {
+                permission: ACCESS_TYPE_EVERYBODY,
+                addresses: vec![],
+            }),
+        })
+    }
+
+    #[query("/cosmwasm.wasm.v1.Query/ContractsByCode")]
+    fn contracts_by_code(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryContractsByCodeRequest,
+    ) -> AppResult<QueryContractsByCodeResponse> {
+        let mut contracts = load_wasm_contracts(ctx.storage)?
+            .into_iter()
+            .filter(|(_, contract)| contract.code_id == request.code_id)
+            .collect::<Vec<_>>();
+
+        // Same ordering of wasmd: by creation, then by address
+        contracts.sort_by(|(a_addr, a), (b_addr, b)| {
+            a.created.cmp(&b.created).then_with(|| a_addr.cmp(b_addr))
+        });
+
+        Ok(QueryContractsByCodeResponse {
+            contracts: contracts.into_iter().map(|(address, _)| address).collect(),
+            pagination: None,
+        })
+    }
+}
+
+impl CosmosSdk {
+    /// Set the [`Metadata`] of `metadata.base`, overriding the default one of `ibc/{hash}` vouchers.
+    pub fn set_denom_metadata(storage: &mut dyn Storage, metadata: Metadata) -> AppResult<()> {
+        DENOM_METADATA.save(
+            storage,
+            &metadata.base,
+            &Binary::from(metadata.encode_to_vec()),
+        )?;
+
+        Ok(())
+    }
+
+    /// Load the [`Metadata`] of a denom, if set or if the denom is an `ibc/{hash}` voucher.
+    pub fn load_denom_metadata(storage: &dyn Storage, denom: &str) -> AppResult<Option<Metadata>> {
+        if let Some(data) = DENOM_METADATA.may_load(storage, denom)? {
+            return Ok(Some(Metadata::decode(data.as_slice())?));
+        }
+
+        Ok(Ics20Helper::incoming_vouchers(storage)?
+            .get(denom)
+            .map(|trace| voucher_metadata(denom, trace)))
+    }
+}
+
+/// Default metadata of an `ibc/{hash}` voucher, as created by `ibc-go` on receive.
+fn voucher_metadata(denom: &str, trace: &str) -> Metadata {
+    let denom_trace = Ics20Helper::denom_trace(trace);
+
+    Metadata {
+        description: format!("IBC token from {}", trace),
+        denom_units: vec![DenomUnit {
+            denom: denom_trace.base_denom.clone(),
+            exponent: 0,
+            aliases: vec![],
+        }],
+        base: denom.to_string(),
+        display: trace.to_string(),
+        name: format!("{} IBC token", trace),
+        symbol: denom_trace.base_denom.to_uppercase(),
+        uri: String::new(),
+        uri_hash: String::new(),
+    }
+}
+
+/// Balances saved by the `BankKeeper`,
+/// a `Map<&Addr, NativeBalance>` named `balances` under the `bank` namespace.
+fn load_bank_balances(storage: &dyn Storage, address: &Addr) -> AppResult<Vec<Coin>> {
+    let key = [
+        to_length_prefixed(b"bank"),
+        to_length_prefixed(b"balances"),
+        address.as_bytes().to_vec(),
+    ]
+    .concat();
+
+    Ok(storage
+        .get(&key)
+        .map(from_json)
+        .transpose()?
+        .unwrap_or_default())
+}
+
+/// Contracts saved by the `WasmKeeper`,
+/// a `Map<&Addr, ContractData>` named `contracts` under the `wasm` namespace.
+fn load_wasm_contracts(storage: &dyn Storage) -> AppResult<Vec<(String, ContractData)>> {
+    let prefix = [
+        to_length_prefixed(b"wasm"),
+        to_length_prefixed(b"contracts"),
+    ]
+    .concat();
+    let mut end = prefix.clone();
+    *end.last_mut().unwrap() += 1;
+
+    storage
+        .range(Some(&prefix), Some(&end), Order::Ascending)
+        .map(|(key, value)| {
+            Ok((
+                String::from_utf8(key[prefix.len()..].to_vec())?,
+                from_json(value)?,
+            ))
+        })
+        .collect()
+}
+
+fn proto_contract_info(contract: ContractInfoResponse) -> ContractInfo {
+    ContractInfo {
+        code_id: contract.code_id,
+        creator: contract.creator.to_string(),
+        admin: contract
+            .admin
+            .map(|admin| admin.to_string())
+            .unwrap_or_default(),
+        label: String::new(),
+        created: None,
+        ibc_port_id: contract.ibc_port.unwrap_or_default(),
+    }
+}
+
+fn ensure_signer<T: Name>(sender: &Addr, signer: &str) -> AppResult<()> {
+    if sender.as_str() != signer {
+        return Err(IperError::InvalidSigner {
+            type_url: T::type_url(),
+            sender: sender.to_string(),
+            signer: signer.to_string(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+fn into_coin(coin: ProtoCoin) -> AppResult<Coin> {
+    Ok(Coin::new(Uint128::from_str(&coin.amount)?, coin.denom))
+}
+
+fn into_coins(coins: Vec<ProtoCoin>) -> AppResult<Vec<Coin>> {
+    coins.into_iter().map(into_coin).collect()
+}
+
+fn from_coin(coin: Coin) -> ProtoCoin {
+    ProtoCoin {
+        denom: coin.denom,
+        amount: coin.amount.to_string(),
+    }
+}
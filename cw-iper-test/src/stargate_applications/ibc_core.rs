@@ -0,0 +1,316 @@
+use cosmwasm_std::{IbcOrder, Order, StdResult};
+use cw_iper_test_macros::stargate_application;
+use ibc_proto::ibc::core::{
+    channel::v1::{
+        Channel, Counterparty, IdentifiedChannel, Order as ChannelOrder, PacketState,
+        QueryChannelRequest, QueryChannelResponse, QueryChannelsRequest, QueryChannelsResponse,
+        QueryConnectionChannelsRequest, QueryConnectionChannelsResponse,
+        QueryNextSequenceReceiveRequest, QueryNextSequenceReceiveResponse,
+        QueryPacketCommitmentRequest, QueryPacketCommitmentResponse, QueryPacketCommitmentsRequest,
+        QueryPacketCommitmentsResponse, QueryUnreceivedPacketsRequest,
+        QueryUnreceivedPacketsResponse, State as ChannelState,
+    },
+    client::v1::Height,
+    commitment::v1::MerklePrefix,
+    connection::v1::{
+        ConnectionEnd as ProtoConnectionEnd, Counterparty as ConnectionCounterparty,
+        QueryConnectionRequest, QueryConnectionResponse, State as ConnectionProtoState, Version,
+    },
+};
+
+use crate::{
+    chain_helper::ChainHelper,
+    connection::{ConnectionState, IBC_CONNECTIONS},
+    error::{AppResult, IperError},
+    ibc::{IbcChannelStatus, IbcChannelWrapper},
+    ibc_module::{PACKET_COMMITMENTS, PACKET_RECEIPTS},
+    iper_app::SharedChannels,
+    stargate::StargateQueryCtx,
+};
+
+/// [`StargateApplication`](crate::StargateApplication) serving the `ibc core` gRPC queries
+/// from the `channels`, `connections` and `packet` commitments of the [`IperApp`](crate::IperApp).
+///
+/// Registered with [`AppBuilderIperExt::with_ibc_core`](crate::AppBuilderIperExt::with_ibc_core).
+///
+/// Proofs are always empty and pagination is ignored, all the results are returned in a single page.
+pub(crate) struct IbcCore {
+    channels: SharedChannels,
+}
+
+impl IbcCore {
+    pub(crate) fn new(channels: SharedChannels) -> Self {
+        Self { channels }
+    }
+
+    fn load_channel(&self, port_id: &str, channel_id: &str) -> AppResult<IbcChannelWrapper> {
+        let channels = self.channels.borrow();
+        let channel = channels.get(channel_id)?;
+
+        if channel.local.port.port_name() != port_id {
+            return Err(IperError::ChannelNotFound {
+                chain_id: channels.chain_id.clone(),
+                channel: format!("{}/{}", port_id, channel_id),
+            }
+            .into());
+        }
+
+        Ok(channel.clone())
+    }
+
+    fn identified_channels(
+        &self,
+        filter: impl Fn(&IbcChannelWrapper) -> bool,
+    ) -> AppResult<Vec<IdentifiedChannel>> {
+        self.channels
+            .borrow()
+            .iter()
+            .filter(|channel| filter(channel))
+            .map(identified_channel)
+            .collect()
+    }
+}
+
+#[stargate_application(name = "ibc_core")]
+impl IbcCore {
+    #[query("/ibc.core.channel.v1.Query/Channel")]
+    fn channel(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryChannelRequest,
+    ) -> AppResult<QueryChannelResponse> {
+        let channel = self.load_channel(&request.port_id, &request.channel_id)?;
+
+        Ok(QueryChannelResponse {
+            channel: Some(proto_channel(&channel)?),
+            proof: vec![],
+            proof_height: Some(query_height(&ctx)?),
+        })
+    }
+
+    #[query("/ibc.core.channel.v1.Query/Channels")]
+    fn channels(
+        &self,
+        ctx: StargateQueryCtx,
+        _request: QueryChannelsRequest,
+    ) -> AppResult<QueryChannelsResponse> {
+        Ok(QueryChannelsResponse {
+            channels: self.identified_channels(|_| true)?,
+            pagination: None,
+            height: Some(query_height(&ctx)?),
+        })
+    }
+
+    #[query("/ibc.core.channel.v1.Query/ConnectionChannels")]
+    fn connection_channels(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryConnectionChannelsRequest,
+    ) -> AppResult<QueryConnectionChannelsResponse> {
+        Ok(QueryConnectionChannelsResponse {
+            channels: self
+                .identified_channels(|channel| channel.local.connection_id == request.connection)?,
+            pagination: None,
+            height: Some(query_height(&ctx)?),
+        })
+    }
+
+    #[query("/ibc.core.channel.v1.Query/PacketCommitment")]
+    fn packet_commitment(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryPacketCommitmentRequest,
+    ) -> AppResult<QueryPacketCommitmentResponse> {
+        let commitment = PACKET_COMMITMENTS
+            .may_load(
+                ctx.storage,
+                (
+                    request.port_id.as_str(),
+                    request.channel_id.as_str(),
+                    request.sequence,
+                ),
+            )?
+            .ok_or(IperError::PacketCommitmentNotFound {
+                port: request.port_id,
+                channel: request.channel_id,
+                sequence: request.sequence,
+            })?;
+
+        Ok(QueryPacketCommitmentResponse {
+            commitment: commitment.to_vec(),
+            proof: vec![],
+            proof_height: Some(query_height(&ctx)?),
+        })
+    }
+
+    #[query("/ibc.core.channel.v1.Query/PacketCommitments")]
+    fn packet_commitments(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryPacketCommitmentsRequest,
+    ) -> AppResult<QueryPacketCommitmentsResponse> {
+        self.load_channel(&request.port_id, &request.channel_id)?;
+
+        let commitments = PACKET_COMMITMENTS
+            .prefix((request.port_id.as_str(), request.channel_id.as_str()))
+            .range(ctx.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(sequence, commitment)| PacketState {
+                    port_id: request.port_id.clone(),
+                    channel_id: request.channel_id.clone(),
+                    sequence,
+                    data: commitment.to_vec(),
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(QueryPacketCommitmentsResponse {
+            commitments,
+            pagination: None,
+            height: Some(query_height(&ctx)?),
+        })
+    }
+
+    #[query("/ibc.core.channel.v1.Query/UnreceivedPackets")]
+    fn unreceived_packets(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryUnreceivedPacketsRequest,
+    ) -> AppResult<QueryUnreceivedPacketsResponse> {
+        self.load_channel(&request.port_id, &request.channel_id)?;
+
+        let sequences = request
+            .packet_commitment_sequences
+            .into_iter()
+            .filter(|sequence| {
+                !PACKET_RECEIPTS.has(
+                    ctx.storage,
+                    (
+                        request.port_id.as_str(),
+                        request.channel_id.as_str(),
+                        *sequence,
+                    ),
+                )
+            })
+            .collect();
+
+        Ok(QueryUnreceivedPacketsResponse {
+            sequences,
+            height: Some(query_height(&ctx)?),
+        })
+    }
+
+    #[query("/ibc.core.channel.v1.Query/NextSequenceReceive")]
+    fn next_sequence_receive(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryNextSequenceReceiveRequest,
+    ) -> AppResult<QueryNextSequenceReceiveResponse> {
+        self.load_channel(&request.port_id, &request.channel_id)?;
+
+        let last_received = PACKET_RECEIPTS
+            .prefix((request.port_id.as_str(), request.channel_id.as_str()))
+            .keys(ctx.storage, None, None, Order::Descending)
+            .next()
+            .transpose()?;
+
+        Ok(QueryNextSequenceReceiveResponse {
+            next_sequence_receive: last_received.unwrap_or_default() + 1,
+            proof: vec![],
+            proof_height: Some(query_height(&ctx)?),
+        })
+    }
+
+    #[query("/ibc.core.connection.v1.Query/Connection")]
+    fn connection(
+        &self,
+        ctx: StargateQueryCtx,
+        request: QueryConnectionRequest,
+    ) -> AppResult<QueryConnectionResponse> {
+        let connection = IBC_CONNECTIONS
+            .may_load(ctx.storage)?
+            .unwrap_or_default()
+            .remove(&request.connection_id)
+            .ok_or_else(|| IperError::ConnectionNotFound {
+                chain_id: self.channels.borrow().chain_id.clone(),
+                connection_id: request.connection_id.clone(),
+            })?;
+
+        let state = match connection.state {
+            ConnectionState::Init => ConnectionProtoState::Init,
+            ConnectionState::TryOpen => ConnectionProtoState::Tryopen,
+            ConnectionState::Open => ConnectionProtoState::Open,
+        };
+
+        Ok(QueryConnectionResponse {
+            connection: Some(ProtoConnectionEnd {
+                client_id: connection.client_id,
+                versions: vec![Version {
+                    identifier: "1".to_string(),
+                    features: vec!["ORDER_ORDERED".to_string(), "ORDER_UNORDERED".to_string()],
+                }],
+                state: state as i32,
+                counterparty: Some(ConnectionCounterparty {
+                    client_id: connection.counterparty.client_id,
+                    connection_id: connection.counterparty.connection_id.unwrap_or_default(),
+                    prefix: Some(MerklePrefix {
+                        key_prefix: b"ibc".to_vec(),
+                    }),
+                }),
+                delay_period: 0,
+            }),
+            proof: vec![],
+            proof_height: Some(query_height(&ctx)?),
+        })
+    }
+}
+
+/// Height of the chain at the time of the query.
+fn query_height(ctx: &StargateQueryCtx) -> AppResult<Height> {
+    Ok(Height {
+        revision_number: ChainHelper::load(ctx.storage)?.revision_number,
+        revision_height: ctx.block.height,
+    })
+}
+
+fn proto_channel(channel: &IbcChannelWrapper) -> AppResult<Channel> {
+    let state = match channel.status {
+        IbcChannelStatus::Created => ChannelState::Init,
+        IbcChannelStatus::Opening => ChannelState::Tryopen,
+        IbcChannelStatus::Connected => ChannelState::Open,
+        IbcChannelStatus::Closed => ChannelState::Closed,
+    };
+
+    let ordering = match channel.local.order {
+        IbcOrder::Unordered => ChannelOrder::Unordered,
+        IbcOrder::Ordered => ChannelOrder::Ordered,
+    };
+
+    Ok(Channel {
+        state: state as i32,
+        ordering: ordering as i32,
+        counterparty: Some(Counterparty {
+            port_id: channel.remote.port.port_name(),
+            channel_id: channel.remote.as_endpoint()?.channel_id,
+        }),
+        connection_hops: vec![channel.local.connection_id.clone()],
+        version: channel.local.version.clone(),
+        upgrade_sequence: 0,
+    })
+}
+
+fn identified_channel(channel: &IbcChannelWrapper) -> AppResult<IdentifiedChannel> {
+    let endpoint = channel.local.as_endpoint()?;
+    let proto = proto_channel(channel)?;
+
+    Ok(IdentifiedChannel {
+        state: proto.state,
+        ordering: proto.ordering,
+        counterparty: proto.counterparty,
+        connection_hops: proto.connection_hops,
+        version: proto.version,
+        port_id: endpoint.port_id,
+        channel_id: endpoint.channel_id,
+        upgrade_sequence: proto.upgrade_sequence,
+    })
+}
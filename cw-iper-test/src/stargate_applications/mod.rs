@@ -0,0 +1,13 @@
+//! ### Default [`StargateApplications`](crate::stargate::StargateApplication)
+//! - [`CosmosSdk`];
+//!
+//! The `ibc core` gRPC queries (`/ibc.core.channel.v1.Query/*` and `/ibc.core.connection.v1.Query/Connection`)
+//! are served on an [`IperApp`](crate::IperApp) built with [`AppBuilderIperExt::with_ibc_core`](crate::AppBuilderIperExt::with_ibc_core).
+
+mod cosmos_sdk;
+mod ibc_core;
+pub mod wasm_v1;
+
+pub use cosmos_sdk::CosmosSdk;
+
+pub(crate) use ibc_core::IbcCore;
@@ -0,0 +1,297 @@
+//! Protobuf types of the `cosmwasm.wasm.v1` package, not included in `ibc-proto`.
+
+use ibc_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+use prost::Name;
+
+/// MsgExecuteContract submits the given message data to a smart contract.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgExecuteContract {
+    /// Sender is the that actor that signed the messages.
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    /// Contract is the address of the smart contract.
+    #[prost(string, tag = "2")]
+    pub contract: String,
+    /// Msg json encoded message to be passed to the contract.
+    #[prost(bytes = "vec", tag = "3")]
+    pub msg: Vec<u8>,
+    /// Funds coins that are transferred to the contract on execution.
+    #[prost(message, repeated, tag = "5")]
+    pub funds: Vec<ibc_proto::cosmos::base::v1beta1::Coin>,
+}
+
+impl Name for MsgExecuteContract {
+    const NAME: &'static str = "MsgExecuteContract";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// MsgExecuteContractResponse returns execution result data.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgExecuteContractResponse {
+    /// Data contains bytes to returned from the contract.
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+}
+
+impl Name for MsgExecuteContractResponse {
+    const NAME: &'static str = "MsgExecuteContractResponse";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// MsgInstantiateContract create a new smart contract instance for the given code id.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgInstantiateContract {
+    /// Sender is the that actor that signed the messages.
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    /// Admin is an optional address that can execute migrations.
+    #[prost(string, tag = "2")]
+    pub admin: String,
+    /// CodeID is the reference to the stored WASM code.
+    #[prost(uint64, tag = "3")]
+    pub code_id: u64,
+    /// Label is optional metadata to be stored with a contract instance.
+    #[prost(string, tag = "4")]
+    pub label: String,
+    /// Msg json encoded message to be passed to the contract on instantiation.
+    #[prost(bytes = "vec", tag = "5")]
+    pub msg: Vec<u8>,
+    /// Funds coins that are transferred to the contract on instantiation.
+    #[prost(message, repeated, tag = "6")]
+    pub funds: Vec<ibc_proto::cosmos::base::v1beta1::Coin>,
+}
+
+impl Name for MsgInstantiateContract {
+    const NAME: &'static str = "MsgInstantiateContract";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// MsgInstantiateContractResponse return instantiation result data.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgInstantiateContractResponse {
+    /// Address is the bech32 address of the new contract instance.
+    #[prost(string, tag = "1")]
+    pub address: String,
+    /// Data contains bytes to returned from the contract.
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: Vec<u8>,
+}
+
+impl Name for MsgInstantiateContractResponse {
+    const NAME: &'static str = "MsgInstantiateContractResponse";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// AbsoluteTxPosition is a unique transaction position that allows for global ordering of transactions.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AbsoluteTxPosition {
+    /// BlockHeight is the block the contract was created at.
+    #[prost(uint64, tag = "1")]
+    pub block_height: u64,
+    /// TxIndex is a monotonic counter within the block (actual transaction index, or gas consumed).
+    #[prost(uint64, tag = "2")]
+    pub tx_index: u64,
+}
+
+impl Name for AbsoluteTxPosition {
+    const NAME: &'static str = "AbsoluteTxPosition";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// ContractInfo stores a WASM contract instance.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ContractInfo {
+    /// CodeID is the reference to the stored Wasm code.
+    #[prost(uint64, tag = "1")]
+    pub code_id: u64,
+    /// Creator address who initially instantiated the contract.
+    #[prost(string, tag = "2")]
+    pub creator: String,
+    /// Admin is an optional address that can execute migrations.
+    #[prost(string, tag = "3")]
+    pub admin: String,
+    /// Label is optional metadata to be stored with a contract instance.
+    #[prost(string, tag = "4")]
+    pub label: String,
+    /// Created Tx position when the contract was instantiated.
+    #[prost(message, optional, tag = "5")]
+    pub created: Option<AbsoluteTxPosition>,
+    /// IBCPortID is the port of the contract, if it has ibc entry points.
+    #[prost(string, tag = "6")]
+    pub ibc_port_id: String,
+}
+
+impl Name for ContractInfo {
+    const NAME: &'static str = "ContractInfo";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QueryContractInfoRequest is the request type for the Query/ContractInfo RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryContractInfoRequest {
+    /// address is the address of the contract to query.
+    #[prost(string, tag = "1")]
+    pub address: String,
+}
+
+impl Name for QueryContractInfoRequest {
+    const NAME: &'static str = "QueryContractInfoRequest";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QueryContractInfoResponse is the response type for the Query/ContractInfo RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryContractInfoResponse {
+    /// address is the address of the contract.
+    #[prost(string, tag = "1")]
+    pub address: String,
+    /// Info of the contract.
+    #[prost(message, optional, tag = "2")]
+    pub contract_info: Option<ContractInfo>,
+}
+
+impl Name for QueryContractInfoResponse {
+    const NAME: &'static str = "QueryContractInfoResponse";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QuerySmartContractStateRequest is the request type for the Query/SmartContractState RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QuerySmartContractStateRequest {
+    /// address is the address of the contract.
+    #[prost(string, tag = "1")]
+    pub address: String,
+    /// QueryData contains the query data passed to the contract.
+    #[prost(bytes = "vec", tag = "2")]
+    pub query_data: Vec<u8>,
+}
+
+impl Name for QuerySmartContractStateRequest {
+    const NAME: &'static str = "QuerySmartContractStateRequest";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QuerySmartContractStateResponse is the response type for the Query/SmartContractState RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QuerySmartContractStateResponse {
+    /// Data contains the json data returned from the smart contract.
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+}
+
+impl Name for QuerySmartContractStateResponse {
+    const NAME: &'static str = "QuerySmartContractStateResponse";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QueryRawContractStateRequest is the request type for the Query/RawContractState RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryRawContractStateRequest {
+    /// address is the address of the contract.
+    #[prost(string, tag = "1")]
+    pub address: String,
+    /// QueryData is the raw key of the contract storage.
+    #[prost(bytes = "vec", tag = "2")]
+    pub query_data: Vec<u8>,
+}
+
+impl Name for QueryRawContractStateRequest {
+    const NAME: &'static str = "QueryRawContractStateRequest";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QueryRawContractStateResponse is the response type for the Query/RawContractState RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryRawContractStateResponse {
+    /// Data contains the raw store data.
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+}
+
+impl Name for QueryRawContractStateResponse {
+    const NAME: &'static str = "QueryRawContractStateResponse";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// AccessConfig access control type.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AccessConfig {
+    /// Permission, as `AccessType` (`3` is `ACCESS_TYPE_EVERYBODY`).
+    #[prost(int32, tag = "1")]
+    pub permission: i32,
+    /// Addresses allowed, for `ACCESS_TYPE_ANY_OF_ADDRESSES`.
+    #[prost(string, repeated, tag = "3")]
+    pub addresses: Vec<String>,
+}
+
+impl Name for AccessConfig {
+    const NAME: &'static str = "AccessConfig";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QueryCodeInfoRequest is the request type for the Query/CodeInfo RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryCodeInfoRequest {
+    /// CodeID is the reference to the stored Wasm code.
+    #[prost(uint64, tag = "1")]
+    pub code_id: u64,
+}
+
+impl Name for QueryCodeInfoRequest {
+    const NAME: &'static str = "QueryCodeInfoRequest";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QueryCodeInfoResponse is the response type for the Query/CodeInfo RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryCodeInfoResponse {
+    /// CodeID is the reference to the stored Wasm code.
+    #[prost(uint64, tag = "1")]
+    pub code_id: u64,
+    /// Creator address who stored the code.
+    #[prost(string, tag = "2")]
+    pub creator: String,
+    /// Checksum of the Wasm code.
+    #[prost(bytes = "vec", tag = "3")]
+    pub checksum: Vec<u8>,
+    /// InstantiatePermission of the code.
+    #[prost(message, optional, tag = "4")]
+    pub instantiate_permission: Option<AccessConfig>,
+}
+
+impl Name for QueryCodeInfoResponse {
+    const NAME: &'static str = "QueryCodeInfoResponse";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QueryContractsByCodeRequest is the request type for the Query/ContractsByCode RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryContractsByCodeRequest {
+    /// CodeID is the reference to the stored Wasm code.
+    #[prost(uint64, tag = "1")]
+    pub code_id: u64,
+    /// pagination defines an optional pagination for the request.
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageRequest>,
+}
+
+impl Name for QueryContractsByCodeRequest {
+    const NAME: &'static str = "QueryContractsByCodeRequest";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
+
+/// QueryContractsByCodeResponse is the response type for the Query/ContractsByCode RPC method.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryContractsByCodeResponse {
+    /// contracts are a set of contract addresses.
+    #[prost(string, repeated, tag = "1")]
+    pub contracts: Vec<String>,
+    /// pagination defines the pagination in the response.
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageResponse>,
+}
+
+impl Name for QueryContractsByCodeResponse {
+    const NAME: &'static str = "QueryContractsByCodeResponse";
+    const PACKAGE: &'static str = "cosmwasm.wasm.v1";
+}
@@ -0,0 +1,95 @@
+use std::{
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    sync::mpsc::{channel, sync_channel, Sender},
+    thread,
+};
+
+use crate::{
+    ecosystem::Ecosystem,
+    error::{AppResult, IperError},
+};
+
+type Job<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+/// `Send + Sync` handle of an [`Ecosystem`], usable from multiple threads and `async` test harnesses.
+///
+/// [`App`](cw_multi_test::App) stores `contracts` as non-`Send` trait objects, so an [`Ecosystem`] can't leave the thread that created it.
+/// [`SyncEcosystem`] creates the state on a dedicated thread and runs there the closures passed to [`SyncEcosystem::with`],
+/// one at a time in the order they are received.
+///
+/// The state `T` is any value built by the `setup` closure, like an [`Ecosystem`] or a [`SpecEcosystem`](crate::SpecEcosystem):
+/// ```ignore
+/// let eco = SyncEcosystem::new(|| {
+///     let registry = SpecRegistry::default().with_ibc_app("ics20", || Ics20);
+///     EcosystemSpec::load("spec.json").unwrap().build(&registry).unwrap()
+/// });
+///
+/// let handle = eco.clone();
+///
+/// tokio::task::spawn_blocking(move || {
+///     handle.with(|env| env.eco.relay_all_packets()).unwrap()
+/// })
+/// .await?;
+/// ```
+///
+/// [`SyncEcosystem::with`] blocks the calling thread until the closure has run:
+/// inside an `async` runtime, call it from a blocking task (like `tokio::task::spawn_blocking`) to avoid stalling the executor.
+///
+/// The thread stops when all the handles are dropped.
+pub struct SyncEcosystem<T: 'static = Ecosystem> {
+    sender: Sender<Job<T>>,
+}
+
+impl<T: 'static> Clone for SyncEcosystem<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T: 'static> SyncEcosystem<T> {
+    /// Spawn the thread and build the state with `setup`.
+    pub fn new(setup: impl FnOnce() -> T + Send + 'static) -> Self {
+        let (sender, receiver) = channel::<Job<T>>();
+
+        thread::Builder::new()
+            .name("cw-iper-test-ecosystem".to_string())
+            .spawn(move || {
+                let mut state = setup();
+
+                for job in receiver {
+                    job(&mut state);
+                }
+            })
+            .expect("failed to spawn the ecosystem thread");
+
+        Self { sender }
+    }
+
+    /// Run `f` on the state, blocking the calling thread until the result is ready.
+    ///
+    /// Panics raised inside `f` (like failed assertions) are resumed on the calling thread,
+    /// so the state stays usable by the other handles.
+    pub fn with<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut T) -> R + Send + 'static,
+    ) -> AppResult<R> {
+        let (result_sender, result_receiver) = sync_channel(1);
+
+        self.sender
+            .send(Box::new(move |state| {
+                let result = catch_unwind(AssertUnwindSafe(|| f(state)));
+                let _ = result_sender.send(result);
+            }))
+            .map_err(|_| IperError::EcosystemStopped)?;
+
+        match result_receiver
+            .recv()
+            .map_err(|_| IperError::EcosystemStopped)?
+        {
+            Ok(result) => Ok(result),
+            Err(panic) => resume_unwind(panic),
+        }
+    }
+}
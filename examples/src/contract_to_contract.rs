@@ -1,11 +1,18 @@
-use cosmwasm_std::{to_json_binary, IbcMsg, IbcOrder, IbcTimeout, Timestamp};
+use cosmwasm_std::{
+    to_json_binary, DepsMut, Empty, Env, IbcMsg, IbcOrder, IbcPacketReceiveMsg, IbcReceiveResponse,
+    IbcTimeout, Never, StdAck, Timestamp,
+};
 use cw_iper_test::{
+    assert_iper_err,
     cw_multi_test::{no_init, AppBuilder, ContractWrapper, Executor, MockApiBech32},
-    AppExt, ContractWrapperExt, Ecosystem, IbcChannelCreator, IbcClosures, IbcPort, IperAppBuilder,
-    IperIbcModule, IperStargateModule, IperContract,
+    ibc_entry_points, AppExt, ContractWrapperExt, Ecosystem, EcosystemFixture, IbcClosures,
+    IbcPort, IperAppBuilder, IperContract, IperError, IperIbcModule, IperStargateModule,
+    PacketStatus, RelayEventKind,
 };
 
-use crate::mock_contracts::counter::{self, CounterConfig, CounterPacketData, CounterQueryMsg};
+use crate::mock_contracts::counter::{
+    self, CounterAckData, CounterConfig, CounterPacketData, CounterQueryMsg,
+};
 
 #[test]
 fn contract_to_contract() {
@@ -14,11 +21,13 @@ fn contract_to_contract() {
         .with_ibc(IperIbcModule::default())
         .with_stargate(IperStargateModule::default())
         .build(no_init)
-        .into_iper_app("neutron");
+        .into_iper_app("neutron")
+        .unwrap();
 
     let osmosis = IperAppBuilder::new("osmo")
         .build(no_init)
-        .into_iper_app("osmosis");
+        .into_iper_app("osmosis")
+        .unwrap();
 
     let eco = Ecosystem::default()
         .add_app(neutron.clone())
@@ -81,21 +90,14 @@ fn contract_to_contract() {
         )
         .unwrap();
 
-    eco.open_ibc_channel(
-        IbcChannelCreator::new(
-            IbcPort::Contract(neutron_addr.clone()),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "neutron",
-        ),
-        IbcChannelCreator::new(
-            IbcPort::Contract(osmosis_addr.clone()),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "osmosis",
-        ),
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::Contract(neutron_addr.clone()),
+        IbcPort::Contract(osmosis_addr.clone()),
+        IbcOrder::Unordered,
+        "version",
     )
     .unwrap();
 
@@ -118,6 +120,176 @@ fn contract_to_contract() {
         )
         .unwrap();
 
+    let report = eco.relay_all_packets().unwrap();
+
+    assert_eq!(report.len(), 2);
+    assert_eq!(report.relayed[0].kind, RelayEventKind::Receive);
+    assert_eq!(report.relayed[0].dest_chain_id, "osmosis");
+    assert_eq!(report.relayed[1].kind, RelayEventKind::Ack);
+    assert_eq!(report.relayed[1].src_chain_id, "neutron");
+    assert_eq!(
+        report.relayed[1].ack,
+        Some(to_json_binary(&CounterAckData::Ok).unwrap())
+    );
+    assert_eq!(report.find("neutron", "channel-0", 1).len(), 2);
+
+    assert_eq!(
+        eco.relay_next_packet("osmosis")
+            .unwrap_err()
+            .downcast_ref::<IperError>(),
+        Some(&IperError::NoPendingPackets {
+            chain_id: "osmosis".to_string()
+        })
+    );
+
+    let counter_src_ack_ok = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_wasm_smart::<CounterConfig>(&neutron_addr, &CounterQueryMsg::Config)
+        .unwrap()
+        .counter_packet_ack_ok;
+
+    assert_eq!(counter_src_ack_ok, 1);
+
+    let counter_receive_dest = osmosis
+        .borrow()
+        .app
+        .wrap()
+        .query_wasm_smart::<CounterConfig>(&osmosis_addr, &CounterQueryMsg::Config)
+        .unwrap()
+        .counter_packet_receive;
+
+    assert_eq!(counter_receive_dest, 1);
+}
+
+fn counter_contract() -> IperContract<Empty> {
+    ibc_entry_points!(crate::mock_contracts::counter)
+}
+
+#[test]
+fn contract_to_contract_fixture() {
+    // Build the setup and export it
+    let (json, neutron_addr, osmosis_addr) = {
+        let neutron = IperAppBuilder::new("neutron")
+            .build(no_init)
+            .into_iper_app("neutron")
+            .unwrap();
+
+        let osmosis = IperAppBuilder::new("osmo")
+            .build(no_init)
+            .into_iper_app("osmosis")
+            .unwrap();
+
+        let eco = Ecosystem::default()
+            .add_app(neutron.clone())
+            .add_app(osmosis.clone());
+
+        let code_id_neutron = neutron
+            .borrow_mut()
+            .store_named_ibc_code("counter", counter_contract());
+
+        let code_id_osmosis = osmosis
+            .borrow_mut()
+            .store_named_ibc_code("counter", counter_contract());
+
+        let neutron_owner = neutron.borrow().app.api().addr_make("owner");
+        let osmosis_owner = osmosis.borrow().app.api().addr_make("owner");
+
+        let neutron_addr = neutron
+            .borrow_mut()
+            .app
+            .instantiate_contract(
+                code_id_neutron,
+                neutron_owner.clone(),
+                &counter::InstantiateMsg {},
+                &[],
+                "label".to_string(),
+                None,
+            )
+            .unwrap();
+
+        let osmosis_addr = osmosis
+            .borrow_mut()
+            .app
+            .instantiate_contract(
+                code_id_osmosis,
+                osmosis_owner,
+                &counter::InstantiateMsg {},
+                &[],
+                "label".to_string(),
+                None,
+            )
+            .unwrap();
+
+        let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+        eco.open_ibc_channel_on(
+            &connection,
+            IbcPort::Contract(neutron_addr.clone()),
+            IbcPort::Contract(osmosis_addr.clone()),
+            IbcOrder::Unordered,
+            "version",
+        )
+        .unwrap();
+
+        let msg = IbcMsg::SendPacket {
+            channel_id: "channel-0".to_string(),
+            data: to_json_binary(&CounterPacketData::Ok).unwrap(),
+            timeout: IbcTimeout::with_timestamp(Timestamp::from_seconds(
+                osmosis.borrow().app.block_info().time.seconds() + 1,
+            )),
+        };
+
+        neutron
+            .borrow_mut()
+            .app
+            .execute_contract(
+                neutron_owner,
+                neutron_addr.clone(),
+                &counter::ExecuteMsg::SendPacket(msg),
+                &[],
+            )
+            .unwrap();
+
+        let json = eco.export_fixture().unwrap().to_json().unwrap();
+
+        (json, neutron_addr, osmosis_addr)
+    };
+
+    // Load the fixture on new chains
+    let fixture = EcosystemFixture::from_json(&json).unwrap();
+
+    let neutron = IperAppBuilder::new("neutron")
+        .build(no_init)
+        .into_iper_app("neutron")
+        .unwrap();
+
+    let osmosis = IperAppBuilder::new("osmo")
+        .build(no_init)
+        .into_iper_app("osmosis")
+        .unwrap();
+
+    for app in [&neutron, &osmosis] {
+        app.borrow_mut()
+            .store_fixture_codes(&fixture, |name| match name {
+                "counter" => Some(counter_contract()),
+                _ => None,
+            })
+            .unwrap();
+    }
+
+    let eco = Ecosystem::default()
+        .add_app(neutron.clone())
+        .add_app(osmosis.clone());
+
+    eco.load_fixture(&fixture).unwrap();
+
+    assert_eq!(
+        eco.packet_status("neutron", "channel-0", 1).unwrap(),
+        PacketStatus::Pending
+    );
+
     eco.relay_all_packets().unwrap();
 
     let counter_src_ack_ok = neutron
@@ -140,3 +312,206 @@ fn contract_to_contract() {
 
     assert_eq!(counter_receive_dest, 1);
 }
+
+#[test]
+fn contract_to_contract_ping_pong() {
+    let neutron = IperAppBuilder::new("neutron")
+        .build(no_init)
+        .into_iper_app("neutron")
+        .unwrap();
+
+    let osmosis = IperAppBuilder::new("osmo")
+        .build(no_init)
+        .into_iper_app("osmosis")
+        .unwrap();
+
+    let eco = Ecosystem::default()
+        .add_app(neutron.clone())
+        .add_app(osmosis.clone())
+        .with_max_relay_iterations(10);
+
+    let code_id_neutron = neutron.borrow_mut().store_ibc_code(counter_contract());
+    let code_id_osmosis = osmosis.borrow_mut().store_ibc_code(counter_contract());
+
+    let neutron_owner = neutron.borrow().app.api().addr_make("owner");
+    let osmosis_owner = osmosis.borrow().app.api().addr_make("owner");
+
+    let neutron_addr = neutron
+        .borrow_mut()
+        .app
+        .instantiate_contract(
+            code_id_neutron,
+            neutron_owner.clone(),
+            &counter::InstantiateMsg {},
+            &[],
+            "label".to_string(),
+            None,
+        )
+        .unwrap();
+
+    let osmosis_addr = osmosis
+        .borrow_mut()
+        .app
+        .instantiate_contract(
+            code_id_osmosis,
+            osmosis_owner,
+            &counter::InstantiateMsg {},
+            &[],
+            "label".to_string(),
+            None,
+        )
+        .unwrap();
+
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::Contract(neutron_addr.clone()),
+        IbcPort::Contract(osmosis_addr),
+        IbcOrder::Unordered,
+        "version",
+    )
+    .unwrap();
+
+    let msg = IbcMsg::SendPacket {
+        channel_id: "channel-0".to_string(),
+        data: to_json_binary(&CounterPacketData::Ping).unwrap(),
+        timeout: IbcTimeout::with_timestamp(Timestamp::from_seconds(
+            osmosis.borrow().app.block_info().time.seconds() + 1,
+        )),
+    };
+
+    neutron
+        .borrow_mut()
+        .app
+        .execute_contract(
+            neutron_owner,
+            neutron_addr,
+            &counter::ExecuteMsg::SendPacket(msg),
+            &[],
+        )
+        .unwrap();
+
+    let err = eco.relay_all_packets().unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("Max relay iterations (10) reached"));
+
+    assert_iper_err!(err, IperError::MaxRelayIterations { max: 10, .. });
+}
+
+/// `contract` rejecting every `packet` with a [`StdAck::Error`].
+mod rejecting {
+    use super::*;
+
+    pub fn ibc_packet_receive(
+        _deps: DepsMut,
+        _env: Env,
+        msg: IbcPacketReceiveMsg,
+    ) -> Result<IbcReceiveResponse, Never> {
+        Ok(IbcReceiveResponse::new(StdAck::error(format!(
+            "rejected packet {}",
+            msg.packet.sequence
+        ))))
+    }
+}
+
+#[test]
+fn contract_to_contract_error_ack() {
+    let neutron = IperAppBuilder::new("neutron")
+        .build(no_init)
+        .into_iper_app("neutron")
+        .unwrap();
+
+    let osmosis = IperAppBuilder::new("osmo")
+        .build(no_init)
+        .into_iper_app("osmosis")
+        .unwrap();
+
+    let eco = Ecosystem::default()
+        .add_app(neutron.clone())
+        .add_app(osmosis.clone());
+
+    let code_id_neutron = neutron.borrow_mut().store_ibc_code(counter_contract());
+    let code_id_osmosis = osmosis
+        .borrow_mut()
+        .store_ibc_code(ibc_entry_points!(crate::contract_to_contract::rejecting));
+
+    let neutron_owner = neutron.borrow().app.api().addr_make("owner");
+    let osmosis_owner = osmosis.borrow().app.api().addr_make("owner");
+
+    let neutron_addr = neutron
+        .borrow_mut()
+        .app
+        .instantiate_contract(
+            code_id_neutron,
+            neutron_owner.clone(),
+            &counter::InstantiateMsg {},
+            &[],
+            "label".to_string(),
+            None,
+        )
+        .unwrap();
+
+    let osmosis_addr = osmosis
+        .borrow_mut()
+        .app
+        .instantiate_contract(
+            code_id_osmosis,
+            osmosis_owner,
+            &Empty {},
+            &[],
+            "label".to_string(),
+            None,
+        )
+        .unwrap();
+
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::Contract(neutron_addr.clone()),
+        IbcPort::Contract(osmosis_addr),
+        IbcOrder::Unordered,
+        "version",
+    )
+    .unwrap();
+
+    let msg = IbcMsg::SendPacket {
+        channel_id: "channel-0".to_string(),
+        data: to_json_binary(&CounterPacketData::Ok).unwrap(),
+        timeout: IbcTimeout::with_timestamp(Timestamp::from_seconds(
+            osmosis.borrow().app.block_info().time.seconds() + 1,
+        )),
+    };
+
+    neutron
+        .borrow_mut()
+        .app
+        .execute_contract(
+            neutron_owner,
+            neutron_addr.clone(),
+            &counter::ExecuteMsg::SendPacket(msg),
+            &[],
+        )
+        .unwrap();
+
+    eco.relay_all_packets().unwrap();
+
+    eco.assert_no_pending();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_acknowledged()
+        .assert_error_ack_contains("rejected packet 1");
+
+    let counter_src_ack_failing = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_wasm_smart::<CounterConfig>(&neutron_addr, &CounterQueryMsg::Config)
+        .unwrap()
+        .counter_packet_ack_failing;
+
+    assert_eq!(counter_src_ack_failing, 1);
+}
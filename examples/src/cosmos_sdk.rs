@@ -0,0 +1,488 @@
+use cosmwasm_std::{
+    from_json, to_json_vec, AnyMsg, Coin, CosmosMsg, Empty, IbcMsg, IbcOrder, IbcTimeout, Uint128,
+};
+use cw_iper_test::{
+    cw_multi_test::{next_block, no_init, BankSudo, Executor, SudoMsg},
+    ibc_applications::{Ics20, Ics20Helper},
+    ibc_entry_points,
+    stargate_applications::{
+        wasm_v1::{
+            MsgExecuteContract, MsgExecuteContractResponse, MsgInstantiateContract,
+            MsgInstantiateContractResponse, QueryCodeInfoRequest, QueryCodeInfoResponse,
+            QueryContractInfoRequest, QueryContractInfoResponse, QueryContractsByCodeRequest,
+            QueryContractsByCodeResponse, QueryRawContractStateRequest,
+            QueryRawContractStateResponse, QuerySmartContractStateRequest,
+            QuerySmartContractStateResponse,
+        },
+        CosmosSdk,
+    },
+    AppBuilderIperExt, AppBuilderStargateExt, AppExt, Ecosystem, IbcPort, IperAppBuilder,
+};
+use ibc_proto::cosmos::{
+    bank::v1beta1::{
+        Metadata, MsgSend, QueryAllBalancesRequest, QueryAllBalancesResponse, QueryBalanceRequest,
+        QueryBalanceResponse, QueryDenomMetadataRequest, QueryDenomMetadataResponse,
+        QueryDenomsMetadataRequest, QueryDenomsMetadataResponse, QuerySupplyOfRequest,
+        QuerySupplyOfResponse,
+    },
+    base::v1beta1::Coin as ProtoCoin,
+};
+use prost::{Message, Name};
+
+use crate::{
+    helpers::query_grpc,
+    mock_contracts::counter::{self, CounterConfig, CounterQueryMsg},
+};
+
+fn any_msg<T: Name + Message>(msg: T) -> CosmosMsg<Empty> {
+    CosmosMsg::Any(AnyMsg {
+        type_url: T::type_url(),
+        value: msg.encode_to_vec().into(),
+    })
+}
+
+fn proto_coin(amount: u128, denom: &str) -> ProtoCoin {
+    ProtoCoin {
+        denom: denom.to_string(),
+        amount: amount.to_string(),
+    }
+}
+
+#[test]
+fn cosmos_sdk_msgs() {
+    let osmosis = IperAppBuilder::new("osmo")
+        .with_stargate_app(CosmosSdk)
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("osmosis")
+        .unwrap();
+
+    let sender = osmosis.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    osmosis
+        .borrow_mut()
+        .app
+        .sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: sender.to_string(),
+            amount: vec![Coin::new(1_000_u128, "uosmo")],
+        }))
+        .unwrap();
+
+    // MsgSend
+    osmosis
+        .borrow_mut()
+        .app
+        .execute(
+            sender.clone(),
+            any_msg(MsgSend {
+                from_address: sender.to_string(),
+                to_address: receiver.to_string(),
+                amount: vec![proto_coin(100, "uosmo")],
+            }),
+        )
+        .unwrap();
+
+    let balance = osmosis
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&receiver, "uosmo")
+        .unwrap();
+
+    assert_eq!(balance.amount, Uint128::new(100));
+
+    // The signer has to be the sender
+    let err = osmosis
+        .borrow_mut()
+        .app
+        .execute(
+            receiver.clone(),
+            any_msg(MsgSend {
+                from_address: sender.to_string(),
+                to_address: receiver.to_string(),
+                amount: vec![proto_coin(100, "uosmo")],
+            }),
+        )
+        .unwrap_err();
+
+    assert!(err.root_cause().to_string().contains("Invalid signer"));
+
+    // MsgInstantiateContract
+    let code_id = osmosis
+        .borrow_mut()
+        .store_ibc_code(ibc_entry_points!(crate::mock_contracts::counter));
+
+    let response = osmosis
+        .borrow_mut()
+        .app
+        .execute(
+            sender.clone(),
+            any_msg(MsgInstantiateContract {
+                sender: sender.to_string(),
+                admin: String::new(),
+                code_id,
+                label: "counter".to_string(),
+                msg: to_json_vec(&counter::InstantiateMsg {}).unwrap(),
+                funds: vec![],
+            }),
+        )
+        .unwrap();
+
+    let contract = MsgInstantiateContractResponse::decode(response.data.unwrap().as_slice())
+        .unwrap()
+        .address;
+
+    // MsgExecuteContract
+    let response = osmosis
+        .borrow_mut()
+        .app
+        .execute(
+            sender.clone(),
+            any_msg(MsgExecuteContract {
+                sender: sender.to_string(),
+                contract: contract.clone(),
+                msg: to_json_vec(&counter::ExecuteMsg::JustReceive {
+                    msg: "hello".to_string(),
+                    to_fail: false,
+                })
+                .unwrap(),
+                funds: vec![proto_coin(50, "uosmo")],
+            }),
+        )
+        .unwrap();
+
+    MsgExecuteContractResponse::decode(response.data.unwrap_or_default().as_slice()).unwrap();
+
+    let config = osmosis
+        .borrow()
+        .app
+        .wrap()
+        .query_wasm_smart::<CounterConfig>(&contract, &CounterQueryMsg::Config)
+        .unwrap();
+
+    assert_eq!(config.counter_ibc_hook, 1);
+
+    let balance = osmosis
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&contract, "uosmo")
+        .unwrap();
+
+    assert_eq!(balance.amount, Uint128::new(50));
+}
+
+#[test]
+fn cosmos_sdk_bank_queries() {
+    let neutron = IperAppBuilder::new("neutron")
+        .with_ibc_app(Ics20)
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("neutron")
+        .unwrap();
+
+    let osmosis = IperAppBuilder::new("osmo")
+        .with_ibc_app(Ics20)
+        .unwrap()
+        .with_stargate_app(CosmosSdk)
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("osmosis")
+        .unwrap();
+
+    let eco = Ecosystem::default()
+        .add_app(neutron.clone())
+        .add_app(osmosis.clone());
+
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::from_application(Ics20),
+        IbcPort::from_application(Ics20),
+        IbcOrder::Unordered,
+        "version",
+    )
+    .unwrap();
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    neutron
+        .borrow_mut()
+        .app
+        .sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: sender.to_string(),
+            amount: vec![Coin::new(1_000_u128, "untrn")],
+        }))
+        .unwrap();
+
+    osmosis
+        .borrow_mut()
+        .app
+        .sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: receiver.to_string(),
+            amount: vec![Coin::new(500_u128, "uosmo")],
+        }))
+        .unwrap();
+
+    neutron
+        .borrow_mut()
+        .app
+        .execute(
+            sender.clone(),
+            CosmosMsg::Ibc(IbcMsg::Transfer {
+                channel_id: "channel-0".to_string(),
+                to_address: receiver.to_string(),
+                amount: Coin::new(1_000_u128, "untrn"),
+                timeout: IbcTimeout::with_timestamp(
+                    osmosis.borrow().app.block_info().time.plus_seconds(1),
+                ),
+                memo: None,
+            }),
+        )
+        .unwrap();
+
+    eco.relay_all_packets().unwrap();
+
+    let ibc_denom = Ics20Helper::compute_ibc_denom_from_trace("transfer/channel-0/untrn");
+
+    let osmosis_ref = osmosis.borrow();
+    let querier = osmosis_ref.app.wrap();
+
+    let response: QueryBalanceResponse = query_grpc(
+        querier,
+        "/cosmos.bank.v1beta1.Query/Balance",
+        QueryBalanceRequest {
+            address: receiver.to_string(),
+            denom: ibc_denom.clone(),
+        },
+    );
+
+    assert_eq!(response.balance, Some(proto_coin(1_000, &ibc_denom)));
+
+    let response: QueryAllBalancesResponse = query_grpc(
+        querier,
+        "/cosmos.bank.v1beta1.Query/AllBalances",
+        QueryAllBalancesRequest {
+            address: receiver.to_string(),
+            pagination: None,
+        },
+    );
+
+    assert_eq!(
+        response.balances,
+        vec![proto_coin(1_000, &ibc_denom), proto_coin(500, "uosmo")]
+    );
+
+    let response: QuerySupplyOfResponse = query_grpc(
+        querier,
+        "/cosmos.bank.v1beta1.Query/SupplyOf",
+        QuerySupplyOfRequest {
+            denom: "uosmo".to_string(),
+        },
+    );
+
+    assert_eq!(response.amount, Some(proto_coin(500, "uosmo")));
+
+    // Default metadata of the voucher
+    let response: QueryDenomMetadataResponse = query_grpc(
+        querier,
+        "/cosmos.bank.v1beta1.Query/DenomMetadata",
+        QueryDenomMetadataRequest {
+            denom: ibc_denom.clone(),
+        },
+    );
+
+    let metadata = response.metadata.unwrap();
+
+    assert_eq!(metadata.base, ibc_denom);
+    assert_eq!(metadata.display, "transfer/channel-0/untrn");
+    assert_eq!(metadata.symbol, "UNTRN");
+    assert_eq!(metadata.denom_units[0].denom, "untrn");
+
+    // Not set yet
+    querier
+        .query_grpc(
+            "/cosmos.bank.v1beta1.Query/DenomMetadata".to_string(),
+            QueryDenomMetadataRequest {
+                denom: "uosmo".to_string(),
+            }
+            .encode_to_vec()
+            .into(),
+        )
+        .unwrap_err();
+
+    drop(osmosis_ref);
+
+    let uosmo_metadata = Metadata {
+        description: "The native staking token of Osmosis".to_string(),
+        base: "uosmo".to_string(),
+        display: "osmo".to_string(),
+        name: "Osmosis".to_string(),
+        symbol: "OSMO".to_string(),
+        ..Default::default()
+    };
+
+    CosmosSdk::set_denom_metadata(
+        osmosis.borrow_mut().app.storage_mut(),
+        uosmo_metadata.clone(),
+    )
+    .unwrap();
+
+    let response: QueryDenomsMetadataResponse = query_grpc(
+        osmosis.borrow().app.wrap(),
+        "/cosmos.bank.v1beta1.Query/DenomsMetadata",
+        QueryDenomsMetadataRequest { pagination: None },
+    );
+
+    assert_eq!(response.metadatas, vec![metadata, uosmo_metadata]);
+}
+
+#[test]
+fn cosmos_sdk_wasm_queries() {
+    let osmosis = IperAppBuilder::new("osmo")
+        .with_stargate_app(CosmosSdk)
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("osmosis")
+        .unwrap();
+
+    let owner = osmosis.borrow().app.api().addr_make("owner");
+
+    let code_id = osmosis
+        .borrow_mut()
+        .store_ibc_code(ibc_entry_points!(crate::mock_contracts::counter));
+
+    let instantiate = |label: &str| {
+        osmosis
+            .borrow_mut()
+            .app
+            .instantiate_contract(
+                code_id,
+                owner.clone(),
+                &counter::InstantiateMsg {},
+                &[],
+                label,
+                Some(owner.to_string()),
+            )
+            .unwrap()
+    };
+
+    let first = instantiate("first");
+
+    osmosis.borrow_mut().app.update_block(next_block);
+
+    // A salted contract still consumes an instance id of the WasmKeeper
+    let salted = osmosis
+        .borrow_mut()
+        .app
+        .instantiate2_contract(
+            code_id,
+            owner.clone(),
+            &counter::InstantiateMsg {},
+            &[],
+            "salted",
+            Some(owner.to_string()),
+            b"salt",
+        )
+        .unwrap();
+
+    osmosis.borrow_mut().app.update_block(next_block);
+
+    let contracts = [first, salted, instantiate("second")];
+
+    let osmosis = osmosis.borrow();
+    let querier = osmosis.app.wrap();
+
+    // ContractInfo
+    let response: QueryContractInfoResponse = query_grpc(
+        querier,
+        "/cosmwasm.wasm.v1.Query/ContractInfo",
+        QueryContractInfoRequest {
+            address: contracts[0].to_string(),
+        },
+    );
+
+    let contract_info = response.contract_info.unwrap();
+
+    assert_eq!(response.address, contracts[0].to_string());
+    assert_eq!(contract_info.code_id, code_id);
+    assert_eq!(contract_info.creator, owner.to_string());
+    assert_eq!(contract_info.admin, owner.to_string());
+
+    // SmartContractState
+    let response: QuerySmartContractStateResponse = query_grpc(
+        querier,
+        "/cosmwasm.wasm.v1.Query/SmartContractState",
+        QuerySmartContractStateRequest {
+            address: contracts[0].to_string(),
+            query_data: to_json_vec(&CounterQueryMsg::Config).unwrap(),
+        },
+    );
+
+    assert_eq!(
+        from_json::<CounterConfig>(&response.data).unwrap(),
+        CounterConfig::default()
+    );
+
+    // RawContractState
+    let response: QueryRawContractStateResponse = query_grpc(
+        querier,
+        "/cosmwasm.wasm.v1.Query/RawContractState",
+        QueryRawContractStateRequest {
+            address: contracts[0].to_string(),
+            query_data: b"counter_config".to_vec(),
+        },
+    );
+
+    assert_eq!(
+        from_json::<CounterConfig>(&response.data).unwrap(),
+        CounterConfig::default()
+    );
+
+    // CodeInfo
+    let response: QueryCodeInfoResponse = query_grpc(
+        querier,
+        "/cosmwasm.wasm.v1.Query/CodeInfo",
+        QueryCodeInfoRequest { code_id },
+    );
+
+    assert_eq!(response.code_id, code_id);
+    assert_eq!(
+        response.checksum,
+        querier
+            .query_wasm_code_info(code_id)
+            .unwrap()
+            .checksum
+            .as_slice()
+            .to_vec()
+    );
+
+    // ContractsByCode
+    let response: QueryContractsByCodeResponse = query_grpc(
+        querier,
+        "/cosmwasm.wasm.v1.Query/ContractsByCode",
+        QueryContractsByCodeRequest {
+            code_id,
+            pagination: None,
+        },
+    );
+
+    assert_eq!(
+        response.contracts,
+        contracts.map(|contract| contract.to_string()).to_vec()
+    );
+
+    let response: QueryContractsByCodeResponse = query_grpc(
+        querier,
+        "/cosmwasm.wasm.v1.Query/ContractsByCode",
+        QueryContractsByCodeRequest {
+            code_id: code_id + 1,
+            pagination: None,
+        },
+    );
+
+    assert!(response.contracts.is_empty());
+}
@@ -0,0 +1,15 @@
+use cosmwasm_std::QuerierWrapper;
+use prost::Message;
+
+/// Send a `GrpcQuery` to `path`, decoding the response as `T`.
+pub fn query_grpc<T: Message + Default>(
+    querier: QuerierWrapper,
+    path: &str,
+    request: impl Message,
+) -> T {
+    let response = querier
+        .query_grpc(path.to_string(), request.encode_to_vec().into())
+        .unwrap();
+
+    T::decode(response.as_slice()).unwrap()
+}
@@ -0,0 +1,200 @@
+use std::{cell::RefCell, rc::Rc};
+
+use cosmwasm_std::{
+    from_json, to_json_binary, AnyMsg, Api, Binary, BlockInfo, CosmosMsg, Empty, IbcMsg, IbcOrder,
+    IbcPacketReceiveMsg, IbcTimeout, Storage,
+};
+use cw_iper_test::{
+    cw_multi_test::{no_init, AppResponse, ContractWrapper, Executor, MockApiBech32},
+    AppBuilderIperExt, AppExt, AppResult, ContractWrapperExt, Ecosystem, IbcClosures, IbcPort,
+    InfallibleResult, IperAppBuilder, IperContract, PacketReceiveFailing, PacketReceiveOk,
+    RouterWrapper,
+};
+use cw_iper_test_macros::{ibc_application, urls};
+use cw_storage_plus::Item;
+
+use crate::mock_contracts::counter::{
+    self, CounterAckData, CounterConfig, CounterPacketData, CounterQueryMsg,
+};
+
+const ECHO_RECEIVED: Item<u64> = Item::new("echo_received");
+
+/// Application acking `counter` packets, with default implementation for all other hooks.
+#[derive(Default, Clone)]
+struct Echo;
+
+#[urls]
+enum EchoMsgUrls {
+    #[strum(serialize = "/echo.v1.MsgEcho")]
+    MsgEcho,
+}
+
+#[urls]
+enum EchoQueryUrls {}
+
+#[ibc_application(
+    port = "echo",
+    stargate(name = "echo", query_urls = EchoQueryUrls, msgs_urls = EchoMsgUrls)
+)]
+impl Echo {
+    #[init]
+    fn setup(&self, _api: &MockApiBech32, storage: &mut dyn Storage) -> AppResult<()> {
+        ECHO_RECEIVED.save(storage, &0)?;
+        Ok(())
+    }
+
+    #[packet_receive]
+    fn receive(
+        &self,
+        _api: &dyn Api,
+        _block: &BlockInfo,
+        _router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: IbcPacketReceiveMsg,
+    ) -> InfallibleResult<PacketReceiveOk, PacketReceiveFailing> {
+        match from_json::<CounterPacketData>(msg.packet.data) {
+            Ok(CounterPacketData::Ok) => {
+                let received = ECHO_RECEIVED.load(*storage.borrow()).unwrap() + 1;
+                ECHO_RECEIVED
+                    .save(*storage.borrow_mut(), &received)
+                    .unwrap();
+
+                InfallibleResult::Ok(PacketReceiveOk {
+                    response: AppResponse::default(),
+                    ack: Some(to_json_binary(&CounterAckData::Ok).unwrap()),
+                })
+            }
+            _ => InfallibleResult::Err(PacketReceiveFailing {
+                error: "unsupported packet".to_string(),
+                ack: Some(to_json_binary(&CounterAckData::Fail).unwrap()),
+            }),
+        }
+    }
+}
+
+#[test]
+fn ibc_application_macro() {
+    let neutron = IperAppBuilder::new("neutron")
+        .build(no_init)
+        .into_iper_app("neutron")
+        .unwrap();
+
+    let osmosis = IperAppBuilder::new("osmo")
+        .with_ibc_app(Echo)
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("osmosis")
+        .unwrap();
+
+    let eco = Ecosystem::default()
+        .add_app(neutron.clone())
+        .add_app(osmosis.clone());
+
+    assert_eq!(Echo::IBC_PORT, "echo");
+    assert_eq!(
+        ECHO_RECEIVED.load(osmosis.borrow().app.storage()).unwrap(),
+        0
+    );
+
+    let code_id = neutron.borrow_mut().store_ibc_code(IperContract::new(
+        ContractWrapper::new(counter::execute, counter::instantiate, counter::query).to_contract(),
+        Some(IbcClosures::new_as_ibc_contract(
+            counter::ibc_channel_open,
+            counter::ibc_channel_close,
+            counter::ibc_channel_connect,
+            counter::ibc_packet_receive,
+            counter::ibc_packet_ack,
+            counter::ibc_packet_timeout,
+        )),
+    ));
+
+    let owner = neutron.borrow().app.api().addr_make("owner");
+
+    let contract = neutron
+        .borrow_mut()
+        .app
+        .instantiate_contract(
+            code_id,
+            owner.clone(),
+            &counter::InstantiateMsg {},
+            &[],
+            "label".to_string(),
+            None,
+        )
+        .unwrap();
+
+    // Default open_channel and channel_connect
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::Contract(contract.clone()),
+        IbcPort::from_application(Echo),
+        IbcOrder::Unordered,
+        "echo-1",
+    )
+    .unwrap();
+
+    for data in [CounterPacketData::Ok, CounterPacketData::Fail] {
+        let msg = IbcMsg::SendPacket {
+            channel_id: "channel-0".to_string(),
+            data: to_json_binary(&data).unwrap(),
+            timeout: IbcTimeout::with_timestamp(
+                osmosis.borrow().app.block_info().time.plus_seconds(1),
+            ),
+        };
+
+        neutron
+            .borrow_mut()
+            .app
+            .execute_contract(
+                owner.clone(),
+                contract.clone(),
+                &counter::ExecuteMsg::SendPacket(msg),
+                &[],
+            )
+            .unwrap();
+    }
+
+    eco.relay_all_packets().unwrap();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_ack_success();
+
+    eco.assert_packet("neutron", "channel-0", 2)
+        .assert_error_ack_contains("fail");
+
+    assert_eq!(
+        ECHO_RECEIVED.load(osmosis.borrow().app.storage()).unwrap(),
+        1
+    );
+
+    let config = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_wasm_smart::<CounterConfig>(&contract, &CounterQueryMsg::Config)
+        .unwrap();
+
+    assert_eq!(config.counter_packet_ack_ok, 1);
+    assert_eq!(config.counter_packet_ack_failing, 1);
+
+    // Default stargate_msg
+    let sender = osmosis.borrow().app.api().addr_make("sender");
+
+    let err = eco
+        .execute(
+            "osmosis",
+            sender,
+            CosmosMsg::<Empty>::Any(AnyMsg {
+                type_url: "/echo.v1.MsgEcho".to_string(),
+                value: Binary::default(),
+            }),
+        )
+        .unwrap_err();
+
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("Unsupported message on echo: /echo.v1.MsgEcho"));
+}
@@ -0,0 +1,225 @@
+use cosmwasm_std::{Coin, CosmosMsg, Empty, IbcMsg, IbcOrder, IbcTimeout};
+use cw_iper_test::{
+    cw_multi_test::{no_init, BankSudo, SudoMsg},
+    ibc_applications::Ics20,
+    AppBuilderIperExt, AppExt, Ecosystem, IbcPort, IperAppBuilder,
+};
+use ibc_proto::ibc::core::{
+    channel::v1::{
+        Order, QueryChannelRequest, QueryChannelResponse, QueryConnectionChannelsRequest,
+        QueryConnectionChannelsResponse, QueryNextSequenceReceiveRequest,
+        QueryNextSequenceReceiveResponse, QueryPacketCommitmentRequest,
+        QueryPacketCommitmentResponse, QueryPacketCommitmentsRequest,
+        QueryPacketCommitmentsResponse, QueryUnreceivedPacketsRequest,
+        QueryUnreceivedPacketsResponse, State,
+    },
+    connection::v1::{QueryConnectionRequest, QueryConnectionResponse, State as ConnectionState},
+};
+use prost::Message;
+
+use crate::helpers::query_grpc;
+
+#[test]
+fn ibc_core_queries() {
+    let neutron = IperAppBuilder::new("neutron")
+        .with_ibc_app(Ics20)
+        .unwrap()
+        .with_ibc_core()
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("neutron-1")
+        .unwrap();
+
+    let osmosis = IperAppBuilder::new("osmo")
+        .with_ibc_app(Ics20)
+        .unwrap()
+        .with_ibc_core()
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("osmosis-1")
+        .unwrap();
+
+    let eco = Ecosystem::default()
+        .add_app(neutron.clone())
+        .add_app(osmosis.clone());
+
+    let connection = eco.open_connection("neutron-1", "osmosis-1").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::from_application(Ics20),
+        IbcPort::from_application(Ics20),
+        IbcOrder::Unordered,
+        "ics20-1",
+    )
+    .unwrap();
+
+    let response: QueryConnectionResponse = query_grpc(
+        neutron.borrow().app.wrap(),
+        "/ibc.core.connection.v1.Query/Connection",
+        QueryConnectionRequest {
+            connection_id: "connection-0".to_string(),
+        },
+    );
+
+    let end = response.connection.unwrap();
+
+    assert_eq!(end.state, ConnectionState::Open as i32);
+    assert_eq!(end.client_id, "07-tendermint-0");
+    assert_eq!(end.counterparty.unwrap().connection_id, "connection-0");
+
+    let response: QueryChannelResponse = query_grpc(
+        neutron.borrow().app.wrap(),
+        "/ibc.core.channel.v1.Query/Channel",
+        QueryChannelRequest {
+            port_id: "transfer".to_string(),
+            channel_id: "channel-0".to_string(),
+        },
+    );
+
+    let channel = response.channel.unwrap();
+
+    assert_eq!(channel.state, State::Open as i32);
+    assert_eq!(channel.ordering, Order::Unordered as i32);
+    assert_eq!(channel.version, "ics20-1");
+    assert_eq!(channel.connection_hops, vec!["connection-0".to_string()]);
+    assert_eq!(channel.counterparty.unwrap().channel_id, "channel-0");
+    assert_eq!(response.proof_height.unwrap().revision_number, 1);
+
+    let response: QueryConnectionChannelsResponse = query_grpc(
+        osmosis.borrow().app.wrap(),
+        "/ibc.core.channel.v1.Query/ConnectionChannels",
+        QueryConnectionChannelsRequest {
+            connection: "connection-0".to_string(),
+            pagination: None,
+        },
+    );
+
+    assert_eq!(response.channels.len(), 1);
+    assert_eq!(response.channels[0].port_id, "transfer");
+
+    // Send a packet
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    neutron
+        .borrow_mut()
+        .app
+        .sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: sender.to_string(),
+            amount: vec![Coin::new(1_000_u128, "untrn")],
+        }))
+        .unwrap();
+
+    eco.execute(
+        "neutron-1",
+        sender,
+        CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+            channel_id: "channel-0".to_string(),
+            to_address: receiver.to_string(),
+            amount: Coin::new(1_000_u128, "untrn"),
+            timeout: IbcTimeout::with_timestamp(
+                osmosis.borrow().app.block_info().time.plus_seconds(1),
+            ),
+            memo: None,
+        }),
+    )
+    .unwrap();
+
+    // The commitment is stored when the packet is tracked
+    eco.relay_history().unwrap();
+
+    let response: QueryPacketCommitmentResponse = query_grpc(
+        neutron.borrow().app.wrap(),
+        "/ibc.core.channel.v1.Query/PacketCommitment",
+        QueryPacketCommitmentRequest {
+            port_id: "transfer".to_string(),
+            channel_id: "channel-0".to_string(),
+            sequence: 1,
+        },
+    );
+
+    assert_eq!(response.commitment.len(), 32);
+
+    let unreceived_request = QueryUnreceivedPacketsRequest {
+        port_id: "transfer".to_string(),
+        channel_id: "channel-0".to_string(),
+        packet_commitment_sequences: vec![1],
+    };
+
+    let response: QueryUnreceivedPacketsResponse = query_grpc(
+        osmosis.borrow().app.wrap(),
+        "/ibc.core.channel.v1.Query/UnreceivedPackets",
+        unreceived_request.clone(),
+    );
+
+    assert_eq!(response.sequences, vec![1]);
+
+    // Receive on osmosis, the ack is still pending
+    eco.relay_next_packet("neutron-1").unwrap();
+
+    let response: QueryUnreceivedPacketsResponse = query_grpc(
+        osmosis.borrow().app.wrap(),
+        "/ibc.core.channel.v1.Query/UnreceivedPackets",
+        unreceived_request,
+    );
+
+    assert!(response.sequences.is_empty());
+
+    let response: QueryNextSequenceReceiveResponse = query_grpc(
+        osmosis.borrow().app.wrap(),
+        "/ibc.core.channel.v1.Query/NextSequenceReceive",
+        QueryNextSequenceReceiveRequest {
+            port_id: "transfer".to_string(),
+            channel_id: "channel-0".to_string(),
+        },
+    );
+
+    assert_eq!(response.next_sequence_receive, 2);
+
+    let commitments_request = QueryPacketCommitmentsRequest {
+        port_id: "transfer".to_string(),
+        channel_id: "channel-0".to_string(),
+        pagination: None,
+    };
+
+    let response: QueryPacketCommitmentsResponse = query_grpc(
+        neutron.borrow().app.wrap(),
+        "/ibc.core.channel.v1.Query/PacketCommitments",
+        commitments_request.clone(),
+    );
+
+    assert_eq!(response.commitments.len(), 1);
+    assert_eq!(response.commitments[0].sequence, 1);
+
+    // The ack removes the commitment
+    eco.relay_all_packets().unwrap();
+
+    let response: QueryPacketCommitmentsResponse = query_grpc(
+        neutron.borrow().app.wrap(),
+        "/ibc.core.channel.v1.Query/PacketCommitments",
+        commitments_request,
+    );
+
+    assert!(response.commitments.is_empty());
+
+    let err = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_grpc(
+            "/ibc.core.channel.v1.Query/PacketCommitment".to_string(),
+            QueryPacketCommitmentRequest {
+                port_id: "transfer".to_string(),
+                channel_id: "channel-0".to_string(),
+                sequence: 1,
+            }
+            .encode_to_vec()
+            .into(),
+        )
+        .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("Packet commitment not found: transfer/channel-0, sequence: 1"));
+}
@@ -1,11 +1,13 @@
 use std::{cell::RefCell, rc::Rc};
 
-use cosmwasm_std::{Addr, Coin, CosmosMsg, IbcMsg, IbcOrder, IbcTimeout, Timestamp, Uint128};
+use cosmwasm_std::{
+    to_json_binary, Addr, Coin, CosmosMsg, Empty, IbcMsg, IbcTimeout, Timestamp, Uint128,
+};
 use cw_iper_test::{
-    cw_multi_test::{no_init, BankSudo, ContractWrapper, Executor, SudoMsg},
+    cw_multi_test::{BankSudo, ContractWrapper, Executor, SudoMsg},
     ibc_applications::{IbcHook, Ics20, Ics20Helper, MemoField, WasmField},
-    AppBuilderIperExt, AppExt, BaseIperApp, ContractWrapperExt, Ecosystem, IbcChannelCreator,
-    IbcPort, IperAppBuilder, IperContract,
+    BaseIperApp, ContractWrapperExt, Ecosystem, EcosystemSpec, IperContract, MiddlewareStack,
+    OriginTx, RelayEventKind, SpecRegistry,
 };
 
 use crate::mock_contracts::counter::{self, CounterConfig, CounterQueryMsg};
@@ -19,92 +21,56 @@ struct TestIbcHookEnv {
 }
 
 fn startup() -> TestIbcHookEnv {
-    let osmosis = IperAppBuilder::new("osmo")
-        .with_ibc_app(IbcHook::new(Ics20))
-        .build(no_init)
-        .into_iper_app("osmosis");
-
-    let neutron = IperAppBuilder::new("neutron")
-        .with_ibc_app(IbcHook::new(Ics20))
-        .build(no_init)
-        .into_iper_app("neutron");
-
-    let eco = Ecosystem::default()
-        .add_app(neutron.clone())
-        .add_app(osmosis.clone());
-
-    let contract = IperContract::new(
-        ContractWrapper::new(counter::execute, counter::instantiate, counter::query)
-            .with_sudo(counter::sudo)
-            .to_contract(),
-        None,
-    );
-
-    let code_id_osmosis = osmosis.borrow_mut().store_ibc_code(contract);
-
-    let osmosis_owner = osmosis.borrow().app.api().addr_make("owner");
-
-    let osmosis_contract_addr = osmosis
-        .borrow_mut()
-        .app
-        .instantiate_contract(
-            code_id_osmosis,
-            osmosis_owner,
-            &counter::InstantiateMsg {},
-            &[],
-            "label".to_string(),
-            None,
-        )
-        .unwrap();
-
-    let contract = IperContract::new(
-        ContractWrapper::new(counter::execute, counter::instantiate, counter::query)
-            .with_sudo(counter::sudo)
-            .to_contract(),
-        None,
-    );
+    let spec = EcosystemSpec::from_json(
+        r#"{
+            "chains": [
+                {
+                    "chain_id": "osmosis",
+                    "prefix": "osmo",
+                    "ibc_apps": ["ibc_hook"],
+                    "contracts": [{ "name": "counter_osmosis", "code": "counter", "msg": {} }]
+                },
+                {
+                    "chain_id": "neutron",
+                    "prefix": "neutron",
+                    "ibc_apps": ["ibc_hook"],
+                    "contracts": [{ "name": "counter_neutron", "code": "counter", "msg": {} }]
+                }
+            ],
+            "channels": [
+                {
+                    "name": "transfer",
+                    "a": { "chain_id": "neutron", "port": { "module": "transfer" } },
+                    "b": { "chain_id": "osmosis", "port": { "module": "transfer" } }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
 
-    let code_id_neutron = neutron.borrow_mut().store_ibc_code(contract);
+    let registry = SpecRegistry::default()
+        .with_ibc_app("ibc_hook", || MiddlewareStack::new(Ics20).with(IbcHook))
+        .with_contract("counter", || {
+            IperContract::new(
+                ContractWrapper::new(counter::execute, counter::instantiate, counter::query)
+                    .with_sudo(counter::sudo)
+                    .to_contract(),
+                None,
+            )
+        });
 
-    let neutron_owner = neutron.borrow().app.api().addr_make("owner");
+    let env = spec.build(&registry).unwrap();
 
-    let neutron_contract_addr = neutron
-        .borrow_mut()
-        .app
-        .instantiate_contract(
-            code_id_neutron,
-            neutron_owner,
-            &counter::InstantiateMsg {},
-            &[],
-            "label".to_string(),
-            None,
-        )
-        .unwrap();
-
-    eco.open_ibc_channel(
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "neutron",
-        ),
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "osmosis",
-        ),
-    )
-    .unwrap();
+    let channel = env.channel("transfer").unwrap();
+    assert_eq!(channel.channel_a, "channel-0");
+    assert_eq!(channel.channel_b, "channel-0");
 
     TestIbcHookEnv {
-        eco,
-        neutron,
-        osmosis,
-        contract_neutron: neutron_contract_addr,
-        contract_osmosis: osmosis_contract_addr,
+        neutron: env.chain("neutron").unwrap(),
+        osmosis: env.chain("osmosis").unwrap(),
+        contract_neutron: env.contract("counter_neutron").unwrap(),
+        contract_osmosis: env.contract("counter_osmosis").unwrap(),
+        eco: env.eco,
     }
 }
 
@@ -517,3 +483,90 @@ fn ibc_hook_with_ibc_callback_failing() {
 
     assert_eq!(balance.amount, Uint128::zero());
 }
+
+#[test]
+fn ibc_hook_relay_history() {
+    let TestIbcHookEnv {
+        eco,
+        neutron,
+        osmosis,
+        contract_osmosis,
+        contract_neutron,
+    } = startup();
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+
+    let amount = Coin::new(1_000_000_u128, "untrn");
+
+    let msg = CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+        channel_id: "channel-0".to_string(),
+        to_address: contract_osmosis.to_string(),
+        amount: amount.clone(),
+        timeout: IbcTimeout::with_timestamp(osmosis.borrow().app.block_info().time.plus_seconds(1)),
+        memo: Some(
+            serde_json::to_string_pretty(&MemoField::new(
+                Some(WasmField {
+                    contract: contract_osmosis.to_string(),
+                    msg: counter::ExecuteMsg::JustReceive {
+                        msg: "test".to_string(),
+                        to_fail: false,
+                    },
+                }),
+                Some(contract_neutron.to_string()),
+            ))
+            .unwrap(),
+        ),
+    });
+
+    neutron
+        .borrow_mut()
+        .app
+        .sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: sender.to_string(),
+            amount: vec![amount.clone()],
+        }))
+        .unwrap();
+
+    eco.execute("neutron", sender.clone(), msg.clone()).unwrap();
+
+    let history = eco.relay_history().unwrap();
+
+    assert_eq!(history.entries().count(), 1);
+
+    eco.relay_all_packets().unwrap();
+
+    let history = eco.relay_history().unwrap();
+
+    let trees = history.trees();
+
+    assert_eq!(trees.len(), 1);
+
+    let send = &trees[0];
+    assert_eq!(send.entry.kind, RelayEventKind::Send);
+    assert_eq!(send.entry.chain_id(), "neutron");
+    assert_eq!(send.entry.sequence, Some(1));
+    assert_eq!(
+        send.entry.origin,
+        Some(OriginTx::Execute {
+            sender: sender.clone(),
+            msg: to_json_binary(&msg).unwrap(),
+        })
+    );
+    assert_eq!(send.children.len(), 1);
+
+    let receive = &send.children[0];
+    assert_eq!(receive.entry.kind, RelayEventKind::Receive);
+    assert_eq!(receive.entry.chain_id(), "osmosis");
+    assert!(receive.entry.ack.is_some());
+    assert!(receive.entry.src_response.is_some());
+    assert_eq!(receive.entry.origin, None);
+    assert_eq!(receive.children.len(), 1);
+
+    let ack = &receive.children[0];
+    assert_eq!(ack.entry.kind, RelayEventKind::Ack);
+    assert_eq!(ack.entry.chain_id(), "neutron");
+    assert_eq!(ack.entry.ack, receive.entry.ack);
+    assert!(ack.children.is_empty());
+
+    assert_eq!(history.find("osmosis", "channel-0", 1).len(), 3);
+}
@@ -1,22 +1,59 @@
+use std::panic::AssertUnwindSafe;
+
 use cosmwasm_std::{
-    AnyMsg, BankQuery, Coin, CosmosMsg, IbcMsg, IbcOrder, IbcTimeout, QueryRequest, SupplyResponse,
-    Uint128,
+    to_json_binary, AnyMsg, BankMsg, BankQuery, Binary, Coin, CosmosMsg, Empty, Event, IbcMsg,
+    IbcOrder, IbcTimeout, IbcTimeoutBlock, QueryRequest, SupplyResponse, Uint128,
 };
 use cw_iper_test::cw_multi_test::{
     no_init, AppBuilder, BankSudo, Executor, MockApiBech32, SudoMsg,
 };
 use cw_iper_test::ibc_applications::Ics20;
-use cw_iper_test::ibc_applications::Ics20Helper;
+use cw_iper_test::ibc_applications::{Ics20Helper, Ics20Params, Ics20SudoMsg};
 
 use cw_iper_test::{
-    AppBuilderIperExt, AppExt, Ecosystem, IbcChannelCreator, IbcPort, IperAppBuilder,
-    IperIbcModule, IperStargateModule,
+    assert_iper_err, serde_json::json, AppBuilderIperExt, AppExt, ClientStatus, ConnectionState,
+    Ecosystem, EcosystemSpec, IbcChannelCreator, IbcPort, IperAppBuilder, IperError, IperIbcModule,
+    IperStargateModule, IperSudoMsg, PacketStatus, Recording, SpecEcosystem, SpecRegistry,
+    SyncEcosystem,
 };
 use ibc_proto::cosmos::base::v1beta1::Coin as IbcCoin;
-use ibc_proto::ibc::apps::transfer::v1::MsgTransfer;
+use ibc_proto::ibc::apps::transfer::v1::{MsgTransfer, MsgTransferResponse};
 
 use prost::Message;
 
+/// [`EcosystemSpec`] of two chains with [`Ics20`], connected by the `transfer` channel (`channel-0` on both sides).
+///
+/// `amount` untrn are minted to `sender` on `neutron`.
+fn ics20_spec(neutron: &str, osmosis: &str, amount: u128) -> EcosystemSpec {
+    let balances = match amount {
+        0 => json!([]),
+        amount => json!([{ "account": "sender", "coins": [Coin::new(amount, "untrn")] }]),
+    };
+
+    EcosystemSpec::from_json(
+        &json!({
+            "chains": [
+                { "chain_id": neutron, "prefix": "neutron", "ibc_apps": ["ics20"], "balances": balances },
+                { "chain_id": osmosis, "prefix": "osmo", "ibc_apps": ["ics20"] }
+            ],
+            "channels": [
+                {
+                    "name": "transfer",
+                    "a": { "chain_id": neutron, "port": { "module": "transfer" } },
+                    "b": { "chain_id": osmosis, "port": { "module": "transfer" } }
+                }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap()
+}
+
+fn build_ics20(spec: &EcosystemSpec) -> SpecEcosystem {
+    spec.build(&SpecRegistry::default().with_ibc_app("ics20", || Ics20))
+        .unwrap()
+}
+
 #[test]
 fn base_ics20_transfer() {
     let neutron = AppBuilder::new()
@@ -24,33 +61,30 @@ fn base_ics20_transfer() {
         .with_ibc(IperIbcModule::default())
         .with_stargate(IperStargateModule::default())
         .with_ibc_app(Ics20)
+        .unwrap()
         .build(no_init)
-        .into_iper_app("neutron");
+        .into_iper_app("neutron")
+        .unwrap();
 
     let osmosis = IperAppBuilder::new("osmo")
         .with_ibc_app(Ics20)
+        .unwrap()
         .build(no_init)
-        .into_iper_app("osmosis");
+        .into_iper_app("osmosis")
+        .unwrap();
 
     let eco = Ecosystem::default()
         .add_app(neutron.clone())
         .add_app(osmosis.clone());
 
-    eco.open_ibc_channel(
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "neutron",
-        ),
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "osmosis",
-        ),
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::from_application(Ics20),
+        IbcPort::from_application(Ics20),
+        IbcOrder::Unordered,
+        "version",
     )
     .unwrap();
 
@@ -82,10 +116,20 @@ fn base_ics20_transfer() {
         }))
         .unwrap();
 
-    neutron.borrow_mut().app.execute(sender.clone(), msg).unwrap();
+    neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), msg)
+        .unwrap();
 
     eco.relay_all_packets().unwrap();
 
+    eco.assert_no_pending();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_ack_success()
+        .assert_event("osmosis", "ics20_mint", &[("receiver", receiver.as_str())]);
+
     let balance = neutron
         .borrow()
         .app
@@ -172,33 +216,30 @@ fn stargate_ics20_transfer() {
         .with_ibc(IperIbcModule::default())
         .with_stargate(IperStargateModule::default())
         .with_ibc_app(Ics20)
+        .unwrap()
         .build(no_init)
-        .into_iper_app("neutron");
+        .into_iper_app("neutron")
+        .unwrap();
 
     let osmosis = IperAppBuilder::new("osmo")
         .with_ibc_app(Ics20)
+        .unwrap()
         .build(no_init)
-        .into_iper_app("osmosis");
+        .into_iper_app("osmosis")
+        .unwrap();
 
     let eco = Ecosystem::default()
         .add_app(neutron.clone())
         .add_app(osmosis.clone());
 
-    eco.open_ibc_channel(
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "neutron",
-        ),
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "osmosis",
-        ),
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::from_application(Ics20),
+        IbcPort::from_application(Ics20),
+        IbcOrder::Unordered,
+        "version",
     )
     .unwrap();
 
@@ -242,7 +283,62 @@ fn stargate_ics20_transfer() {
         }))
         .unwrap();
 
-    neutron.borrow_mut().app.execute(sender.clone(), msg).unwrap();
+    let no_timeout_msg = MsgTransfer {
+        source_port: "transfer".to_string(),
+        source_channel: "channel-0".to_string(),
+        token: Some(IbcCoin {
+            denom: amount.denom.clone(),
+            amount: amount.amount.to_string(),
+        }),
+        sender: sender.to_string(),
+        receiver: receiver.to_string(),
+        timeout_height: None,
+        timeout_timestamp: 0,
+        memo: "".to_string(),
+    };
+
+    #[allow(deprecated)]
+    let no_timeout_msg = CosmosMsg::Any(AnyMsg {
+        type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_string(),
+        value: no_timeout_msg.encode_to_vec().into(),
+    });
+
+    let err = neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), no_timeout_msg)
+        .unwrap_err();
+
+    assert!(format!("{:?}", err).contains("Invalid ibc timeout"));
+
+    // The sequence assigned to the transfer is reverted with the failed tx
+    neutron
+        .borrow_mut()
+        .app
+        .execute_multi(
+            sender.clone(),
+            vec![
+                msg.clone(),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: receiver.to_string(),
+                    amount: vec![Coin::new(1_u128, "unknown")],
+                }),
+            ],
+        )
+        .unwrap_err();
+
+    let response = neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), msg)
+        .unwrap();
+
+    assert_eq!(
+        MsgTransferResponse::decode(response.data.unwrap().as_slice())
+            .unwrap()
+            .sequence,
+        1
+    );
 
     eco.relay_all_packets().unwrap();
 
@@ -261,10 +357,10 @@ fn stargate_ics20_transfer() {
         .borrow()
         .app
         .wrap()
-        .query_balance(&receiver, ibc_denom)
+        .query_balance(&receiver, &ibc_denom)
         .unwrap();
 
-    assert_eq!(balance.amount, amount.amount)
+    assert_eq!(balance.amount, amount.amount);
 }
 
 #[test]
@@ -274,33 +370,30 @@ fn failing_ics20_transfer() {
         .with_ibc(IperIbcModule::default())
         .with_stargate(IperStargateModule::default())
         .with_ibc_app(Ics20)
+        .unwrap()
         .build(no_init)
-        .into_iper_app("neutron");
+        .into_iper_app("neutron")
+        .unwrap();
 
     let osmosis = IperAppBuilder::new("osmo")
         .with_ibc_app(Ics20)
+        .unwrap()
         .build(no_init)
-        .into_iper_app("osmosis");
+        .into_iper_app("osmosis")
+        .unwrap();
 
     let eco = Ecosystem::default()
         .add_app(neutron.clone())
         .add_app(osmosis.clone());
 
-    eco.open_ibc_channel(
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "neutron",
-        ),
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "osmosis",
-        ),
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::from_application(Ics20),
+        IbcPort::from_application(Ics20),
+        IbcOrder::Unordered,
+        "version",
     )
     .unwrap();
 
@@ -334,10 +427,22 @@ fn failing_ics20_transfer() {
         }))
         .unwrap();
 
-    neutron.borrow_mut().app.execute(sender.clone(), msg).unwrap();
+    neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), msg)
+        .unwrap();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_pending();
 
     eco.relay_all_packets().unwrap();
 
+    eco.assert_no_pending();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_timed_out();
+
     let balance = neutron
         .borrow()
         .app
@@ -366,33 +471,30 @@ fn timeout_ics20_transfer() {
         .with_ibc(IperIbcModule::default())
         .with_stargate(IperStargateModule::default())
         .with_ibc_app(Ics20)
+        .unwrap()
         .build(no_init)
-        .into_iper_app("neutron");
+        .into_iper_app("neutron")
+        .unwrap();
 
     let osmosis = IperAppBuilder::new("osmo")
         .with_ibc_app(Ics20)
+        .unwrap()
         .build(no_init)
-        .into_iper_app("osmosis");
+        .into_iper_app("osmosis")
+        .unwrap();
 
     let eco = Ecosystem::default()
         .add_app(neutron.clone())
         .add_app(osmosis.clone());
 
-    eco.open_ibc_channel(
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "neutron",
-        ),
-        IbcChannelCreator::new(
-            IbcPort::from_application(Ics20),
-            IbcOrder::Unordered,
-            "version",
-            "connection_id",
-            "osmosis",
-        ),
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::from_application(Ics20),
+        IbcPort::from_application(Ics20),
+        IbcOrder::Unordered,
+        "version",
     )
     .unwrap();
 
@@ -424,10 +526,25 @@ fn timeout_ics20_transfer() {
         }))
         .unwrap();
 
-    neutron.borrow_mut().app.execute(sender.clone(), msg).unwrap();
+    neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), msg)
+        .unwrap();
+
+    let response = eco.relay_next_packet("neutron").unwrap();
+
+    eco.assert_packet("osmosis", "channel-0", 1)
+        .assert_error_ack_contains(response.assert_err());
 
     eco.relay_all_packets().unwrap();
 
+    eco.assert_no_pending();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_acknowledged()
+        .assert_error_ack_contains(response.assert_err());
+
     let balance = neutron
         .borrow()
         .app
@@ -448,3 +565,813 @@ fn timeout_ics20_transfer() {
 
     assert_eq!(balance.amount, Uint128::zero())
 }
+
+#[test]
+fn snapshot_ics20_transfer() {
+    let env = build_ics20(&ics20_spec("neutron", "osmosis", 1_000_000));
+    let (eco, neutron, osmosis) = (
+        &env.eco,
+        env.chain("neutron").unwrap(),
+        env.chain("osmosis").unwrap(),
+    );
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    let amount = Coin::new(1_000_000_u128, "untrn");
+
+    let snapshot = eco.snapshot();
+
+    let ibc_denom = Ics20Helper::compute_ibc_denom_from_trace("transfer/channel-0/untrn");
+
+    // Branch 1: successful transfer
+    let msg = CosmosMsg::Ibc(IbcMsg::Transfer {
+        channel_id: "channel-0".to_string(),
+        to_address: receiver.to_string(),
+        amount: amount.clone(),
+        timeout: IbcTimeout::with_timestamp(osmosis.borrow().app.block_info().time.plus_seconds(1)),
+        memo: None,
+    });
+
+    neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), msg)
+        .unwrap();
+
+    eco.relay_all_packets().unwrap();
+
+    let balance = osmosis
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&receiver, &ibc_denom)
+        .unwrap();
+
+    assert_eq!(balance.amount, amount.amount);
+
+    // Branch 2: timed out transfer from the same setup
+    eco.restore(snapshot).unwrap();
+
+    let balance = osmosis
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&receiver, &ibc_denom)
+        .unwrap();
+
+    assert_eq!(balance.amount, Uint128::zero());
+
+    let msg = CosmosMsg::Ibc(IbcMsg::Transfer {
+        channel_id: "channel-0".to_string(),
+        to_address: receiver.to_string(),
+        amount: amount.clone(),
+        timeout: IbcTimeout::with_timestamp(
+            osmosis.borrow().app.block_info().time.minus_seconds(1),
+        ),
+        memo: None,
+    });
+
+    neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), msg)
+        .unwrap();
+
+    eco.relay_all_packets().unwrap();
+
+    assert_eq!(
+        eco.packet_status("neutron", "channel-0", 1).unwrap(),
+        PacketStatus::TimedOut { relayed: true }
+    );
+
+    let balance = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&sender, "untrn")
+        .unwrap();
+
+    assert_eq!(balance.amount, amount.amount);
+}
+
+#[test]
+fn replay_ics20_transfer() {
+    let setup = |amount: u128| build_ics20(&ics20_spec("neutron", "osmosis", amount));
+
+    let env = setup(1_000_000);
+    let (eco, neutron, osmosis) = (
+        &env.eco,
+        env.chain("neutron").unwrap(),
+        env.chain("osmosis").unwrap(),
+    );
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    eco.start_recording();
+
+    eco.execute(
+        "neutron",
+        sender,
+        CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+            channel_id: "channel-0".to_string(),
+            to_address: receiver.to_string(),
+            amount: Coin::new(1_000_000_u128, "untrn"),
+            timeout: IbcTimeout::with_timestamp(
+                osmosis.borrow().app.block_info().time.plus_seconds(1),
+            ),
+            memo: None,
+        }),
+    )
+    .unwrap();
+
+    eco.relay_all_packets().unwrap();
+
+    let json = eco.stop_recording().unwrap().to_json().unwrap();
+    let recording = Recording::from_json(&json).unwrap();
+
+    // Execute + receive + ack
+    assert_eq!(recording.steps.len(), 3);
+
+    // Same setup, the replayed steps are not recorded again
+    let env = setup(1_000_000);
+
+    env.eco.start_recording();
+    env.eco.replay(&recording).unwrap();
+
+    assert!(env.eco.stop_recording().unwrap().steps.is_empty());
+
+    // Different setup, the transfer fails
+    let err = setup(0).eco.replay(&recording).unwrap_err();
+    assert!(err.to_string().contains("Replay diverged at step 0"));
+}
+
+#[test]
+fn duplicated_ics20_app() {
+    let err = IperAppBuilder::new("osmo")
+        .with_ibc_app(Ics20)
+        .unwrap()
+        .with_ibc_app(Ics20)
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err.downcast_ref::<IperError>(),
+        Some(&IperError::DuplicatedTypeUrl {
+            type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_string()
+        })
+    );
+}
+
+#[test]
+fn light_client_ics20_transfer() {
+    let mut spec = ics20_spec("neutron", "osmosis", 1_000_000);
+    spec.trusting_period = Some(100);
+
+    let env = build_ics20(&spec);
+    let (eco, neutron, osmosis) = (
+        &env.eco,
+        env.chain("neutron").unwrap(),
+        env.chain("osmosis").unwrap(),
+    );
+
+    let client = eco.light_client("osmosis", "neutron").unwrap();
+    assert_eq!(client.client_id, "07-tendermint-0");
+    assert_eq!(
+        client.latest_height,
+        neutron.borrow().app.block_info().height
+    );
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    eco.execute(
+        "neutron",
+        sender,
+        CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+            channel_id: "channel-0".to_string(),
+            to_address: receiver.to_string(),
+            amount: Coin::new(1_000_000_u128, "untrn"),
+            timeout: IbcTimeout::with_timestamp(
+                osmosis.borrow().app.block_info().time.plus_seconds(1_000),
+            ),
+            memo: None,
+        }),
+    )
+    .unwrap();
+
+    let snapshot = eco.snapshot();
+
+    // Trusting period elapsed on osmosis without updates
+    osmosis
+        .borrow_mut()
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(200));
+
+    let report = eco.relay_all_packets().unwrap();
+
+    assert!(report.is_empty());
+    assert_eq!(report.blocked.len(), 1);
+    assert_eq!(report.blocked[0].chain_id, "neutron");
+    assert_iper_err!(
+        report.blocked[0].reason,
+        IperError::ClientNotActive {
+            status: ClientStatus::Expired,
+            ..
+        }
+    );
+
+    let err = eco.relay_next_packet("neutron").unwrap_err();
+
+    assert_iper_err!(
+        err,
+        IperError::ClientNotActive {
+            status: ClientStatus::Expired,
+            ..
+        }
+    );
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_pending();
+
+    // Misbehaviour of neutron
+    eco.restore(snapshot).unwrap();
+    eco.submit_misbehaviour("osmosis", "neutron").unwrap();
+
+    assert!(eco.relay_all_packets().unwrap().is_empty());
+
+    let err = eco.relay_next_packet("neutron").unwrap_err();
+
+    assert_iper_err!(
+        err,
+        IperError::ClientNotActive {
+            status: ClientStatus::Frozen,
+            ..
+        }
+    );
+
+    // Clients kept alive by the relayer
+    eco.restore(snapshot).unwrap();
+
+    for app in [&neutron, &osmosis] {
+        app.borrow_mut()
+            .app
+            .update_block(|block| block.time = block.time.plus_seconds(50));
+    }
+
+    eco.update_client("osmosis", "neutron").unwrap();
+    eco.update_client("neutron", "osmosis").unwrap();
+
+    osmosis
+        .borrow_mut()
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(50));
+
+    eco.relay_all_packets().unwrap();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_ack_success();
+}
+
+#[test]
+fn connection_ics20_transfer() {
+    let env = build_ics20(&ics20_spec("neutron", "osmosis", 1_000_000));
+    let (eco, neutron, osmosis) = (
+        &env.eco,
+        env.chain("neutron").unwrap(),
+        env.chain("osmosis").unwrap(),
+    );
+
+    let connection = &env.channel("transfer").unwrap().connection;
+
+    assert_eq!(connection.connection_a, "connection-0");
+    assert_eq!(connection.connection_b, "connection-0");
+
+    let end = neutron.borrow().connection("connection-0").unwrap();
+
+    assert_eq!(end.client_id, "07-tendermint-0");
+    assert_eq!(end.state, ConnectionState::Open);
+    assert_eq!(end.counterparty.chain_id, "osmosis");
+    assert_eq!(
+        end.counterparty.connection_id,
+        Some("connection-0".to_string())
+    );
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    eco.execute(
+        "neutron",
+        sender,
+        CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+            channel_id: "channel-0".to_string(),
+            to_address: receiver.to_string(),
+            amount: Coin::new(1_000_000_u128, "untrn"),
+            timeout: IbcTimeout::with_timestamp(
+                osmosis.borrow().app.block_info().time.plus_seconds(1),
+            ),
+            memo: None,
+        }),
+    )
+    .unwrap();
+
+    eco.relay_all_packets().unwrap();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_ack_success();
+
+    // Sides on different connections
+    eco.open_connection("neutron", "osmosis").unwrap();
+
+    let err = eco
+        .open_ibc_channel(
+            IbcChannelCreator::new(
+                IbcPort::from_application(Ics20),
+                IbcOrder::Unordered,
+                "ics20-1",
+                "connection-0",
+                "neutron",
+            ),
+            IbcChannelCreator::new(
+                IbcPort::from_application(Ics20),
+                IbcOrder::Unordered,
+                "ics20-1",
+                "connection-1",
+                "osmosis",
+            ),
+        )
+        .unwrap_err();
+
+    assert_iper_err!(err, IperError::ConnectionMismatch { .. });
+
+    // Not ICS-24 compliant
+    let err = eco
+        .open_ibc_channel(
+            IbcChannelCreator::new(
+                IbcPort::from_application(Ics20),
+                IbcOrder::Unordered,
+                "ics20-1",
+                "conn",
+                "neutron",
+            ),
+            IbcChannelCreator::new(
+                IbcPort::from_application(Ics20),
+                IbcOrder::Unordered,
+                "ics20-1",
+                "connection-1",
+                "osmosis",
+            ),
+        )
+        .unwrap_err();
+
+    assert_iper_err!(err, IperError::InvalidIdentifier { .. });
+
+    // ICS-24 compliant, but not in the connection-{n} format
+    let err = eco
+        .open_ibc_channel(
+            IbcChannelCreator::new(
+                IbcPort::from_application(Ics20),
+                IbcOrder::Unordered,
+                "ics20-1",
+                "connection-a",
+                "neutron",
+            ),
+            IbcChannelCreator::new(
+                IbcPort::from_application(Ics20),
+                IbcOrder::Unordered,
+                "ics20-1",
+                "connection-1",
+                "osmosis",
+            ),
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<IperError>(),
+        Some(&IperError::InvalidIdentifier {
+            identifier: "connection-a".to_string(),
+            reason: "not in the connection-{n} format".to_string(),
+        })
+    );
+}
+
+#[test]
+fn upgrade_ics20_transfer() {
+    let env = build_ics20(&ics20_spec("neutron-1", "osmosis-1", 1_000_000));
+    let (eco, neutron, osmosis) = (
+        &env.eco,
+        env.chain("neutron-1").unwrap(),
+        env.chain("osmosis-1").unwrap(),
+    );
+
+    assert_eq!(osmosis.borrow().revision_number().unwrap(), 1);
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    eco.execute(
+        "neutron-1",
+        sender.clone(),
+        CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+            channel_id: "channel-0".to_string(),
+            to_address: receiver.to_string(),
+            amount: Coin::new(1_000_000_u128, "untrn"),
+            timeout: IbcTimeout::with_block(IbcTimeoutBlock {
+                revision: 1,
+                height: osmosis.borrow().app.block_info().height + 100,
+            }),
+            memo: None,
+        }),
+    )
+    .unwrap();
+
+    // The packet is in flight during the upgrade
+    assert_eq!(eco.upgrade_chain("osmosis-1").unwrap(), 2);
+    assert_eq!(osmosis.borrow().app.block_info().height, 1);
+
+    eco.relay_all_packets().unwrap();
+
+    eco.assert_no_pending();
+
+    eco.assert_packet("neutron-1", "channel-0", 1)
+        .assert_timed_out();
+
+    // The light client of neutron follows the new revision
+    let client = eco.light_client("neutron-1", "osmosis-1").unwrap();
+
+    assert_eq!(client.revision_number, 2);
+    assert_eq!(client.latest_height, 1);
+
+    let balance = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&sender, "untrn")
+        .unwrap();
+
+    assert_eq!(balance.amount, Uint128::new(1_000_000));
+}
+
+#[test]
+fn spec_shared_connection() {
+    let spec = EcosystemSpec::from_json(
+        r#"{
+            "chains": [
+                { "chain_id": "neutron", "prefix": "neutron", "ibc_apps": ["ics20"] },
+                {
+                    "chain_id": "osmosis",
+                    "prefix": "osmo",
+                    "ibc_apps": ["ics20"],
+                    "balances": [{ "account": "sender", "coins": [{ "denom": "uosmo", "amount": "1000000" }] }]
+                }
+            ],
+            "channels": [
+                {
+                    "name": "neutron_osmosis",
+                    "a": { "chain_id": "neutron", "port": { "module": "transfer" } },
+                    "b": { "chain_id": "osmosis", "port": { "module": "transfer" } }
+                },
+                {
+                    "name": "osmosis_neutron",
+                    "a": { "chain_id": "osmosis", "port": { "module": "transfer" } },
+                    "b": { "chain_id": "neutron", "port": { "module": "transfer" } }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let env = spec
+        .build(&SpecRegistry::default().with_ibc_app("ics20", || Ics20))
+        .unwrap();
+
+    let neutron_osmosis = env.channel("neutron_osmosis").unwrap();
+    let osmosis_neutron = env.channel("osmosis_neutron").unwrap();
+
+    // Both channels are opened on the same connection, oriented as declared
+    assert_eq!(osmosis_neutron.connection.chain_a, "osmosis");
+    assert_eq!(
+        osmosis_neutron.connection,
+        neutron_osmosis.connection.reversed()
+    );
+
+    let neutron = env.chain("neutron").unwrap();
+    let osmosis = env.chain("osmosis").unwrap();
+
+    assert_eq!(neutron.borrow().connections().unwrap().len(), 1);
+    assert_eq!(osmosis.borrow().connections().unwrap().len(), 1);
+
+    let sender = osmosis.borrow().app.api().addr_make("sender");
+    let receiver = neutron.borrow().app.api().addr_make("receiver");
+
+    env.eco
+        .execute(
+            "osmosis",
+            sender,
+            CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+                channel_id: osmosis_neutron.channel_a.clone(),
+                to_address: receiver.to_string(),
+                amount: Coin::new(1_000_000_u128, "uosmo"),
+                timeout: IbcTimeout::with_timestamp(
+                    neutron.borrow().app.block_info().time.plus_seconds(1),
+                ),
+                memo: None,
+            }),
+        )
+        .unwrap();
+
+    env.eco.relay_all_packets().unwrap();
+
+    env.eco
+        .assert_packet("osmosis", &osmosis_neutron.channel_a, 1)
+        .assert_ack_success();
+
+    let denom = Ics20Helper::compute_ibc_denom_from_trace(&format!(
+        "transfer/{}/uosmo",
+        osmosis_neutron.channel_b
+    ));
+
+    let balance = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(receiver, denom)
+        .unwrap();
+
+    assert_eq!(balance.amount, Uint128::new(1_000_000));
+}
+
+#[test]
+fn sync_ics20_transfer() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SyncEcosystem<SpecEcosystem>>();
+
+    let eco = SyncEcosystem::new(|| build_ics20(&ics20_spec("neutron", "osmosis", 1_000_000)));
+
+    // Send the transfer from another thread
+    let handle = eco.clone();
+
+    std::thread::spawn(move || {
+        handle
+            .with(|env| {
+                let neutron = env.chain("neutron").unwrap();
+                let osmosis = env.chain("osmosis").unwrap();
+
+                let sender = neutron.borrow().app.api().addr_make("sender");
+                let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+                env.eco
+                    .execute(
+                        "neutron",
+                        sender,
+                        CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+                            channel_id: "channel-0".to_string(),
+                            to_address: receiver.to_string(),
+                            amount: Coin::new(1_000_000_u128, "untrn"),
+                            timeout: IbcTimeout::with_timestamp(
+                                osmosis.borrow().app.block_info().time.plus_seconds(1),
+                            ),
+                            memo: None,
+                        }),
+                    )
+                    .unwrap();
+            })
+            .unwrap();
+    })
+    .join()
+    .unwrap();
+
+    let report = eco
+        .with(|env| env.eco.relay_all_packets().unwrap())
+        .unwrap();
+
+    assert_eq!(report.len(), 2);
+
+    let balance = eco
+        .with(|env| {
+            let osmosis = env.chain("osmosis").unwrap();
+            let receiver = osmosis.borrow().app.api().addr_make("receiver");
+            let denom = Ics20Helper::compute_ibc_denom_from_trace("transfer/channel-0/untrn");
+
+            let balance = osmosis
+                .borrow()
+                .app
+                .wrap()
+                .query_balance(receiver, denom)
+                .unwrap();
+
+            balance.amount
+        })
+        .unwrap();
+
+    assert_eq!(balance, Uint128::new(1_000_000));
+
+    // Panics are resumed on the calling thread and the state stays usable
+    let panic = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        eco.with(|env| {
+            env.eco
+                .assert_packet("neutron", "channel-0", 1)
+                .assert_timed_out();
+        })
+    }));
+
+    assert!(panic.is_err());
+
+    eco.with(|env| env.eco.assert_no_pending()).unwrap();
+}
+
+#[test]
+fn halt_ics20_transfer() {
+    let env = build_ics20(&ics20_spec("neutron", "osmosis", 2_000_000));
+    let (eco, neutron, osmosis) = (
+        &env.eco,
+        env.chain("neutron").unwrap(),
+        env.chain("osmosis").unwrap(),
+    );
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    let transfer = |timeout: u64| {
+        CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+            channel_id: "channel-0".to_string(),
+            to_address: receiver.to_string(),
+            amount: Coin::new(1_000_000_u128, "untrn"),
+            timeout: IbcTimeout::with_timestamp(
+                neutron.borrow().app.block_info().time.plus_seconds(timeout),
+            ),
+            memo: None,
+        })
+    };
+
+    // Paused channel: packets pile up
+    eco.pause_channel("osmosis", "channel-0").unwrap();
+
+    eco.execute("neutron", sender.clone(), transfer(1_000))
+        .unwrap();
+
+    assert!(eco.relay_all_packets().unwrap().is_empty());
+
+    assert_eq!(
+        eco.relay_next_packet("neutron")
+            .unwrap_err()
+            .downcast_ref::<IperError>(),
+        Some(&IperError::ChannelPaused {
+            chain_id: "neutron".to_string(),
+            channel: "channel-0".to_string()
+        })
+    );
+
+    eco.resume_channel("neutron", "channel-0").unwrap();
+
+    eco.relay_all_packets().unwrap();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_ack_success();
+
+    // Halted chain: txs fail and the clock is frozen
+    eco.halt_chain("osmosis").unwrap();
+
+    assert!(osmosis.borrow().is_halted().unwrap());
+
+    assert_eq!(
+        eco.execute(
+            "osmosis",
+            receiver.clone(),
+            CosmosMsg::<Empty>::Bank(BankMsg::Burn { amount: vec![] })
+        )
+        .unwrap_err()
+        .downcast_ref::<IperError>(),
+        Some(&IperError::ChainHalted {
+            chain_id: "osmosis".to_string()
+        })
+    );
+
+    eco.execute("neutron", sender.clone(), transfer(100))
+        .unwrap();
+
+    let osmosis_time = osmosis.borrow().app.block_info().time;
+
+    eco.produce_blocks(20, 6).unwrap();
+
+    assert_eq!(osmosis.borrow().app.block_info().time, osmosis_time);
+
+    assert!(eco.relay_all_packets().unwrap().is_empty());
+
+    assert_eq!(
+        eco.packet_status("neutron", "channel-0", 2).unwrap(),
+        PacketStatus::Pending
+    );
+
+    // On resume the clock catches up and the packet times out
+    eco.resume_chain("osmosis").unwrap();
+
+    assert_eq!(
+        osmosis.borrow().app.block_info().time,
+        neutron.borrow().app.block_info().time
+    );
+
+    eco.relay_all_packets().unwrap();
+
+    eco.assert_no_pending();
+
+    eco.assert_packet("neutron", "channel-0", 2)
+        .assert_timed_out();
+
+    let balance = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&sender, "untrn")
+        .unwrap();
+
+    assert_eq!(balance.amount, Uint128::new(1_000_000));
+}
+
+#[test]
+fn sudo_ics20_params() {
+    let env = build_ics20(&ics20_spec("neutron", "osmosis", 1_000_000));
+    let (eco, neutron, osmosis) = (
+        &env.eco,
+        env.chain("neutron").unwrap(),
+        env.chain("osmosis").unwrap(),
+    );
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    let transfer = CosmosMsg::<Empty>::Ibc(IbcMsg::Transfer {
+        channel_id: "channel-0".to_string(),
+        to_address: receiver.to_string(),
+        amount: Coin::new(1_000_000_u128, "untrn"),
+        timeout: IbcTimeout::with_timestamp(
+            neutron.borrow().app.block_info().time.plus_seconds(1_000),
+        ),
+        memo: None,
+    });
+
+    let update_params = |send_enabled: bool, receive_enabled: bool| IperSudoMsg::IbcApplication {
+        port: "transfer".to_string(),
+        msg: to_json_binary(&Ics20SudoMsg::UpdateParams(Ics20Params {
+            send_enabled,
+            receive_enabled,
+        }))
+        .unwrap(),
+    };
+
+    // Send disabled on neutron
+    eco.sudo("neutron", update_params(false, true))
+        .unwrap()
+        .assert_event(&Event::new("ics20_update_params").add_attribute("send_enabled", "false"));
+
+    let err = eco
+        .execute("neutron", sender.clone(), transfer.clone())
+        .unwrap_err();
+
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("ics20 send is disabled"));
+
+    // Receive disabled on osmosis: the packet is acked with an error and the funds are refunded
+    eco.sudo("neutron", update_params(true, true)).unwrap();
+    eco.sudo("osmosis", update_params(true, false)).unwrap();
+
+    eco.execute("neutron", sender.clone(), transfer).unwrap();
+
+    eco.relay_all_packets().unwrap();
+
+    eco.assert_packet("neutron", "channel-0", 1)
+        .assert_error_ack_contains("ics20 receive is disabled");
+
+    let balance = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&sender, "untrn")
+        .unwrap();
+
+    assert_eq!(balance.amount, Uint128::new(1_000_000));
+
+    assert_eq!(
+        Ics20Params::load(osmosis.borrow().app.storage()).unwrap(),
+        Ics20Params {
+            send_enabled: true,
+            receive_enabled: false
+        }
+    );
+
+    // Ics20 doesn't handle stargate sudo msgs
+    let err = eco
+        .sudo(
+            "osmosis",
+            IperSudoMsg::StargateApplication {
+                name: "ics20".to_string(),
+                msg: Binary::default(),
+            },
+        )
+        .unwrap_err();
+
+    assert_iper_err!(err, IperError::UnsupportedMsg { .. });
+}
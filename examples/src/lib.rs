@@ -1,8 +1,18 @@
 #[cfg(test)]
 mod contract_to_contract;
 #[cfg(test)]
+mod cosmos_sdk;
+#[cfg(test)]
+mod helpers;
+#[cfg(test)]
+mod ibc_application;
+#[cfg(test)]
+mod ibc_core;
+#[cfg(test)]
 mod ics20;
 #[cfg(test)]
+mod middleware;
+#[cfg(test)]
 mod mock_contracts;
 
 #[cfg(test)]
@@ -0,0 +1,285 @@
+use std::{cell::RefCell, rc::Rc};
+
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, AnyMsg, Api, Binary, BlockInfo, Coin, CosmosMsg, Empty,
+    GrpcQuery, IbcOrder, Querier, Storage, Uint128,
+};
+use cw_iper_test::{
+    anyhow::bail,
+    cw_multi_test::{no_init, AppResponse, BankSudo, Executor, SudoMsg},
+    ibc_applications::{IbcHook, Ics20},
+    AppBuilderIperExt, AppExt, AppResult, Ecosystem, IbcPort, IperAppBuilder, IperError,
+    Middleware, MiddlewareResponse, MiddlewareStack, RouterWrapper,
+};
+use cw_storage_plus::Item;
+use ibc_proto::{cosmos::base::v1beta1::Coin as IbcCoin, ibc::apps::transfer::v1::MsgTransfer};
+use prost::{Message, Name};
+
+const TRANSFER_LIMIT: Item<Uint128> = Item::new("transfer_limit");
+const TRANSFERS: Item<u64> = Item::new("transfers");
+
+const MSG_SET_LIMIT: &str = "/limit.v1.MsgSetLimit";
+const QUERY_LIMIT: &str = "/limit.v1.Query/Limit";
+
+/// Middleware limiting the amount of a stargate `MsgTransfer`, with its own msg and query to manage the limit.
+struct TransferLimit;
+
+impl Middleware for TransferLimit {
+    fn mid_msg_type_urls(&self) -> Vec<String> {
+        vec![MSG_SET_LIMIT.to_string()]
+    }
+
+    fn mid_query_type_urls(&self) -> Vec<String> {
+        vec![QUERY_LIMIT.to_string()]
+    }
+
+    fn mid_stargate_msg_before(
+        &self,
+        _api: &dyn Api,
+        _block: &BlockInfo,
+        _sender: Addr,
+        _router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        msg: AnyMsg,
+    ) -> AppResult<MiddlewareResponse<AppResponse, AnyMsg>> {
+        if msg.type_url == MSG_SET_LIMIT {
+            TRANSFER_LIMIT.save(*storage.borrow_mut(), &from_json(&msg.value)?)?;
+            return Ok(MiddlewareResponse::Stop(AppResponse::default()));
+        }
+
+        if msg.type_url == MsgTransfer::type_url() {
+            let transfer = MsgTransfer::decode(msg.value.as_slice())?;
+            let amount: Uint128 = transfer.token.unwrap_or_default().amount.parse()?;
+
+            if let Some(limit) = TRANSFER_LIMIT.may_load(*storage.borrow())? {
+                if amount > limit {
+                    bail!("transfer limit exceeded: {} > {}", amount, limit);
+                }
+            }
+        }
+
+        Ok(MiddlewareResponse::Continue(msg))
+    }
+
+    fn mid_stargate_msg_after(
+        &self,
+        _api: &dyn Api,
+        _block: &BlockInfo,
+        _sender: Addr,
+        _router: &RouterWrapper,
+        storage: Rc<RefCell<&mut dyn Storage>>,
+        original_msg: AnyMsg,
+        _forwarded_msg: AnyMsg,
+        _returning_reponse: AppResponse,
+    ) -> AppResult<AppResponse> {
+        if original_msg.type_url == MsgTransfer::type_url() {
+            let transfers = TRANSFERS.may_load(*storage.borrow())?.unwrap_or_default();
+            TRANSFERS.save(*storage.borrow_mut(), &(transfers + 1))?;
+        }
+
+        Ok(AppResponse::default())
+    }
+
+    fn mid_stargate_query_before(
+        &self,
+        _api: &dyn Api,
+        _block: &BlockInfo,
+        storage: &dyn Storage,
+        _querier: &dyn Querier,
+        request: GrpcQuery,
+    ) -> AppResult<MiddlewareResponse<Binary, GrpcQuery>> {
+        if request.path == QUERY_LIMIT {
+            return Ok(MiddlewareResponse::Stop(to_json_binary(
+                &TRANSFER_LIMIT.may_load(storage)?,
+            )?));
+        }
+
+        Ok(MiddlewareResponse::Continue(request))
+    }
+}
+
+#[test]
+fn middleware_stargate_hooks() {
+    let neutron = IperAppBuilder::new("neutron")
+        .with_ibc_app(MiddlewareStack::new(Ics20).with(TransferLimit))
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("neutron")
+        .unwrap();
+
+    let osmosis = IperAppBuilder::new("osmo")
+        .with_ibc_app(Ics20)
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("osmosis")
+        .unwrap();
+
+    let eco = Ecosystem::default()
+        .add_app(neutron.clone())
+        .add_app(osmosis.clone());
+
+    let connection = eco.open_connection("neutron", "osmosis").unwrap();
+
+    eco.open_ibc_channel_on(
+        &connection,
+        IbcPort::from_application(Ics20),
+        IbcPort::from_application(Ics20),
+        IbcOrder::Unordered,
+        "version",
+    )
+    .unwrap();
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+    let receiver = osmosis.borrow().app.api().addr_make("receiver");
+
+    neutron
+        .borrow_mut()
+        .app
+        .sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: sender.to_string(),
+            amount: vec![Coin::new(1_000_u128, "untrn")],
+        }))
+        .unwrap();
+
+    let transfer_msg = |amount: u128| {
+        CosmosMsg::<Empty>::Any(AnyMsg {
+            type_url: MsgTransfer::type_url(),
+            value: MsgTransfer {
+                source_port: "transfer".to_string(),
+                source_channel: "channel-0".to_string(),
+                token: Some(IbcCoin {
+                    denom: "untrn".to_string(),
+                    amount: amount.to_string(),
+                }),
+                sender: sender.to_string(),
+                receiver: receiver.to_string(),
+                timeout_height: None,
+                timeout_timestamp: osmosis.borrow().app.block_info().time.nanos() + 1,
+                memo: "".to_string(),
+            }
+            .encode_to_vec()
+            .into(),
+        })
+    };
+
+    // Msg registered by the middleware
+    neutron
+        .borrow_mut()
+        .app
+        .execute(
+            sender.clone(),
+            CosmosMsg::Any(AnyMsg {
+                type_url: MSG_SET_LIMIT.to_string(),
+                value: to_json_binary(&Uint128::new(100)).unwrap(),
+            }),
+        )
+        .unwrap();
+
+    // Query registered by the middleware
+    let limit: Option<Uint128> = from_json(
+        neutron
+            .borrow()
+            .app
+            .wrap()
+            .query_grpc(QUERY_LIMIT.to_string(), Binary::default())
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(limit, Some(Uint128::new(100)));
+
+    // Stargate MsgTransfer intercepted before the inner Ics20
+    let err = neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), transfer_msg(101))
+        .unwrap_err();
+
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("transfer limit exceeded: 101 > 100"));
+
+    assert_eq!(
+        TRANSFERS.may_load(neutron.borrow().app.storage()).unwrap(),
+        None
+    );
+
+    neutron
+        .borrow_mut()
+        .app
+        .execute(sender.clone(), transfer_msg(100))
+        .unwrap();
+
+    assert_eq!(TRANSFERS.load(neutron.borrow().app.storage()).unwrap(), 1);
+
+    eco.relay_all_packets().unwrap();
+
+    let balance = neutron
+        .borrow()
+        .app
+        .wrap()
+        .query_balance(&sender, "untrn")
+        .unwrap();
+
+    assert_eq!(balance.amount, Uint128::new(900));
+}
+
+#[test]
+fn middleware_stack() {
+    let stack = MiddlewareStack::new(Ics20)
+        .with(TransferLimit)
+        .with(IbcHook);
+
+    assert_eq!(stack.layers(), vec!["IbcHook", "TransferLimit", "Ics20"]);
+    assert_eq!(
+        stack.call_order(),
+        vec![
+            "IbcHook::before",
+            "TransferLimit::before",
+            "Ics20",
+            "TransferLimit::after",
+            "IbcHook::after"
+        ]
+    );
+
+    // The msg of TransferLimit is already handled by the layer below
+    let err = MiddlewareStack::new(Ics20)
+        .with(TransferLimit)
+        .try_with(TransferLimit)
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err.downcast_ref::<IperError>(),
+        Some(&IperError::DuplicatedTypeUrl {
+            type_url: MSG_SET_LIMIT.to_string()
+        })
+    );
+
+    let neutron = IperAppBuilder::new("neutron")
+        .with_ibc_app(stack)
+        .unwrap()
+        .build(no_init)
+        .into_iper_app("neutron")
+        .unwrap();
+
+    let sender = neutron.borrow().app.api().addr_make("sender");
+
+    neutron
+        .borrow_mut()
+        .app
+        .execute(
+            sender.clone(),
+            CosmosMsg::Any(AnyMsg {
+                type_url: MSG_SET_LIMIT.to_string(),
+                value: to_json_binary(&Uint128::new(100)).unwrap(),
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(
+        TRANSFER_LIMIT.load(neutron.borrow().app.storage()).unwrap(),
+        Uint128::new(100)
+    );
+}
@@ -2,8 +2,8 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
     entry_point, from_json, to_json_binary, Binary, Deps, DepsMut, Env, Ibc3ChannelOpenResponse,
     IbcBasicResponse, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcMsg,
-    IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, MessageInfo,
-    Never, Reply, Response, StdError, StdResult,
+    IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, IbcTimeout,
+    MessageInfo, Never, Reply, Response, StdError, StdResult,
 };
 use cw_iper_test::ibc_applications::IBCLifecycleComplete;
 use cw_storage_plus::Item;
@@ -42,6 +42,8 @@ pub struct MigrateMsg {}
 pub enum CounterPacketData {
     Ok,
     Fail,
+    /// Send a `Ping` packet back to the sender
+    Ping,
 }
 
 #[cw_serde]
@@ -180,10 +182,23 @@ pub fn ibc_channel_connect(
 
 pub fn ibc_packet_receive(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     msg: IbcPacketReceiveMsg,
 ) -> Result<IbcReceiveResponse, Never> {
     println!("\nPacket_received: {:#?}", msg);
+
+    if let Ok(CounterPacketData::Ping) = from_json(&msg.packet.data) {
+        return Ok(
+            IbcReceiveResponse::new(to_json_binary(&CounterAckData::Ok).unwrap()).add_message(
+                IbcMsg::SendPacket {
+                    channel_id: msg.packet.dest.channel_id,
+                    data: to_json_binary(&CounterPacketData::Ping).unwrap(),
+                    timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(1)),
+                },
+            ),
+        );
+    }
+
     let ack = || -> StdResult<_> {
         match from_json::<CounterPacketData>(&msg.packet.data)? {
             CounterPacketData::Ok => {
@@ -193,7 +208,7 @@ pub fn ibc_packet_receive(
                 })?;
                 Ok(CounterAckData::Ok)
             }
-            CounterPacketData::Fail => Ok(CounterAckData::Fail),
+            CounterPacketData::Fail | CounterPacketData::Ping => Ok(CounterAckData::Fail),
         }
     }()
     .unwrap_or(CounterAckData::Fail);